@@ -0,0 +1,14 @@
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+
+    // GIT_SHA wins for builds outside a checkout (e.g. Docker contexts)
+    let sha = std::env::var("GIT_SHA").ok().filter(|s| !s.is_empty()).or_else(|| {
+        let output = Command::new("git").args(["rev-parse", "--short=12", "HEAD"]).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    println!("cargo:rustc-env=GCPIAM_GIT_SHA={}", sha.unwrap_or_else(|| "unknown".to_string()));
+}
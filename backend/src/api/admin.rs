@@ -0,0 +1,118 @@
+//! Maintainer-only endpoints guarded by ADMIN_TOKEN
+
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+
+use super::export::ndjson_response;
+use super::AppState;
+use crate::dataset::{is_snapshot_id, unix_time};
+use crate::error::{ApiError, Result};
+use crate::models::{ApiResponse, QueryLogExportQuery, TopQueriesQuery};
+use crate::query_log::TopQuery;
+use crate::refresh::RefreshStatus;
+
+/// Maximum number of aggregated queries per request
+const MAX_TOP_QUERIES: usize = 1000;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Require `Authorization: Bearer <ADMIN_TOKEN>`, as every endpoint that
+/// reads the query log or changes the live dataset does
+pub(super) fn authorize(req: &HttpRequest, data: &AppState) -> Result<()> {
+    let expected = data
+        .admin_token
+        .as_deref()
+        .ok_or_else(|| ApiError::Unauthorized("Maintainer endpoints are disabled (ADMIN_TOKEN not set)".to_string()))?;
+
+    let provided = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    if provided == Some(expected) {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized("Missing or invalid admin token".to_string()))
+    }
+}
+
+/// Most frequent recent queries, or those that returned no results
+pub async fn top_queries(
+    req: HttpRequest,
+    query: web::Query<TopQueriesQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Vec<TopQuery>>>> {
+    authorize(&req, &data)?;
+
+    if query.limit == 0 || query.limit > MAX_TOP_QUERIES {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_TOP_QUERIES
+        )));
+    }
+
+    Ok(web::Json(ApiResponse::ok(
+        data.query_log.top_queries(query.limit, query.zero_results),
+    )))
+}
+
+/// Outcome counters for the background dataset refresh
+pub async fn refresh_status(req: HttpRequest, data: web::Data<AppState>) -> Result<web::Json<ApiResponse<RefreshStatus>>> {
+    authorize(&req, &data)?;
+    Ok(web::Json(ApiResponse::ok(data.refresh.status())))
+}
+
+/// Recorded searches between two days (inclusive) as NDJSON, oldest first
+pub async fn export_query_log(
+    req: HttpRequest,
+    query: web::Query<QueryLogExportQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    authorize(&req, &data)?;
+
+    let (from, to) = export_range(query.from.as_deref(), query.to.as_deref())?;
+    let query_log = data.clone();
+    let records = web::block(move || query_log.query_log.export(from, to))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+        .map_err(|e| ApiError::InternalError(format!("Failed to read query log: {}", e)))?;
+    Ok(ndjson_response(records))
+}
+
+/// Half-open `[from, to)` Unix-second range covering the days `from`
+/// through `to` inclusive, unbounded on a side left out
+fn export_range(from: Option<&str>, to: Option<&str>) -> Result<(u64, u64)> {
+    let from = from.map(day_start).transpose()?.unwrap_or(0);
+    let to = to.map(day_start).transpose()?.map_or(u64::MAX, |start| start + SECONDS_PER_DAY);
+    if from >= to {
+        return Err(ApiError::BadRequest("'from' must not be after 'to'".to_string()));
+    }
+    Ok((from, to))
+}
+
+/// Unix seconds at the start of a YYYY-MM-DD day (UTC)
+fn day_start(day: &str) -> Result<u64> {
+    is_snapshot_id(day)
+        .then(|| unix_time(&format!("{}T00:00:00", day)))
+        .flatten()
+        .and_then(|secs| u64::try_from(secs).ok())
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid date '{}' (expected YYYY-MM-DD)", day)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_range_includes_the_whole_last_day() {
+        let june_1 = 1_717_200_000;
+        assert_eq!(export_range(Some("2024-06-01"), Some("2024-06-01")).unwrap(), (june_1, june_1 + SECONDS_PER_DAY));
+        assert_eq!(export_range(Some("2024-06-01"), Some("2024-06-02")).unwrap(), (june_1, june_1 + 2 * SECONDS_PER_DAY));
+        assert_eq!(export_range(None, None).unwrap(), (0, u64::MAX));
+        assert_eq!(export_range(Some("2024-06-01"), None).unwrap(), (june_1, u64::MAX));
+
+        assert!(export_range(Some("2024-06-02"), Some("2024-06-01")).is_err());
+        assert!(export_range(Some("2024-13-01"), None).is_err());
+        assert!(export_range(None, Some("June 1")).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Catalog analytics computed from the index
+
+use actix_web::web;
+
+use super::AppState;
+use crate::error::{ApiError, Result};
+use crate::models::{AnalyticsQuery, ApiResponse, GrantedPermission, RankedRole, SingleRolePermission};
+use crate::search::{SortField, SortOrder};
+
+/// Maximum page size for analytics listings
+const MAX_ANALYTICS_LIMIT: usize = 1000;
+
+fn validate(query: &AnalyticsQuery) -> Result<()> {
+    if query.limit == 0 || query.limit > MAX_ANALYTICS_LIMIT {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_ANALYTICS_LIMIT
+        )));
+    }
+    Ok(())
+}
+
+/// Roles with the most permissions
+pub async fn largest_roles(
+    query: web::Query<AnalyticsQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Vec<RankedRole>>>> {
+    validate(&query)?;
+
+    let roles = data.with_dataset(query.dataset.as_deref(), |engine| {
+        engine
+            .list_roles(Some((SortField::PermissionCount, SortOrder::Desc)))
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit)
+            .map(|role| RankedRole {
+                name: role.name.clone(),
+                title: role.title.clone(),
+                stage: role.stage.clone(),
+                permission_count: role.included_permissions.len(),
+            })
+            .collect()
+    })?;
+
+    Ok(web::Json(ApiResponse::ok(roles)))
+}
+
+/// Permissions granted by the most roles
+pub async fn most_granted_permissions(
+    query: web::Query<AnalyticsQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Vec<GrantedPermission>>>> {
+    validate(&query)?;
+
+    let permissions = data.with_dataset(query.dataset.as_deref(), |engine| {
+        engine
+            .most_granted_permissions()
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit)
+            .map(|perm| GrantedPermission {
+                name: perm.name.clone(),
+                role_count: perm.granted_by_roles.len(),
+            })
+            .collect()
+    })?;
+
+    Ok(web::Json(ApiResponse::ok(permissions)))
+}
+
+/// Permissions that only a single role grants
+pub async fn single_role_permissions(
+    query: web::Query<AnalyticsQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Vec<SingleRolePermission>>>> {
+    validate(&query)?;
+
+    let permissions = data.with_dataset(query.dataset.as_deref(), |engine| {
+        engine
+            .single_role_permissions()
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit)
+            .filter_map(|perm| {
+                let role = engine.role_summary(perm.granted_by_roles.first()?)?;
+                Some(SingleRolePermission {
+                    name: perm.name.clone(),
+                    role,
+                })
+            })
+            .collect()
+    })?;
+
+    Ok(web::Json(ApiResponse::ok(permissions)))
+}
@@ -0,0 +1,110 @@
+//! Custom role ingestion
+
+use actix_web::{web, HttpRequest};
+use std::collections::HashSet;
+
+use super::admin::authorize;
+use super::AppState;
+use crate::error::{ApiError, Result};
+use crate::models::{ApiResponse, CustomRolesData, CustomRolesRequest};
+use crate::search::builder::validate_role;
+use crate::search::engine::is_custom_role_name;
+
+/// Upload custom role definitions and make them searchable
+///
+/// They join the shared live dataset and outlive refreshes, so uploads
+/// need the admin token.
+pub async fn create_custom_roles(
+    req: HttpRequest,
+    body: web::Json<CustomRolesRequest>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<CustomRolesData>>> {
+    authorize(&req, &data)?;
+    let request = body.into_inner();
+    if request.roles.is_empty() {
+        return Err(ApiError::BadRequest("At least one role is required".to_string()));
+    }
+
+    // Validate the whole batch before touching the index
+    let mut seen = HashSet::new();
+    for (position, role) in request.roles.iter().enumerate() {
+        if !is_custom_role_name(&role.name) {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid custom role name '{}': expected projects/{{id}}/roles/{{id}} or organizations/{{id}}/roles/{{id}}",
+                role.name
+            )));
+        }
+        if role.title.trim().is_empty() {
+            return Err(ApiError::BadRequest(format!("Role '{}' is missing a title", role.name)));
+        }
+        validate_role(position, &role.name, &role.included_permissions)?;
+        if !seen.insert(role.name.as_str()) {
+            return Err(ApiError::BadRequest(format!("Role '{}' appears more than once", role.name)));
+        }
+    }
+
+    // Index into a copy of the live engine; searches keep using the
+    // current one until the copy is swapped in
+    let names = data.update_live(|live| {
+        let engine = &mut live.engine;
+        if let Some(existing) = request.roles.iter().find(|r| engine.has_role(&r.name)) {
+            return Err(ApiError::Conflict(format!("Role '{}' already exists", existing.name)));
+        }
+
+        let names: Vec<String> = request.roles.iter().map(|r| r.name.clone()).collect();
+        for role in request.roles {
+            engine.index_custom_role(role.name, role.title, role.description, role.stage, role.included_permissions);
+        }
+        engine.finalize();
+        Ok(names)
+    })?;
+
+    Ok(web::Json(ApiResponse::ok(CustomRolesData {
+        indexed: names.len(),
+        names,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Dataset;
+    use crate::models::CustomRoleInput;
+    use actix_web::test::TestRequest;
+    use std::collections::BTreeMap;
+
+    fn upload() -> web::Json<CustomRolesRequest> {
+        web::Json(CustomRolesRequest {
+            roles: vec![CustomRoleInput {
+                name: "projects/acme/roles/reader".to_string(),
+                title: "Reader".to_string(),
+                description: String::new(),
+                stage: "GA".to_string(),
+                included_permissions: vec!["storage.objects.get".to_string()],
+            }],
+        })
+    }
+
+    #[actix_web::test]
+    async fn uploads_without_the_admin_token_are_unauthorized() {
+        let data = web::Data::new(AppState::new(Dataset::empty(), BTreeMap::new()).with_admin_token(Some("secret".to_string())));
+        let missing = TestRequest::default().to_http_request();
+        let wrong = TestRequest::default().insert_header(("Authorization", "Bearer guess")).to_http_request();
+        for req in [missing, wrong] {
+            let result = create_custom_roles(req, upload(), data.clone()).await;
+            assert!(matches!(result, Err(ApiError::Unauthorized(_))));
+        }
+        assert!(!data.live().engine.has_role("projects/acme/roles/reader"));
+
+        let req = TestRequest::default().insert_header(("Authorization", "Bearer secret")).to_http_request();
+        let indexed = create_custom_roles(req, upload(), data.clone()).await.unwrap();
+        assert_eq!(indexed.data.as_ref().unwrap().indexed, 1);
+        assert!(data.live().engine.has_role("projects/acme/roles/reader"));
+
+        // No configured token disables uploads outright
+        let disabled = web::Data::new(AppState::new(Dataset::empty(), BTreeMap::new()));
+        let req = TestRequest::default().insert_header(("Authorization", "Bearer secret")).to_http_request();
+        let result = create_custom_roles(req, upload(), disabled).await;
+        assert!(matches!(result, Err(ApiError::Unauthorized(_))));
+    }
+}
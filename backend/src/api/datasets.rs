@@ -0,0 +1,65 @@
+//! Dataset snapshot listing and version headers
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+
+use super::AppState;
+use crate::dataset::LATEST;
+use crate::models::{ApiResponse, DatasetInfo, DatasetQuery};
+
+/// Response header carrying the `last_updated` of the dataset that answered
+pub const DATASET_VERSION_HEADER: &str = "x-dataset-version";
+
+/// Tag responses with the version of the dataset they were served from
+///
+/// That is the `?dataset=` snapshot when one is requested, else the live
+/// dataset as of the start of the request, so clients can notice a reload
+/// between two requests of the same session.
+pub async fn dataset_version(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let version = req.app_data::<web::Data<AppState>>().and_then(|data| {
+        let dataset = web::Query::<DatasetQuery>::from_query(req.query_string())
+            .ok()
+            .and_then(|q| q.into_inner().dataset);
+        match dataset.as_deref() {
+            None | Some(LATEST) => data.live().last_updated.clone(),
+            Some(id) => data.snapshots.get(id).and_then(|s| s.last_updated.clone()),
+        }
+    });
+
+    let mut res = next.call(req).await?;
+    if let Some(value) = version.and_then(|v| HeaderValue::from_str(&v).ok()) {
+        res.headers_mut().insert(HeaderName::from_static(DATASET_VERSION_HEADER), value);
+    }
+    Ok(res)
+}
+
+/// List the live dataset followed by every loaded snapshot, newest first
+pub async fn list_datasets(data: web::Data<AppState>) -> web::Json<ApiResponse<Vec<DatasetInfo>>> {
+    let live = data.live();
+    let (total_permissions, total_roles) = live.engine.stats();
+
+    let mut datasets = vec![DatasetInfo {
+        id: LATEST.to_string(),
+        last_updated: live.last_updated.clone(),
+        total_permissions,
+        total_roles,
+    }];
+
+    datasets.extend(data.snapshots.iter().rev().map(|(id, snapshot)| {
+        let (total_permissions, total_roles) = snapshot.engine.stats();
+        DatasetInfo {
+            id: id.clone(),
+            last_updated: snapshot.last_updated.clone(),
+            total_permissions,
+            total_roles,
+        }
+    }));
+
+    web::Json(ApiResponse::ok(datasets))
+}
@@ -0,0 +1,113 @@
+//! Newline-delimited JSON exports of the full catalog
+
+use actix_web::{web, HttpResponse};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use super::AppState;
+use crate::dataset::{Dataset, LATEST};
+use crate::error::{ApiError, Result};
+use crate::models::ListRequest;
+use crate::search::SearchEngine;
+
+/// Number of NDJSON lines sent per streamed chunk
+const EXPORT_CHUNK_SIZE: usize = 256;
+
+/// Serialized chunks buffered ahead of a slow client
+const EXPORT_BUFFERED_CHUNKS: usize = 4;
+
+/// Export all permissions as newline-delimited JSON
+pub async fn export_permissions(
+    query: web::Query<ListRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let source = ExportSource::resolve(&data, query.dataset.as_deref())?;
+    let sort = query.sort();
+    Ok(stream_catalog(source, move |engine| engine.list_permissions(sort)))
+}
+
+/// Export all roles as newline-delimited JSON
+pub async fn export_roles(
+    query: web::Query<ListRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let source = ExportSource::resolve(&data, query.dataset.as_deref())?;
+    let sort = query.sort();
+    Ok(stream_catalog(source, move |engine| engine.list_roles(sort)))
+}
+
+/// Dataset an export reads from, held until its stream finishes so a
+/// reload mid-export doesn't pull the catalog out from under it
+enum ExportSource {
+    Live(Arc<Dataset>),
+    Snapshot(web::Data<AppState>, String),
+}
+
+impl ExportSource {
+    /// The live dataset for `None` or "latest", otherwise a stored snapshot
+    fn resolve(data: &web::Data<AppState>, dataset: Option<&str>) -> Result<Self> {
+        match dataset {
+            None | Some(LATEST) => Ok(ExportSource::Live(data.live())),
+            Some(id) if data.snapshots.contains_key(id) => Ok(ExportSource::Snapshot(data.clone(), id.to_string())),
+            Some(id) => Err(ApiError::NotFound(format!("Unknown dataset '{}'", id))),
+        }
+    }
+
+    fn engine(&self) -> &SearchEngine {
+        match self {
+            ExportSource::Live(live) => &live.engine,
+            ExportSource::Snapshot(data, id) => &data.snapshots[id].engine,
+        }
+    }
+}
+
+/// Stream `items` of the source's engine as NDJSON
+///
+/// Items are serialized straight from the engine on the blocking pool, a
+/// few chunks ahead of the client, so only references to them are
+/// collected (for ordering) rather than copies.
+fn stream_catalog<T, F>(source: ExportSource, items: F) -> HttpResponse
+where
+    T: Serialize,
+    F: for<'a> FnOnce(&'a SearchEngine) -> Vec<&'a T> + Send + 'static,
+{
+    let (tx, mut rx) = mpsc::channel(EXPORT_BUFFERED_CHUNKS);
+    actix_web::rt::task::spawn_blocking(move || {
+        for chunk in items(source.engine()).chunks(EXPORT_CHUNK_SIZE) {
+            // A send fails once the client has gone away
+            if tx.blocking_send(ndjson_chunk(chunk)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let body = stream::poll_fn(move |cx| rx.poll_recv(cx))
+        .map(|chunk| chunk.map_err(actix_web::error::ErrorInternalServerError));
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body)
+}
+
+/// Stream items as NDJSON using chunked transfer encoding
+pub(super) fn ndjson_response<T: Serialize + 'static>(items: Vec<T>) -> HttpResponse {
+    let body = stream::iter(items)
+        .chunks(EXPORT_CHUNK_SIZE)
+        .map(|chunk| ndjson_chunk(&chunk).map_err(actix_web::error::ErrorInternalServerError));
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body)
+}
+
+/// One NDJSON line per item
+fn ndjson_chunk<T: Serialize>(items: &[T]) -> serde_json::Result<web::Bytes> {
+    let mut buf = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut buf, item)?;
+        buf.push(b'\n');
+    }
+    Ok(web::Bytes::from(buf))
+}
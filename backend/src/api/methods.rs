@@ -0,0 +1,72 @@
+//! HEAD and OPTIONS support for API routes
+//!
+//! Read-only routes are registered with `get()`, which also matches HEAD so
+//! the same handler runs and actix drops the body while keeping
+//! Content-Length. The `method_headers` middleware answers plain OPTIONS
+//! requests with an `Allow` header (CORS preflights are handled by the CORS
+//! middleware before reaching it) and adds an ETag to buffered GET/HEAD
+//! responses.
+
+use actix_web::body::{self, BodySize, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{guard, web, Error, HttpResponse, Route};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Paths that only accept POST
+const POST_ONLY_PATHS: &[&str] = &["/api/v1/custom-roles", "/api/v1/check-access", "/api/v1/roles/granting-all"];
+
+/// Route matching GET and HEAD
+pub fn get() -> Route {
+    web::route().guard(guard::Any(guard::Get()).or(guard::Head()))
+}
+
+/// Methods accepted by the route at `path`
+pub fn allowed_methods(path: &str) -> &'static str {
+    if POST_ONLY_PATHS.contains(&path) {
+        "POST, OPTIONS"
+    } else {
+        "GET, HEAD, OPTIONS"
+    }
+}
+
+/// Answer OPTIONS requests and attach ETags to GET/HEAD responses
+pub async fn method_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if req.method() == Method::OPTIONS {
+        let allow = allowed_methods(req.path());
+        let response = HttpResponse::NoContent()
+            .insert_header((header::ALLOW, allow))
+            .finish();
+        return Ok(req.into_response(response).map_into_boxed_body());
+    }
+
+    let cacheable = req.method() == Method::GET || req.method() == Method::HEAD;
+    let res = next.call(req).await?;
+
+    // Streaming responses (NDJSON exports) are passed through untouched
+    if !cacheable
+        || !res.status().is_success()
+        || !matches!(res.response().body().size(), BodySize::Sized(_))
+    {
+        return Ok(res.map_into_boxed_body());
+    }
+
+    let (req, res) = res.into_parts();
+    let (mut res, body) = res.into_parts();
+    let bytes = body::to_bytes(body).await.map_err(Into::into)?;
+
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{:016x}\"", hasher.finish())) {
+        res.headers_mut().insert(header::ETAG, etag);
+    }
+
+    let res = res.set_body(bytes).map_into_boxed_body();
+    Ok(ServiceResponse::new(req, res))
+}
@@ -0,0 +1,207 @@
+//! REST API handlers and route configuration
+//!
+//! Every JSON endpoint returns an `ApiResponse<T>` envelope; failures are
+//! reported through `ApiError` so clients always see the same shape.
+
+pub mod admin;
+pub mod analytics;
+pub mod custom_roles;
+pub mod datasets;
+pub mod export;
+pub mod methods;
+pub mod pages;
+pub mod permissions;
+pub mod roles;
+pub mod search;
+pub mod status;
+
+use actix_web::web;
+use arc_swap::ArcSwap;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::dataset::{Dataset, LATEST};
+use crate::error::{ApiError, Result};
+use crate::query_log::{QueryLog, DEFAULT_CAPACITY};
+use crate::refresh::RefreshMetrics;
+use crate::search::{Popularity, SearchEngine};
+
+/// How long query-log popularity is reused before being recomputed
+pub const POPULARITY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Application state holding the live search engine and historical snapshots
+pub struct AppState {
+    /// The live dataset, swapped whole on reload so in-flight requests keep
+    /// reading the one they loaded
+    live: ArcSwap<Dataset>,
+    /// Serializes changes to `live` so concurrent writers don't lose updates
+    live_writer: Mutex<()>,
+    pub snapshots: BTreeMap<String, Dataset>,
+    pub query_log: QueryLog,
+    /// Bearer token required by /api/v1/admin/* and custom role uploads
+    /// (both disabled when unset)
+    pub admin_token: Option<String>,
+    pub config: Config,
+    pub refresh: RefreshMetrics,
+    /// Searches abandoned after `Config::search_timeout`
+    pub search_timeouts: AtomicU64,
+    /// Popularity from the query log and when it was computed
+    popularity: Mutex<Option<(Instant, Arc<Popularity>)>>,
+}
+
+impl AppState {
+    pub fn new(live: Dataset, snapshots: BTreeMap<String, Dataset>) -> Self {
+        AppState {
+            live: ArcSwap::from_pointee(live),
+            live_writer: Mutex::new(()),
+            snapshots,
+            query_log: QueryLog::new(DEFAULT_CAPACITY),
+            admin_token: None,
+            config: Config::default(),
+            refresh: RefreshMetrics::default(),
+            search_timeouts: AtomicU64::new(0),
+            popularity: Mutex::new(None),
+        }
+    }
+
+    pub fn with_query_log(mut self, query_log: QueryLog) -> Self {
+        self.query_log = query_log;
+        self
+    }
+
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token;
+        self
+    }
+
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The current live dataset
+    ///
+    /// Handlers should load it once per request: a reload swaps in a new
+    /// dataset without touching the one already returned.
+    pub fn live(&self) -> Arc<Dataset> {
+        self.live.load_full()
+    }
+
+    /// Popularity of roles and permissions in the query log, for
+    /// `RankingConfig::popularity_boost` (`None` while the boost is off)
+    ///
+    /// Recomputed at most every `POPULARITY_TTL`, so a burst of identical
+    /// searches can't reorder results for everyone immediately.
+    pub fn popularity(&self) -> Option<Arc<Popularity>> {
+        if self.config.ranking.popularity_boost == 0.0 {
+            return None;
+        }
+        let mut cached = self.popularity.lock().unwrap();
+        if let Some((computed, popularity)) = cached.as_ref() {
+            if computed.elapsed() < POPULARITY_TTL {
+                return Some(popularity.clone());
+            }
+        }
+        let top = self.query_log.top_queries(usize::MAX, false);
+        let popularity = Arc::new(self.live().engine.popularity(top.iter().map(|q| (q.query.as_str(), q.count))));
+        *cached = Some((Instant::now(), popularity.clone()));
+        Some(popularity)
+    }
+
+    /// Replace the live dataset, keeping uploaded custom roles it lacks
+    pub fn replace_live(&self, mut live: Dataset) {
+        let _writer = self.live_writer.lock().unwrap();
+        let current = self.live.load();
+        let custom: Vec<_> = current.engine.all_roles().filter(|r| r.custom && !live.engine.has_role(&r.name)).cloned().collect();
+        if !custom.is_empty() {
+            for role in custom {
+                live.engine.index_custom_role(role.name, role.title, role.description, role.stage, role.included_permissions);
+            }
+            live.engine.finalize();
+        }
+        self.live.store(Arc::new(live));
+    }
+
+    /// Apply `f` to a copy of the live dataset and swap the copy in if it
+    /// succeeds
+    pub fn update_live<R>(&self, f: impl FnOnce(&mut Dataset) -> Result<R>) -> Result<R> {
+        let _writer = self.live_writer.lock().unwrap();
+        let mut live = Dataset::clone(&self.live.load());
+        let result = f(&mut live)?;
+        self.live.store(Arc::new(live));
+        Ok(result)
+    }
+
+    /// Run `f` against the requested dataset (the live index when `None` or "latest")
+    pub fn with_dataset<R>(&self, dataset: Option<&str>, f: impl FnOnce(&SearchEngine) -> R) -> Result<R> {
+        match dataset {
+            None | Some(LATEST) => Ok(f(&self.live().engine)),
+            Some(id) => self
+                .snapshots
+                .get(id)
+                .map(|snapshot| f(&snapshot.engine))
+                .ok_or_else(|| ApiError::NotFound(format!("Unknown dataset '{}'", id))),
+        }
+    }
+}
+
+/// Register all API and page routes
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(
+        web::QueryConfig::default()
+            .error_handler(|err, _| ApiError::BadRequest(err.to_string()).into()),
+    )
+    .app_data(
+        web::JsonConfig::default()
+            .error_handler(|err, _| ApiError::BadRequest(err.to_string()).into()),
+    )
+    // Health check
+    .route("/api/v1/health", methods::get().to(status::health_check))
+    .route("/api/v1/version", methods::get().to(status::version))
+    // Search endpoint
+    .route("/api/v1/search", methods::get().to(search::search))
+    // Stats endpoint
+    .route("/api/v1/stats", methods::get().to(status::stats))
+    // Catalog analytics
+    .route("/api/v1/analytics/largest-roles", methods::get().to(analytics::largest_roles))
+    .route("/api/v1/analytics/most-granted-permissions", methods::get().to(analytics::most_granted_permissions))
+    .route("/api/v1/analytics/single-role-permissions", methods::get().to(analytics::single_role_permissions))
+    // Maintainer endpoints
+    .route("/api/v1/admin/top-queries", methods::get().to(admin::top_queries))
+    .route("/api/v1/admin/refresh", methods::get().to(admin::refresh_status))
+    .route("/api/v1/admin/query-log", methods::get().to(admin::export_query_log))
+    // Dataset snapshots
+    .route("/api/v1/datasets", methods::get().to(datasets::list_datasets))
+    // Recently deleted roles
+    .route("/api/v1/deleted-roles", methods::get().to(roles::deleted_roles))
+    // Roles covering a whole permission set (before the role detail routes)
+    .route("/api/v1/roles/granting-all", web::post().to(roles::roles_granting_all))
+    // Detail endpoints (sub-resources first, role names contain slashes)
+    .route("/api/v1/roles/{name:.+}/history", methods::get().to(roles::role_history))
+    .route("/api/v1/roles/{name:.+}/similar", methods::get().to(roles::similar_roles))
+    .route("/api/v1/roles/{name:.+}", methods::get().to(roles::get_role))
+    .route("/api/v1/permissions/expand", methods::get().to(permissions::expand_permissions))
+    .route("/api/v1/permissions/{name}", methods::get().to(permissions::get_permission))
+    // NDJSON exports
+    .route("/api/v1/export/permissions", methods::get().to(export::export_permissions))
+    .route("/api/v1/export/roles", methods::get().to(export::export_roles))
+    // Custom role ingestion
+    .route("/api/v1/custom-roles", web::post().to(custom_roles::create_custom_roles))
+    // Access checks
+    .route("/api/v1/check-access", web::post().to(permissions::check_access))
+    // Static pages for SEO
+    .route("/permissions/{name:.*}", methods::get().to(pages::serve_permission_page))
+    .route("/roles/{name:.*}", methods::get().to(pages::serve_role_page))
+    .route("/sitemap.xml", methods::get().to(pages::serve_sitemap))
+    .route("/{part:sitemap-[0-9]+\\.xml}", methods::get().to(pages::serve_sitemap_part))
+    // Catch all
+    .default_service(web::route().to(not_found));
+}
+
+/// Not found handler
+async fn not_found() -> Result<web::Json<()>> {
+    Err(ApiError::NotFound("Endpoint not found".to_string()))
+}
@@ -0,0 +1,72 @@
+//! Server-rendered static pages for SEO
+
+use actix_web::{web, HttpResponse, http::header};
+use std::fs;
+use std::path::PathBuf;
+
+/// Serve permission static page
+pub async fn serve_permission_page(path: web::Path<String>) -> HttpResponse {
+    let perm_name = path.into_inner();
+    let static_dir = std::env::var("STATIC_DIR")
+        .unwrap_or_else(|_| "../data/static".to_string());
+
+    // Convert permission name to filename (replace / with _)
+    let filename = format!("{}.html", perm_name.replace('/', "_"));
+    let filepath = PathBuf::from(&static_dir).join("permissions").join(&filename);
+
+    match fs::read_to_string(&filepath) {
+        Ok(content) => HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "text/html; charset=utf-8"))
+            .body(content),
+        Err(_) => HttpResponse::NotFound()
+            .insert_header((header::CONTENT_TYPE, "text/html; charset=utf-8"))
+            .body(format!(r#"<!DOCTYPE html>
+<html><head><title>Permission Not Found</title></head>
+<body><h1>Permission not found: {}</h1><p><a href="/">Back to search</a></p></body></html>"#, perm_name))
+    }
+}
+
+/// Serve role static page
+pub async fn serve_role_page(path: web::Path<String>) -> HttpResponse {
+    let role_name = path.into_inner();
+    let static_dir = std::env::var("STATIC_DIR")
+        .unwrap_or_else(|_| "../data/static".to_string());
+
+    // Convert role name to filename (replace / with _)
+    let filename = format!("{}.html", role_name.replace('/', "_"));
+    let filepath = PathBuf::from(&static_dir).join("roles").join(&filename);
+
+    match fs::read_to_string(&filepath) {
+        Ok(content) => HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "text/html; charset=utf-8"))
+            .body(content),
+        Err(_) => HttpResponse::NotFound()
+            .insert_header((header::CONTENT_TYPE, "text/html; charset=utf-8"))
+            .body(format!(r#"<!DOCTYPE html>
+<html><head><title>Role Not Found</title></head>
+<body><h1>Role not found: {}</h1><p><a href="/">Back to search</a></p></body></html>"#, role_name))
+    }
+}
+
+/// Serve sitemap.xml (a plain sitemap, or a sitemap index on large sites)
+pub async fn serve_sitemap() -> HttpResponse {
+    read_sitemap("sitemap.xml")
+}
+
+/// Serve a numbered sitemap-N.xml referenced from the sitemap index
+pub async fn serve_sitemap_part(path: web::Path<String>) -> HttpResponse {
+    read_sitemap(&path.into_inner())
+}
+
+fn read_sitemap(filename: &str) -> HttpResponse {
+    let static_dir = std::env::var("STATIC_DIR")
+        .unwrap_or_else(|_| "../data/static".to_string());
+    let filepath = PathBuf::from(&static_dir).join(filename);
+
+    match fs::read_to_string(&filepath) {
+        Ok(content) => HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "application/xml; charset=utf-8"))
+            .body(content),
+        Err(_) => HttpResponse::NotFound().body("Sitemap not found")
+    }
+}
@@ -0,0 +1,147 @@
+//! Permission detail, wildcard expansion and access check endpoints
+
+use actix_web::web;
+
+use super::AppState;
+use crate::error::{ApiError, Result};
+use crate::models::{ApiResponse, CheckAccessRequest, DatasetQuery, ExpandQuery, ExpandedPermissions, PermissionDetail};
+use crate::search::engine::AccessCheck;
+
+/// Longest accepted wildcard pattern
+const MAX_PATTERN_LENGTH: usize = 200;
+
+/// Most roles accepted by one access check
+const MAX_HELD_ROLES: usize = 500;
+
+/// Get a single permission with every role that grants it
+pub async fn get_permission(
+    path: web::Path<String>,
+    query: web::Query<DatasetQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<PermissionDetail>>> {
+    let name = path.into_inner();
+
+    let detail = data.with_dataset(query.dataset.as_deref(), |engine| {
+        engine.get_permission(&name).map(|perm| PermissionDetail {
+            name: perm.name.clone(),
+            service: perm.service.clone(),
+            provider: perm.provider.clone(),
+            resource: perm.resource.clone(),
+            action: perm.action.clone(),
+            description: perm.description.clone(),
+            granted_by_roles: perm
+                .granted_by_roles
+                .iter()
+                .filter_map(|role| engine.role_summary(role))
+                .collect(),
+        })
+    })?;
+
+    detail
+        .map(|detail| web::Json(ApiResponse::ok(detail)))
+        .ok_or_else(|| ApiError::NotFound(format!("Permission not found: {}", name)))
+}
+
+/// Expand a wildcard pattern (`compute.instances.*`, `*.setIamPolicy`) into
+/// the concrete permissions it matches
+pub async fn expand_permissions(
+    query: web::Query<ExpandQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<ExpandedPermissions>>> {
+    let pattern = query.pattern.trim();
+    if pattern.is_empty() {
+        return Err(ApiError::BadRequest("Query parameter 'pattern' is required".to_string()));
+    }
+    if pattern.len() > MAX_PATTERN_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "Pattern too long (max {} characters)",
+            MAX_PATTERN_LENGTH
+        )));
+    }
+
+    let permissions = data.with_dataset(query.dataset.as_deref(), |engine| {
+        engine
+            .expand_permissions(pattern)
+            .into_iter()
+            .map(|perm| perm.name.clone())
+            .collect::<Vec<_>>()
+    })?;
+
+    Ok(web::Json(ApiResponse::ok(ExpandedPermissions {
+        pattern: pattern.to_string(),
+        count: permissions.len(),
+        permissions,
+    })))
+}
+
+/// Whether any of a member's roles grants a permission, and which
+pub async fn check_access(
+    body: web::Json<CheckAccessRequest>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<AccessCheck>>> {
+    let request = body.into_inner();
+    let permission = request.permission.trim();
+    if permission.is_empty() {
+        return Err(ApiError::BadRequest("A permission is required".to_string()));
+    }
+    if request.roles.is_empty() || request.roles.len() > MAX_HELD_ROLES {
+        return Err(ApiError::BadRequest(format!(
+            "Between 1 and {} roles are required",
+            MAX_HELD_ROLES
+        )));
+    }
+
+    data.with_dataset(request.dataset.as_deref(), |engine| {
+        engine
+            .get_permission(permission)
+            .map(|_| engine.check_access(&request.roles, permission))
+    })?
+    .map(|check| web::Json(ApiResponse::ok(check)))
+    .ok_or_else(|| ApiError::NotFound(format!("Permission not found: {}", permission)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Dataset;
+    use crate::search::engine::SearchEngine;
+    use std::collections::BTreeMap;
+
+    #[actix_web::test]
+    async fn permission_detail_carries_scraped_fields() {
+        let data: gcpiam_core::IamDataFile = serde_json::from_value(serde_json::json!({
+            "roles": [{
+                "name": "roles/storage.azureReader",
+                "title": "Storage Account Reader",
+                "description": "",
+                "stage": "GA",
+                "provider": "azure",
+                "included_permissions": ["Microsoft.Storage/storageAccounts/read"],
+            }],
+            "permissions": [{
+                "name": "Microsoft.Storage/storageAccounts/read",
+                "service": "Microsoft.Storage",
+                "provider": "azure",
+                "resource": "storageAccounts",
+                "action": "read",
+                "description": "Returns the list of storage accounts",
+            }],
+            "metadata": {"total_roles": 1, "total_permissions": 1},
+        }))
+        .unwrap();
+        let dataset = Dataset { engine: SearchEngine::from_dataset(data).unwrap(), ..Dataset::empty() };
+        let state = web::Data::new(AppState::new(dataset, BTreeMap::new()));
+
+        let path = web::Path::from("Microsoft.Storage/storageAccounts/read".to_string());
+        let detail = get_permission(path, web::Query(DatasetQuery { dataset: None }), state.clone()).await.unwrap();
+        let detail = detail.into_inner().data.unwrap();
+        assert_eq!((detail.service.as_str(), detail.provider.as_str()), ("Microsoft.Storage", "azure"));
+        assert_eq!((detail.resource.as_str(), detail.action.as_str()), ("storageAccounts", "read"));
+        assert_eq!(detail.description.as_deref(), Some("Returns the list of storage accounts"));
+        assert_eq!(detail.granted_by_roles.len(), 1);
+
+        let path = web::Path::from("Microsoft.Storage/storageAccounts/delete".to_string());
+        let missing = get_permission(path, web::Query(DatasetQuery { dataset: None }), state).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+    }
+}
@@ -0,0 +1,160 @@
+//! Role detail and permission-set endpoints
+
+use actix_web::web;
+use gcpiam_core::DeletedRoleData;
+use std::collections::HashSet;
+
+use super::AppState;
+use crate::dataset::LATEST;
+use crate::error::{ApiError, Result};
+use crate::models::{ApiResponse, DatasetQuery, GrantingAllRequest, RoleHistory, RoleHistoryEntry, SimilarRolesQuery};
+use crate::search::engine::{Role, SimilarRole, SupersetRoles};
+
+/// Maximum number of similar roles returned per request
+const MAX_SIMILAR_ROLES: usize = 100;
+
+/// Most permissions accepted by one granting-all query
+const MAX_GRANTING_ALL_PERMISSIONS: usize = 1000;
+
+/// Get a single role with all of its permissions
+pub async fn get_role(
+    path: web::Path<String>,
+    query: web::Query<DatasetQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Role>>> {
+    let name = path.into_inner();
+
+    data.with_dataset(query.dataset.as_deref(), |engine| engine.get_role(&name).cloned())?
+        .map(|role| web::Json(ApiResponse::ok(role)))
+        .ok_or_else(|| {
+            let deleted_on = match query.dataset.as_deref() {
+                None | Some(LATEST) => data
+                    .live()
+                    .deleted_roles
+                    .iter()
+                    .find(|r| r.name == name)
+                    .and_then(|r| r.deleted_on.clone()),
+                Some(_) => None,
+            };
+            match deleted_on {
+                Some(date) => ApiError::NotFound(format!("Role {} was deleted on {}", name, date)),
+                None => ApiError::NotFound(format!("Role not found: {}", name)),
+            }
+        })
+}
+
+/// Roles recently deleted or removed from the live dataset, newest first
+pub async fn deleted_roles(data: web::Data<AppState>) -> web::Json<ApiResponse<Vec<DeletedRoleData>>> {
+    web::Json(ApiResponse::ok(data.live().deleted_roles.clone()))
+}
+
+/// Permission changes of a role across stored snapshots, oldest first,
+/// ending with the live dataset
+///
+/// Each entry is diffed against the most recent earlier dataset that
+/// contained the role, so the first appearance has empty change lists.
+pub async fn role_history(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<RoleHistory>>> {
+    let name = path.into_inner();
+
+    let mut states: Vec<(String, Option<String>, Option<Role>)> = data
+        .snapshots
+        .iter()
+        .map(|(id, snapshot)| {
+            (id.clone(), snapshot.last_updated.clone(), snapshot.engine.get_role(&name).cloned())
+        })
+        .collect();
+    let live = data.live();
+    states.push((LATEST.to_string(), live.last_updated.clone(), live.engine.get_role(&name).cloned()));
+
+    let canonical = states
+        .iter()
+        .rev()
+        .find_map(|(_, _, role)| role.as_ref().map(|r| r.name.clone()))
+        .ok_or_else(|| ApiError::NotFound(format!("Role not found in any dataset: {}", name)))?;
+
+    let mut previous: Option<HashSet<String>> = None;
+    let entries = states
+        .into_iter()
+        .map(|(dataset, last_updated, role)| match role {
+            Some(role) => {
+                let current: HashSet<String> = role.included_permissions.iter().cloned().collect();
+                let (mut added, mut removed): (Vec<String>, Vec<String>) = match &previous {
+                    Some(prev) => (
+                        current.difference(prev).cloned().collect(),
+                        prev.difference(&current).cloned().collect(),
+                    ),
+                    None => (vec![], vec![]),
+                };
+                added.sort();
+                removed.sort();
+                previous = Some(current);
+
+                RoleHistoryEntry {
+                    dataset,
+                    last_updated,
+                    present: true,
+                    stage: Some(role.stage),
+                    permission_count: role.included_permissions.len(),
+                    permissions_added: added,
+                    permissions_removed: removed,
+                }
+            }
+            None => RoleHistoryEntry {
+                dataset,
+                last_updated,
+                present: false,
+                stage: None,
+                permission_count: 0,
+                permissions_added: vec![],
+                permissions_removed: vec![],
+            },
+        })
+        .collect();
+
+    Ok(web::Json(ApiResponse::ok(RoleHistory {
+        name: canonical,
+        entries,
+    })))
+}
+
+/// Roles with the most similar permission sets, useful for finding narrower alternatives
+pub async fn similar_roles(
+    path: web::Path<String>,
+    query: web::Query<SimilarRolesQuery>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Vec<SimilarRole>>>> {
+    let name = path.into_inner();
+    if query.limit == 0 || query.limit > MAX_SIMILAR_ROLES {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_SIMILAR_ROLES
+        )));
+    }
+
+    data.with_dataset(query.dataset.as_deref(), |engine| engine.similar_roles(&name, query.limit))?
+        .map(|similar| web::Json(ApiResponse::ok(similar)))
+        .ok_or_else(|| ApiError::NotFound(format!("Role not found: {}", name)))
+}
+
+/// Roles whose permissions include every requested one, so a single role
+/// can replace a stack of several
+pub async fn roles_granting_all(
+    body: web::Json<GrantingAllRequest>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<SupersetRoles>>> {
+    let request = body.into_inner();
+    if request.permissions.is_empty() || request.permissions.len() > MAX_GRANTING_ALL_PERMISSIONS {
+        return Err(ApiError::BadRequest(format!(
+            "Between 1 and {} permissions are required",
+            MAX_GRANTING_ALL_PERMISSIONS
+        )));
+    }
+
+    let roles = data.with_dataset(request.dataset.as_deref(), |engine| {
+        engine.roles_granting_all(&request.permissions)
+    })?;
+    Ok(web::Json(ApiResponse::ok(roles)))
+}
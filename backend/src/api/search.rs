@@ -0,0 +1,180 @@
+//! Search endpoint
+
+use actix_web::web;
+use serde_json::Value;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use super::AppState;
+use crate::error::{ApiError, Result};
+use crate::models::{ApiResponse, SearchData, SearchMode, SearchRequest, SEARCH_RESULT_FIELDS};
+use crate::query_log::QueryFilters;
+use crate::search::engine::PROVIDERS;
+
+/// Below this many combined hits the query is retried with spelling correction
+const FEW_RESULTS: usize = 3;
+
+/// Search endpoint - returns permissions with associated roles, and roles with their permissions
+pub async fn search(
+    query: web::Query<SearchRequest>,
+    data: web::Data<AppState>,
+) -> Result<web::Json<ApiResponse<Value>>> {
+    let started = Instant::now();
+
+    // Validate query
+    let search_query = query.q.trim();
+    if search_query.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Query parameter 'q' is required and cannot be empty".to_string(),
+        ));
+    }
+
+    if search_query.len() > 100 {
+        return Err(ApiError::BadRequest(
+            "Query too long (max 100 characters)".to_string(),
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&query.min_score) {
+        return Err(ApiError::BadRequest(
+            "min_score must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    if query.limit == 0 || query.limit > data.config.max_limit {
+        return Err(ApiError::LimitOutOfRange {
+            limit: query.limit,
+            max: data.config.max_limit,
+        });
+    }
+
+    let fields = query.fields();
+    if let Some(unknown) = fields.iter().flatten().find(|f| !SEARCH_RESULT_FIELDS.contains(f)) {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown field '{}' (expected one of: {})",
+            unknown,
+            SEARCH_RESULT_FIELDS.join(", ")
+        )));
+    }
+
+    if let Some(provider) = query.provider.as_deref().filter(|p| !PROVIDERS.contains(p)) {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown provider '{}' (expected one of: {})",
+            provider,
+            PROVIDERS.join(", ")
+        )));
+    }
+
+    let mode = query.mode;
+    let mut options = query.options(data.config.ranking);
+    options.popularity = data.popularity();
+
+    // Search both permissions and roles on the blocking pool, so a slow
+    // query holds a pool thread rather than this worker. Past the timeout
+    // the request is answered and the abandoned search's result discarded.
+    let state = data.clone();
+    let dataset = query.dataset.clone();
+    let owned_query = search_query.to_string();
+    let search = web::block(move || state.with_dataset(dataset.as_deref(), |engine| {
+        let search_query = owned_query.as_str();
+        let permissions = engine.search_permissions(search_query, mode.as_str(), &options);
+        let roles = engine.search_roles(search_query, mode.as_str(), &options);
+
+        // Exact lookups are taken literally; other modes retry a corrected query on few hits
+        let found = permissions.len() + roles.len();
+        if found < FEW_RESULTS && !matches!(mode, SearchMode::Exact) {
+            if let Some(corrected) = engine.correct_query(search_query) {
+                let corrected_permissions = engine.search_permissions(&corrected, mode.as_str(), &options);
+                let corrected_roles = engine.search_roles(&corrected, mode.as_str(), &options);
+                if corrected_permissions.len() + corrected_roles.len() > found {
+                    return (corrected_permissions, corrected_roles, Some(corrected));
+                }
+            }
+        }
+
+        (permissions, roles, None)
+    }));
+    let (permissions, roles, corrected_query) = match actix_web::rt::time::timeout(data.config.search_timeout, search).await {
+        Ok(result) => result.map_err(|e| ApiError::InternalError(e.to_string()))??,
+        Err(_) => {
+            data.search_timeouts.fetch_add(1, Ordering::Relaxed);
+            return Err(ApiError::SearchTimeout {
+                timeout_ms: data.config.search_timeout.as_millis() as u64,
+            });
+        }
+    };
+
+    let filters = QueryFilters {
+        provider: query.provider.clone(),
+        dataset: query.dataset.clone(),
+        include_deprecated: query.include_deprecated,
+    };
+    data.query_log
+        .record(search_query, mode, filters, permissions.len() + roles.len(), started.elapsed());
+
+    let mut body = serde_json::to_value(SearchData {
+        permissions,
+        roles,
+        query: search_query.to_string(),
+        mode,
+        corrected_query,
+    })
+    .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+    if let Some(fields) = &fields {
+        for key in ["permissions", "roles"] {
+            if let Some(Value::Array(items)) = body.get_mut(key) {
+                items.iter_mut().for_each(|item| select_fields(item, fields));
+            }
+        }
+    }
+
+    Ok(web::Json(ApiResponse::ok(body)))
+}
+
+/// Drop every key of a result object that was not requested
+fn select_fields(item: &mut Value, fields: &[&str]) {
+    if let Value::Object(map) = item {
+        map.retain(|key, _| fields.contains(&key.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Dataset;
+    use crate::search::SearchEngine;
+    use std::collections::BTreeMap;
+
+    fn state() -> web::Data<AppState> {
+        let mut engine = SearchEngine::new();
+        engine.index_role(
+            "roles/storage.viewer".to_string(),
+            "Storage Viewer".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec!["storage.buckets.get".to_string(), "storage.buckets.list".to_string()],
+        );
+        engine.finalize();
+        web::Data::new(AppState::new(Dataset { engine, ..Dataset::empty() }, BTreeMap::new()))
+    }
+
+    async fn corrected(state: &web::Data<AppState>, query: &str) -> (Option<String>, usize) {
+        let request = web::Query::<SearchRequest>::from_query(query).unwrap();
+        let body = search(request, state.clone()).await.unwrap().into_inner().data.unwrap();
+        let found = body["permissions"].as_array().unwrap().len() + body["roles"].as_array().unwrap().len();
+        (body.get("corrected_query").and_then(Value::as_str).map(str::to_string), found)
+    }
+
+    #[actix_web::test]
+    async fn few_results_retry_the_corrected_query() {
+        let state = state();
+        let (correction, found) = corrected(&state, "q=strage&mode=prefix").await;
+        assert_eq!(correction.as_deref(), Some("storage"));
+        assert!(found > 0);
+
+        // Enough hits, or exact lookups, keep the query as typed
+        assert_eq!(corrected(&state, "q=storage&mode=prefix").await.0, None);
+        assert_eq!(corrected(&state, "q=strage&mode=exact").await, (None, 0));
+    }
+}
@@ -0,0 +1,65 @@
+//! Health, statistics and version endpoints
+
+use actix_web::web;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gcpiam_core::INDEX_FORMAT_VERSION;
+
+use super::AppState;
+use crate::dataset;
+use crate::models::{ApiResponse, HealthData, IndexStats, StatsData, VersionData};
+use crate::{GIT_SHA, VERSION};
+
+/// Health check endpoint, flagging a live dataset the scraper stopped refreshing
+pub async fn health_check(data: web::Data<AppState>) -> web::Json<ApiResponse<HealthData>> {
+    let last_updated = data.live().last_updated.clone();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default();
+    let dataset_age_secs = last_updated
+        .as_deref()
+        .and_then(dataset::unix_time)
+        .map(|updated| now.saturating_sub(updated).max(0) as u64);
+    let stale = dataset_age_secs.is_none_or(|age| age > data.config.max_dataset_age.as_secs());
+
+    web::Json(ApiResponse::ok(HealthData {
+        status: "healthy".to_string(),
+        version: VERSION.to_string(),
+        last_updated,
+        dataset_age_secs,
+        stale,
+    }))
+}
+
+/// Get statistics endpoint
+pub async fn stats(data: web::Data<AppState>) -> web::Json<ApiResponse<StatsData>> {
+    let live = data.live();
+    let engine = &live.engine;
+    let (perm_count, role_count) = engine.stats();
+    let catalog = engine.catalog_stats().clone();
+
+    web::Json(ApiResponse::ok(StatsData {
+        total_permissions: perm_count,
+        total_roles: role_count,
+        indexed: true,
+        version: VERSION.to_string(),
+        last_updated: live.last_updated.clone(),
+        permissions_by_service: catalog.permissions_by_service,
+        roles_by_stage: catalog.roles_by_stage,
+        average_permissions_per_role: catalog.average_permissions_per_role,
+        index: IndexStats {
+            build_ms: live.build_time.as_millis() as u64,
+            memory_bytes: engine.memory_usage(),
+        },
+        search_timeouts: data.search_timeouts.load(Ordering::Relaxed),
+    }))
+}
+
+/// Build and dataset versions
+pub async fn version(data: web::Data<AppState>) -> web::Json<ApiResponse<VersionData>> {
+    web::Json(ApiResponse::ok(VersionData {
+        version: VERSION.to_string(),
+        git_sha: GIT_SHA.to_string(),
+        dataset: data.live().last_updated.clone(),
+        index_format_version: INDEX_FORMAT_VERSION,
+    }))
+}
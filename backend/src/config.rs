@@ -0,0 +1,89 @@
+//! Server configuration read from the environment
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::search::RankingConfig;
+
+/// Default upper bound for `limit` on search requests
+pub const DEFAULT_MAX_LIMIT: usize = 100;
+
+/// Default time a search may run before the request gets a 503
+pub const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default age past which /health reports the live dataset as stale (the
+/// scraper publishes daily)
+pub const DEFAULT_MAX_DATASET_AGE: Duration = Duration::from_secs(48 * 60 * 60);
+
+/// Tunable request limits
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Largest `limit` a search request may ask for
+    pub max_limit: usize,
+    /// Longest a search may run before it is abandoned
+    pub search_timeout: Duration,
+    /// Dataset age past which /health reports it stale
+    pub max_dataset_age: Duration,
+    /// Relevance weights applied to every search
+    pub ranking: RankingConfig,
+}
+
+impl Config {
+    /// Read configuration from environment variables, falling back to defaults
+    ///
+    /// - `MAX_RESULT_LIMIT` - largest accepted search `limit`
+    /// - `SEARCH_TIMEOUT_MS` - search time limit in milliseconds
+    /// - `MAX_DATASET_AGE_HOURS` - age past which the dataset is stale
+    /// - `RANKING_CONFIG` - JSON file of `RankingConfig` weights
+    pub fn from_env() -> Self {
+        let max_limit = std::env::var("MAX_RESULT_LIMIT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&max| max > 0)
+            .unwrap_or(DEFAULT_MAX_LIMIT);
+
+        let search_timeout = std::env::var("SEARCH_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_SEARCH_TIMEOUT);
+
+        let max_dataset_age = std::env::var("MAX_DATASET_AGE_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&hours| hours > 0)
+            .map(|hours| Duration::from_secs(hours * 60 * 60))
+            .unwrap_or(DEFAULT_MAX_DATASET_AGE);
+
+        let ranking = match std::env::var("RANKING_CONFIG") {
+            Ok(path) => load_ranking(Path::new(&path)).unwrap_or_else(|e| {
+                log::warn!("Ignoring ranking config {}: {}", path, e);
+                RankingConfig::default()
+            }),
+            Err(_) => RankingConfig::default(),
+        };
+
+        Config { max_limit, search_timeout, max_dataset_age, ranking }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_limit: DEFAULT_MAX_LIMIT,
+            search_timeout: DEFAULT_SEARCH_TIMEOUT,
+            max_dataset_age: DEFAULT_MAX_DATASET_AGE,
+            ranking: RankingConfig::default(),
+        }
+    }
+}
+
+/// Read and validate ranking weights from a JSON file; omitted fields keep
+/// their defaults
+pub fn load_ranking(path: &Path) -> Result<RankingConfig, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let ranking: RankingConfig = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    ranking.validate()?;
+    Ok(ranking)
+}
@@ -0,0 +1,579 @@
+//! Dataset loading
+//!
+//! The live dataset comes from `iam-data.json`; historical snapshots live in
+//! dated subdirectories (`data/2024-06-01/iam-data.json`) and are loaded
+//! read-only so they can be queried with `?dataset=`.
+//!
+//! The live dataset can also be downloaded at startup (`IAM_DATA_URL`, an
+//! https:// or gs:// URL of a published `iam-data.json`), verified against
+//! the `manifest.json` published beside it.
+//!
+//! The live dataset can instead be a prebuilt index (`IAM_INDEX_PATH`), the
+//! same archive the edge embeds, which skips parsing the JSON and serves
+//! exactly the edge's data.
+//!
+//! When the scraper's `manifest.json` sits next to a dataset file, the file
+//! must match the SHA-256 checksum recorded there; truncated or modified
+//! datasets are rejected instead of indexed. With a public key
+//! (`IAM_MANIFEST_PUBLIC_KEY`) the manifest must also carry a valid ed25519
+//! signature (`manifest.json.sig`) and list the file, so checksums are only
+//! trusted when they come from the scraper's signing key.
+
+use anyhow::Context;
+use ed25519_dalek::pkcs8::DecodePublicKey;
+use ed25519_dalek::{Signature, VerifyingKey};
+use gcpiam_core::{DeletedRoleData, IamDataFile, PrebuiltIndex};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::search::SearchEngine;
+
+/// Dataset ID that always refers to the live index
+pub const LATEST: &str = "latest";
+
+/// File name of a dataset inside a snapshot directory
+pub const DATA_FILE_NAME: &str = "iam-data.json";
+
+/// Checksum manifest the scraper writes beside its dataset files
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Detached ed25519 signature over the manifest's bytes
+pub const SIGNATURE_NAME: &str = "manifest.json.sig";
+
+#[derive(Deserialize)]
+struct Manifest {
+    files: HashMap<String, ManifestEntry>,
+}
+
+#[derive(Deserialize)]
+struct ManifestEntry {
+    sha256: String,
+    size: u64,
+}
+
+/// The manifest and signature found beside a dataset file, `None` where
+/// missing (absent on disk, 404 when downloaded)
+struct ManifestFiles {
+    manifest: Option<Vec<u8>>,
+    signature: Option<Vec<u8>>,
+    /// Path or URL of the manifest, for errors
+    location: String,
+}
+
+/// A loaded, finalized search index plus its metadata
+#[derive(Clone)]
+pub struct Dataset {
+    pub engine: SearchEngine,
+    pub last_updated: Option<String>,
+    /// Roles recently deleted or removed, newest first (not indexed)
+    pub deleted_roles: Vec<DeletedRoleData>,
+    /// Time spent decoding the file and building the engine
+    pub build_time: Duration,
+}
+
+impl Dataset {
+    /// An empty dataset used when no data file is available
+    pub fn empty() -> Self {
+        Dataset {
+            engine: SearchEngine::new(),
+            last_updated: None,
+            deleted_roles: Vec::new(),
+            build_time: Duration::ZERO,
+        }
+    }
+}
+
+/// Where the live dataset is loaded from, tried in order: `url`, then
+/// `index_path`, then `data_path`
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub url: Option<String>,
+    pub index_path: Option<PathBuf>,
+    pub data_path: PathBuf,
+    /// Key the manifest must be signed with; unsigned data is refused when set
+    pub public_key: Option<VerifyingKey>,
+}
+
+impl Source {
+    /// Read `IAM_DATA_URL`, `IAM_INDEX_PATH`, `IAM_DATA_PATH` and
+    /// `IAM_MANIFEST_PUBLIC_KEY`
+    ///
+    /// Fails if the public key can't be loaded, rather than silently
+    /// accepting unsigned data.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let public_key = match std::env::var("IAM_MANIFEST_PUBLIC_KEY").ok().filter(|v| !v.is_empty()) {
+            Some(path) => Some(load_public_key(Path::new(&path))?),
+            None => None,
+        };
+        Ok(Source {
+            url: std::env::var("IAM_DATA_URL").ok().filter(|v| !v.is_empty()),
+            index_path: std::env::var("IAM_INDEX_PATH").ok().filter(|v| !v.is_empty()).map(PathBuf::from),
+            data_path: std::env::var("IAM_DATA_PATH")
+                .unwrap_or_else(|_| "../data/iam-data.json".to_string())
+                .into(),
+            public_key,
+        })
+    }
+
+    /// Load from the first source that succeeds, warning about the ones
+    /// that fail; the error is `data_path`'s when all do
+    pub async fn load(&self) -> anyhow::Result<Dataset> {
+        let public_key = self.public_key.as_ref();
+        if let Some(url) = &self.url {
+            match fetch(url, public_key).await {
+                Ok(dataset) => return Ok(dataset),
+                Err(e) => log::warn!("{:#}; falling back to local data", e),
+            }
+        }
+        if let Some(index_path) = &self.index_path {
+            match load_index(index_path, public_key) {
+                Ok(dataset) => return Ok(dataset),
+                Err(e) => log::warn!("{:#}; falling back to {}", e, self.data_path.display()),
+            }
+        }
+        load_file(&self.data_path, public_key)
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(url) = &self.url {
+            write!(f, "{}, then ", url)?;
+        }
+        if let Some(index_path) = &self.index_path {
+            write!(f, "{}, then ", index_path.display())?;
+        }
+        write!(f, "{}", self.data_path.display())
+    }
+}
+
+/// An ed25519 public key from a PEM file (`openssl pkey -in key.pem -pubout`)
+pub fn load_public_key(path: &Path) -> anyhow::Result<VerifyingKey> {
+    let pem = fs::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    VerifyingKey::from_public_key_pem(&pem)
+        .map_err(|e| anyhow::anyhow!("{} is not an ed25519 public key: {}", path.display(), e))
+}
+
+/// Load and index an iam-data.json file
+pub fn load_file(path: &Path, public_key: Option<&VerifyingKey>) -> anyhow::Result<Dataset> {
+    let content = fs::read(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    verify_manifest(path, &content, public_key)?;
+    parse(&content, &path.display().to_string())
+}
+
+/// Download and index a published iam-data.json
+///
+/// `gs://bucket/object` URLs are read through the public Cloud Storage
+/// endpoint, so the object must be publicly readable. A `manifest.json` (and
+/// its signature) next to the dataset is checked as for local files; a
+/// missing one (404) is accepted only without a public key.
+pub async fn fetch(url: &str, public_key: Option<&VerifyingKey>) -> anyhow::Result<Dataset> {
+    let url = match url.strip_prefix("gs://") {
+        Some(object) => format!("https://storage.googleapis.com/{}", object),
+        None if url.starts_with("https://") => url.to_string(),
+        None => anyhow::bail!("Unsupported dataset URL {} (expected https:// or gs://)", url),
+    };
+    let client = reqwest::Client::new();
+    let content = download(&client, &url)
+        .await?
+        .with_context(|| format!("Could not download {}: not found", url))?;
+
+    let (base, name) = url.rsplit_once('/').unwrap_or_default();
+    let manifest_url = format!("{}/{}", base, MANIFEST_NAME);
+    let manifest = download(&client, &manifest_url).await?;
+    let signature = match (&manifest, public_key) {
+        (Some(_), Some(_)) => download(&client, &format!("{}/{}", base, SIGNATURE_NAME)).await?,
+        _ => None,
+    };
+    let files = ManifestFiles { manifest, signature, location: manifest_url };
+    check_manifest(&files, name, &content, &url, public_key)?;
+
+    parse(&content, &url)
+}
+
+/// GET `url`, `None` for a 404
+async fn download(client: &reqwest::Client, url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Could not download {}", url))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = response
+        .error_for_status()
+        .with_context(|| format!("Could not download {}", url))?
+        .bytes()
+        .await
+        .with_context(|| format!("Could not download {}", url))?;
+    Ok(Some(body.to_vec()))
+}
+
+/// Index the contents of an iam-data.json read from `source`
+fn parse(content: &[u8], source: &str) -> anyhow::Result<Dataset> {
+    let started = Instant::now();
+    let mut data: IamDataFile = serde_json::from_slice(content)
+        .with_context(|| format!("Failed to parse {}", source))?;
+
+    let last_updated = data.metadata.last_updated.take();
+    let deleted_roles = std::mem::take(&mut data.deleted_roles);
+    let engine = SearchEngine::from_dataset(data)
+        .with_context(|| format!("Invalid dataset {}", source))?;
+
+    Ok(Dataset {
+        engine,
+        last_updated,
+        deleted_roles,
+        build_time: started.elapsed(),
+    })
+}
+
+/// Load a prebuilt index file (`build_index` output)
+///
+/// The archive doesn't carry deleted roles, so none are reported.
+pub fn load_index(path: &Path, public_key: Option<&VerifyingKey>) -> anyhow::Result<Dataset> {
+    let content = fs::read(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    verify_manifest(path, &content, public_key)?;
+    let started = Instant::now();
+    let index = PrebuiltIndex::from_bytes(&content)
+        .with_context(|| format!("Failed to decode index {}", path.display()))?;
+
+    let engine = index.to_engine()
+        .with_context(|| format!("Invalid index {}", path.display()))?;
+
+    Ok(Dataset {
+        engine,
+        last_updated: index.last_updated,
+        deleted_roles: Vec::new(),
+        build_time: started.elapsed(),
+    })
+}
+
+/// Check `content` (read from `path`) against the manifest in the same directory
+fn verify_manifest(path: &Path, content: &[u8], public_key: Option<&VerifyingKey>) -> anyhow::Result<()> {
+    let manifest_path = path.with_file_name(MANIFEST_NAME);
+    let files = ManifestFiles {
+        manifest: read_if_present(&manifest_path)?,
+        signature: read_if_present(&path.with_file_name(SIGNATURE_NAME))?,
+        location: manifest_path.display().to_string(),
+    };
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    check_manifest(&files, name, content, &path.display().to_string(), public_key)
+}
+
+/// Read `path`, `None` if it doesn't exist
+fn read_if_present(path: &Path) -> anyhow::Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Could not read {}", path.display())),
+    }
+}
+
+/// Check `content` (the file `name`, read from `source`) against its manifest
+///
+/// Without a public key, a missing manifest or a file it doesn't list is
+/// accepted. With one, the manifest must exist, its signature must verify
+/// before any checksum in it is trusted, and the file must be listed.
+fn check_manifest(
+    files: &ManifestFiles,
+    name: &str,
+    content: &[u8],
+    source: &str,
+    public_key: Option<&VerifyingKey>,
+) -> anyhow::Result<()> {
+    let Some(manifest) = &files.manifest else {
+        if public_key.is_some() {
+            anyhow::bail!("{} has no signed manifest ({} not found)", source, files.location);
+        }
+        return Ok(());
+    };
+    if let Some(public_key) = public_key {
+        let signature = files
+            .signature
+            .as_deref()
+            .with_context(|| format!("{} is not signed ({} not found)", files.location, SIGNATURE_NAME))?;
+        let signature = Signature::from_slice(signature)
+            .map_err(|_| anyhow::anyhow!("{} has a malformed signature", files.location))?;
+        public_key
+            .verify_strict(manifest, &signature)
+            .map_err(|_| anyhow::anyhow!("{} has an invalid signature", files.location))?;
+    }
+    let manifest: Manifest = serde_json::from_slice(manifest)
+        .with_context(|| format!("Failed to parse {}", files.location))?;
+
+    let Some(entry) = manifest.files.get(name) else {
+        if public_key.is_some() {
+            anyhow::bail!("{} is not listed in {}", source, files.location);
+        }
+        return Ok(());
+    };
+    let digest = format!("{:x}", Sha256::digest(content));
+    if content.len() as u64 != entry.size || digest != entry.sha256 {
+        anyhow::bail!(
+            "{} does not match {} ({} bytes, expected {}); truncated or modified",
+            source,
+            files.location,
+            content.len(),
+            entry.size
+        );
+    }
+    Ok(())
+}
+
+/// Load every dated snapshot (`YYYY-MM-DD/iam-data.json`) under `dir`
+///
+/// Snapshots that fail to load (including failed manifest checks) are
+/// skipped with a warning.
+pub fn load_snapshots(dir: &Path, public_key: Option<&VerifyingKey>) -> BTreeMap<String, Dataset> {
+    let mut snapshots = BTreeMap::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return snapshots,
+    };
+
+    for entry in entries.flatten() {
+        let id = entry.file_name().to_string_lossy().to_string();
+        if !is_snapshot_id(&id) {
+            continue;
+        }
+
+        let path = entry.path().join(DATA_FILE_NAME);
+        if !path.is_file() {
+            continue;
+        }
+
+        match load_file(&path, public_key) {
+            Ok(dataset) => {
+                snapshots.insert(id, dataset);
+            }
+            Err(e) => println!("   Warning: Skipping snapshot {}: {:#}", id, e),
+        }
+    }
+
+    snapshots
+}
+
+/// Seconds since the Unix epoch of a `last_updated` timestamp
+/// (`2024-06-01T04:04:23.920243Z`; fractional seconds are ignored)
+pub fn unix_time(iso: &str) -> Option<i64> {
+    let field = |range: std::ops::Range<usize>| iso.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+
+    // Howard Hinnant's days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let days = era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + doy - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Whether a directory name is a snapshot date (YYYY-MM-DD)
+pub fn is_snapshot_id(id: &str) -> bool {
+    let bytes = id.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, EncodePublicKey};
+    use ed25519_dalek::{Signer, SigningKey};
+    use tempfile::TempDir;
+
+    /// A one-role iam-data.json
+    fn dataset_json(role: &str) -> String {
+        serde_json::json!({
+            "roles": [{
+                "name": role,
+                "title": "Viewer",
+                "description": "",
+                "stage": "GA",
+                "included_permissions": ["storage.objects.get"],
+            }],
+            "permissions": [],
+            "metadata": {"total_roles": 1, "total_permissions": 1, "last_updated": "2024-06-01T00:00:00Z"},
+        })
+        .to_string()
+    }
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn snapshot_ids_are_dates() {
+        assert!(is_snapshot_id("2024-06-01"));
+        for id in ["latest", "2024-6-01", "2024_06_01", "2024-06-011", "abcd-ef-gh", ""] {
+            assert!(!is_snapshot_id(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn load_snapshots_skips_unusable_directories() {
+        let dir = TempDir::new().unwrap();
+        write(&dir.path().join("2024-06-01").join(DATA_FILE_NAME), &dataset_json("roles/a"));
+        write(&dir.path().join("2024-07-01").join(DATA_FILE_NAME), &dataset_json("roles/b"));
+        // Not a date, no data file, unparseable data
+        write(&dir.path().join("latest").join(DATA_FILE_NAME), &dataset_json("roles/c"));
+        fs::create_dir_all(dir.path().join("2024-08-01")).unwrap();
+        write(&dir.path().join("2024-09-01").join(DATA_FILE_NAME), "{");
+
+        let snapshots = load_snapshots(dir.path(), None);
+        assert_eq!(snapshots.keys().collect::<Vec<_>>(), vec!["2024-06-01", "2024-07-01"]);
+        assert!(snapshots["2024-06-01"].engine.has_role("roles/a"));
+        assert!(snapshots["2024-07-01"].engine.has_role("roles/b"));
+        assert_eq!(snapshots["2024-06-01"].last_updated.as_deref(), Some("2024-06-01T00:00:00Z"));
+
+        assert!(load_snapshots(&dir.path().join("missing"), None).is_empty());
+    }
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    /// A manifest listing `entries` (name, content)
+    fn manifest_json(entries: &[(&str, &str)]) -> String {
+        let files: serde_json::Map<_, _> = entries
+            .iter()
+            .map(|(name, content)| {
+                let entry = serde_json::json!({
+                    "sha256": format!("{:x}", Sha256::digest(content.as_bytes())),
+                    "size": content.len(),
+                });
+                (name.to_string(), entry)
+            })
+            .collect();
+        serde_json::json!({ "files": files }).to_string()
+    }
+
+    /// A dataset in `dir` with the manifest `manifest`, signed by `key` if given
+    fn published(dir: &Path, content: &str, manifest: Option<&str>, key: Option<&SigningKey>) -> PathBuf {
+        let path = dir.join(DATA_FILE_NAME);
+        write(&path, content);
+        if let Some(manifest) = manifest {
+            write(&dir.join(MANIFEST_NAME), manifest);
+            if let Some(key) = key {
+                fs::write(dir.join(SIGNATURE_NAME), key.sign(manifest.as_bytes()).to_bytes()).unwrap();
+            }
+        }
+        path
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let content = dataset_json("roles/a");
+        let manifest = manifest_json(&[(DATA_FILE_NAME, &dataset_json("roles/b"))]);
+        let path = published(dir.path(), &content, Some(&manifest), None);
+
+        let err = load_file(&path, None).err().unwrap();
+        assert!(format!("{:#}", err).contains("truncated or modified"), "{:#}", err);
+
+        let manifest = manifest_json(&[(DATA_FILE_NAME, &content)]);
+        let path = published(dir.path(), &content, Some(&manifest), None);
+        assert!(load_file(&path, None).unwrap().engine.has_role("roles/a"));
+    }
+
+    #[test]
+    fn unlisted_or_unmanifested_files_need_no_key() {
+        let dir = TempDir::new().unwrap();
+        let content = dataset_json("roles/a");
+        let key = signing_key();
+        let public_key = key.verifying_key();
+
+        // No manifest at all
+        let path = published(dir.path(), &content, None, None);
+        assert!(load_file(&path, None).is_ok());
+        assert!(load_file(&path, Some(&public_key)).is_err());
+
+        // A signed manifest that doesn't list the file
+        let manifest = manifest_json(&[("other.json", "{}")]);
+        let path = published(dir.path(), &content, Some(&manifest), Some(&key));
+        assert!(load_file(&path, None).is_ok());
+        let err = load_file(&path, Some(&public_key)).err().unwrap();
+        assert!(format!("{:#}", err).contains("is not listed in"), "{:#}", err);
+    }
+
+    #[test]
+    fn signatures_are_verified_before_checksums() {
+        let dir = TempDir::new().unwrap();
+        let content = dataset_json("roles/a");
+        let manifest = manifest_json(&[(DATA_FILE_NAME, &content)]);
+        let key = signing_key();
+        let public_key = key.verifying_key();
+
+        let path = published(dir.path(), &content, Some(&manifest), Some(&key));
+        assert!(load_file(&path, Some(&public_key)).unwrap().engine.has_role("roles/a"));
+
+        // Signed by someone else
+        let forger = SigningKey::from_bytes(&[9; 32]);
+        published(dir.path(), &content, Some(&manifest), Some(&forger));
+        let err = load_file(&path, Some(&public_key)).err().unwrap();
+        assert!(format!("{:#}", err).contains("invalid signature"), "{:#}", err);
+        assert!(load_file(&path, None).is_ok());
+
+        // Unsigned
+        fs::remove_file(dir.path().join(SIGNATURE_NAME)).unwrap();
+        let err = load_file(&path, Some(&public_key)).err().unwrap();
+        assert!(format!("{:#}", err).contains("is not signed"), "{:#}", err);
+    }
+
+    #[test]
+    fn missing_downloads_only_pass_without_a_key() {
+        // What fetch sees when manifest.json or its signature is a 404
+        let content = dataset_json("roles/a");
+        let manifest = manifest_json(&[(DATA_FILE_NAME, &content)]);
+        let key = signing_key();
+        let public_key = key.verifying_key();
+        let files = |manifest: Option<&str>, signature: Option<Vec<u8>>| ManifestFiles {
+            manifest: manifest.map(|m| m.as_bytes().to_vec()),
+            signature,
+            location: "https://example.com/manifest.json".to_string(),
+        };
+        let check = |files: &ManifestFiles, public_key| {
+            check_manifest(files, DATA_FILE_NAME, content.as_bytes(), "https://example.com/iam-data.json", public_key)
+        };
+
+        assert!(check(&files(None, None), None).is_ok());
+        assert!(check(&files(None, None), Some(&public_key)).is_err());
+        assert!(check(&files(Some(&manifest), None), None).is_ok());
+        assert!(check(&files(Some(&manifest), None), Some(&public_key)).is_err());
+        let signature = key.sign(manifest.as_bytes()).to_bytes().to_vec();
+        assert!(check(&files(Some(&manifest), Some(signature)), Some(&public_key)).is_ok());
+    }
+
+    #[test]
+    fn public_keys_load_from_pem() {
+        let dir = TempDir::new().unwrap();
+        let public_key = signing_key().verifying_key();
+        let path = dir.path().join("manifest.pub");
+        write(&path, &public_key.to_public_key_pem(LineEnding::LF).unwrap());
+        assert_eq!(load_public_key(&path).unwrap(), public_key);
+
+        write(&path, "not a key");
+        assert!(load_public_key(&path).is_err());
+        assert!(load_public_key(&dir.path().join("missing.pub")).is_err());
+    }
+}
@@ -1,42 +1,90 @@
 use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
-use serde::Serialize;
 use std::fmt;
 
+use crate::models::ApiResponse;
+use crate::search::BuildError;
+
 #[derive(Debug)]
 pub enum ApiError {
     NotFound(String),
     BadRequest(String),
+    Unauthorized(String),
+    Conflict(String),
+    /// A `limit` parameter outside `1..=max`
+    LimitOutOfRange { limit: usize, max: usize },
+    /// A search that ran past `Config::search_timeout`
+    SearchTimeout { timeout_ms: u64 },
     InternalError(String),
 }
 
+impl ApiError {
+    /// Machine-readable error code included in the response envelope
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::LimitOutOfRange { .. } => "limit_out_of_range",
+            ApiError::SearchTimeout { .. } => "search_timeout",
+            ApiError::InternalError(_) => "internal_error",
+        }
+    }
+
+    /// Human-readable error message without the status prefix
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::NotFound(msg)
+            | ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::InternalError(msg) => msg.clone(),
+            ApiError::LimitOutOfRange { limit, max } => {
+                format!("limit {} is out of range (must be between 1 and {})", limit, max)
+            }
+            ApiError::SearchTimeout { timeout_ms } => {
+                format!("Search did not finish within {} ms; try a more specific query", timeout_ms)
+            }
+        }
+    }
+}
+
 impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ApiError::NotFound(msg) => write!(f, "Not Found: {}", msg),
             ApiError::BadRequest(msg) => write!(f, "Bad Request: {}", msg),
+            ApiError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            ApiError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            ApiError::LimitOutOfRange { .. } => write!(f, "Bad Request: {}", self.message()),
+            ApiError::SearchTimeout { .. } => write!(f, "Service Unavailable: {}", self.message()),
             ApiError::InternalError(msg) => write!(f, "Internal Error: {}", msg),
         }
     }
 }
 
 impl ResponseError for ApiError {
-    fn error_response(&self) -> HttpResponse {
-        let status = match self {
+    fn status_code(&self) -> StatusCode {
+        match self {
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
-            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::BadRequest(_) | ApiError::LimitOutOfRange { .. } => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::SearchTimeout { .. } => StatusCode::SERVICE_UNAVAILABLE,
             ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
-        };
-
-        #[derive(Serialize)]
-        struct ErrorResponse {
-            success: bool,
-            error: String,
         }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .json(ApiResponse::<()>::error(self.code(), self.message()))
+    }
+}
 
-        HttpResponse::build(status).json(ErrorResponse {
-            success: false,
-            error: self.to_string(),
-        })
+/// Rejected engine input is the caller's to fix
+impl From<BuildError> for ApiError {
+    fn from(err: BuildError) -> Self {
+        ApiError::BadRequest(err.to_string())
     }
 }
 
@@ -1,17 +1,34 @@
-/// GCP IAM Search Backend API
-///
-/// High-performance REST API for searching GCP IAM roles and permissions.
-/// Uses a hybrid search engine with multiple index types for fast queries.
-///
-/// # Modules
-/// - `models` - Data types and structures
-/// - `search` - Search engine implementation
-/// - `error` - Error handling
+//! GCP IAM Search Backend API
+//!
+//! High-performance REST API for searching GCP IAM roles and permissions.
+//! Uses a hybrid search engine with multiple index types for fast queries.
+//!
+//! # Modules
+//! - `api` - REST endpoint handlers and routing
+//! - `config` - Server configuration from the environment
+//! - `dataset` - Loading the live dataset and dated snapshots
+//! - `models` - Data types and structures
+//! - `query_log` - Anonymized search query analytics
+//! - `refresh` - Periodic background reloading of the live dataset
+//! - `search` - Search engine (re-exported from `gcpiam-core`)
+//! - `error` - Error handling
 
+pub mod api;
+pub mod config;
+pub mod dataset;
 pub mod models;
-pub mod search;
+pub mod query_log;
+pub mod refresh;
 pub mod error;
 
+pub use gcpiam_core::search;
+
+/// Crate version reported by the API
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Commit the binary was built from (`GIT_SHA` or `git rev-parse`), or "unknown"
+pub const GIT_SHA: &str = env!("GCPIAM_GIT_SHA");
+
 pub use error::{ApiError, Result};
-pub use models::{SearchRequest, SearchMode, SearchResult, ApiResponse};
+pub use models::{SearchRequest, SearchMode, SearchResult, ApiResponse, ErrorBody};
 pub use search::SearchEngine;
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use std::collections::BTreeMap;
+
+use crate::search::engine::{IndexMemory, PermissionSearchResult, RoleSearchResult, RoleSummary};
+use crate::search::{RankingConfig, SearchOptions, SortField, SortOrder, DEFAULT_MIN_SCORE};
+
 /// API request for searching permissions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchRequest {
@@ -10,6 +15,95 @@ pub struct SearchRequest {
     pub limit: usize,
     #[serde(default)]
     pub offset: usize,
+    /// Minimum fuzzy similarity (0.0-1.0)
+    #[serde(default = "default_min_score")]
+    pub min_score: f64,
+    /// Include roles in the DEPRECATED stage
+    #[serde(default)]
+    pub include_deprecated: bool,
+    #[serde(default)]
+    pub sort: SortField,
+    #[serde(default)]
+    pub order: Option<SortOrder>,
+    /// Snapshot date to query instead of the live dataset
+    #[serde(default)]
+    pub dataset: Option<String>,
+    /// Comma-separated result fields to return (all fields when unset)
+    #[serde(default)]
+    pub fields: Option<String>,
+    /// Only results from this cloud: gcp, aws or azure
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Attach each result's score breakdown
+    #[serde(default)]
+    pub explain: bool,
+}
+
+/// Result fields selectable with `?fields=` (permission and role fields combined)
+pub const SEARCH_RESULT_FIELDS: &[&str] = &[
+    "name",
+    "score",
+    "service",
+    "resource",
+    "action",
+    "granted_by_roles",
+    "title",
+    "description",
+    "stage",
+    "permission_count",
+    "sample_permissions",
+    "deprecated",
+    "custom",
+    "warnings",
+    "explanation",
+];
+
+impl SearchRequest {
+    /// Requested result fields, or `None` to return everything
+    pub fn fields(&self) -> Option<Vec<&str>> {
+        self.fields.as_deref().map(|fields| {
+            fields
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .collect()
+        })
+    }
+
+    /// Ranking, ordering and pagination options for the engine
+    pub fn options(&self, ranking: RankingConfig) -> SearchOptions {
+        SearchOptions {
+            min_score: self.min_score,
+            include_deprecated: self.include_deprecated,
+            sort: self.sort,
+            order: self.order,
+            limit: self.limit,
+            offset: self.offset,
+            provider: self.provider.clone(),
+            ranking,
+            explain: self.explain,
+            popularity: None,
+        }
+    }
+}
+
+/// Query parameters for full-catalog listings and exports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListRequest {
+    #[serde(default)]
+    pub sort: Option<SortField>,
+    #[serde(default)]
+    pub order: Option<SortOrder>,
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+impl ListRequest {
+    /// Requested ordering, if any
+    pub fn sort(&self) -> Option<(SortField, SortOrder)> {
+        self.sort
+            .map(|field| (field, self.order.unwrap_or_else(|| field.default_order())))
+    }
 }
 
 /// Search mode
@@ -22,6 +116,16 @@ pub enum SearchMode {
     Fuzzy,
 }
 
+impl SearchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchMode::Exact => "exact",
+            SearchMode::Prefix => "prefix",
+            SearchMode::Fuzzy => "fuzzy",
+        }
+    }
+}
+
 /// Search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult<T> {
@@ -29,13 +133,277 @@ pub struct SearchResult<T> {
     pub score: f64,
 }
 
-/// API response
+/// API response envelope shared by all JSON endpoints
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
-    pub data: T,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorBody>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn ok(data: T) -> Self {
+        ApiResponse {
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn error(code: &str, message: impl Into<String>) -> Self {
+        ApiResponse {
+            success: false,
+            data: None,
+            error: Some(ErrorBody {
+                code: code.to_string(),
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// Machine-readable error details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+}
+
+/// Payload for /api/v1/search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchData {
+    pub permissions: Vec<PermissionSearchResult>,
+    pub roles: Vec<RoleSearchResult>,
+    pub query: String,
+    pub mode: SearchMode,
+    /// Spelling-corrected query actually used when the original found too little
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corrected_query: Option<String>,
+}
+
+/// Dataset selector for detail endpoints
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatasetQuery {
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+/// Query parameters for /api/v1/permissions/expand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpandQuery {
+    /// Permission pattern with `*` wildcards, e.g. `compute.instances.*`
+    pub pattern: String,
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+/// Payload for /api/v1/permissions/expand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpandedPermissions {
+    pub pattern: String,
+    pub count: usize,
+    pub permissions: Vec<String>,
+}
+
+/// Request body for POST /api/v1/check-access
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckAccessRequest {
+    /// Roles held by the member, by full name or bare predefined ID
+    pub roles: Vec<String>,
+    pub permission: String,
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+/// Request body for POST /api/v1/roles/granting-all
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrantingAllRequest {
+    pub permissions: Vec<String>,
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+/// Query parameters for /api/v1/roles/{name}/similar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarRolesQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+/// Query parameters for /api/v1/analytics/*
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+/// A role ranked by size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedRole {
+    pub name: String,
+    pub title: String,
+    pub stage: String,
+    pub permission_count: usize,
+}
+
+/// A permission ranked by how many roles grant it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrantedPermission {
+    pub name: String,
+    pub role_count: usize,
+}
+
+/// A permission only obtainable through one role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SingleRolePermission {
+    pub name: String,
+    pub role: RoleSummary,
+}
+
+/// Query parameters for /api/v1/admin/top-queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopQueriesQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Only include queries that returned no results
+    #[serde(default)]
+    pub zero_results: bool,
+}
+
+/// Query parameters for /api/v1/admin/query-log
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryLogExportQuery {
+    /// First day to include (YYYY-MM-DD, UTC); from the oldest record when unset
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Last day to include (YYYY-MM-DD, UTC); through today when unset
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+/// Summary of a loaded dataset for /api/v1/datasets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    pub id: String,
+    pub last_updated: Option<String>,
+    pub total_permissions: usize,
+    pub total_roles: usize,
+}
+
+/// Payload for /api/v1/permissions/{name}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionDetail {
+    pub name: String,
+    pub service: String,
+    pub provider: String,
+    pub resource: String,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub granted_by_roles: Vec<RoleSummary>,
+}
+
+/// A role's state in one dataset, relative to the previous dataset containing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleHistoryEntry {
+    pub dataset: String,
+    pub last_updated: Option<String>,
+    pub present: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+    pub permission_count: usize,
+    pub permissions_added: Vec<String>,
+    pub permissions_removed: Vec<String>,
+}
+
+/// Payload for /api/v1/roles/{name}/history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleHistory {
+    pub name: String,
+    pub entries: Vec<RoleHistoryEntry>,
+}
+
+/// A custom role definition uploaded by an organization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRoleInput {
+    pub name: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_stage")]
+    pub stage: String,
+    pub included_permissions: Vec<String>,
+}
+
+/// Request body for POST /api/v1/custom-roles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRolesRequest {
+    pub roles: Vec<CustomRoleInput>,
+}
+
+/// Payload for POST /api/v1/custom-roles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRolesData {
+    pub indexed: usize,
+    pub names: Vec<String>,
+}
+
+/// Payload for /api/v1/stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsData {
+    pub total_permissions: usize,
+    pub total_roles: usize,
+    pub indexed: bool,
+    pub version: String,
+    pub last_updated: Option<String>,
+    pub permissions_by_service: BTreeMap<String, usize>,
+    pub roles_by_stage: BTreeMap<String, usize>,
+    pub average_permissions_per_role: f64,
+    pub index: IndexStats,
+    /// Searches answered with 503 after exceeding the search timeout
+    pub search_timeouts: u64,
+}
+
+/// Live index build cost and size, in /api/v1/stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStats {
+    /// Time to decode the dataset and build the engine
+    pub build_ms: u64,
+    /// Approximate heap bytes per structure
+    pub memory_bytes: IndexMemory,
+}
+
+/// Payload for /api/v1/version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionData {
+    pub version: String,
+    pub git_sha: String,
+    /// `last_updated` of the live dataset
+    pub dataset: Option<String>,
+    /// `INDEX_FORMAT_VERSION` of the prebuilt index this build reads
+    pub index_format_version: u32,
+}
+
+/// Payload for /api/v1/health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthData {
+    pub status: String,
+    pub version: String,
+    pub last_updated: Option<String>,
+    /// Seconds since the live dataset's `last_updated`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset_age_secs: Option<u64>,
+    /// Whether the dataset is older than MAX_DATASET_AGE_HOURS, or its age
+    /// is unknown
+    pub stale: bool,
 }
 
 fn default_mode() -> SearchMode {
@@ -45,3 +413,11 @@ fn default_mode() -> SearchMode {
 fn default_limit() -> usize {
     10
 }
+
+fn default_stage() -> String {
+    "GA".to_string()
+}
+
+fn default_min_score() -> f64 {
+    DEFAULT_MIN_SCORE
+}
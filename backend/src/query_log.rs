@@ -0,0 +1,368 @@
+//! Anonymized search query analytics
+//!
+//! Keeps the most recent queries in a fixed-size ring buffer. Only the
+//! normalized query text, mode, filters, result count and latency are
+//! recorded — no client information. When a path is configured, records are
+//! appended to an NDJSON file, rotated by size, and reloaded on startup; the
+//! files also back date-range exports.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::models::SearchMode;
+
+/// Default number of queries kept in memory
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Default size past which the persisted log is rotated
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated files kept (`query-log.ndjson.1` is newest)
+pub const DEFAULT_MAX_FILES: usize = 5;
+
+/// A single recorded search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRecord {
+    pub query: String,
+    pub mode: SearchMode,
+    pub zero_results: bool,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Permissions and roles returned (absent from older records)
+    #[serde(default)]
+    pub result_count: usize,
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub filters: QueryFilters,
+}
+
+/// Search parameters narrowing a query, when set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Snapshot searched instead of the live dataset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_deprecated: bool,
+}
+
+/// When the persisted log is rotated
+#[derive(Debug, Clone, Copy)]
+pub struct Rotation {
+    /// Size past which the active file is rotated
+    pub max_bytes: u64,
+    /// Rotated files kept before the oldest is deleted
+    pub max_files: usize,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation { max_bytes: DEFAULT_MAX_BYTES, max_files: DEFAULT_MAX_FILES }
+    }
+}
+
+/// The active NDJSON file and its rotated predecessors
+struct Persistence {
+    path: PathBuf,
+    rotation: Rotation,
+    /// Current size of the active file
+    size: u64,
+}
+
+/// Aggregated view of one normalized query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopQuery {
+    pub query: String,
+    pub count: usize,
+    pub zero_result_count: usize,
+    pub modes: BTreeMap<String, usize>,
+    pub last_seen: u64,
+}
+
+/// Ring buffer of recent queries, optionally persisted
+pub struct QueryLog {
+    records: Mutex<VecDeque<QueryRecord>>,
+    capacity: usize,
+    persistence: Option<Mutex<Persistence>>,
+}
+
+impl QueryLog {
+    /// In-memory log holding at most `capacity` queries
+    pub fn new(capacity: usize) -> Self {
+        QueryLog {
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            persistence: None,
+        }
+    }
+
+    /// Log persisted to an NDJSON file, restoring the newest `capacity`
+    /// records from it and its rotated files
+    pub fn with_persistence(capacity: usize, path: &Path, rotation: Rotation) -> io::Result<Self> {
+        let mut records = VecDeque::with_capacity(capacity);
+        for file in rotated_files(path, rotation.max_files).into_iter().rev().chain([path.to_path_buf()]) {
+            for record in read_records(&file)? {
+                records.push_back(record);
+                if records.len() > capacity {
+                    records.pop_front();
+                }
+            }
+        }
+
+        // Create the file up front so a bad path fails at startup
+        let size = OpenOptions::new().create(true).append(true).open(path)?.metadata()?.len();
+
+        Ok(QueryLog {
+            records: Mutex::new(records),
+            capacity,
+            persistence: Some(Mutex::new(Persistence { path: path.to_path_buf(), rotation, size })),
+        })
+    }
+
+    /// Record a search
+    pub fn record(&self, query: &str, mode: SearchMode, filters: QueryFilters, result_count: usize, latency: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let record = QueryRecord {
+            query: normalize(query),
+            mode,
+            zero_results: result_count == 0,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            result_count,
+            latency_ms: latency.as_millis() as u64,
+            filters,
+        };
+
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.lock().unwrap().append(&record) {
+                log::warn!("Failed to persist query record: {}", e);
+            }
+        }
+
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Records with `from <= timestamp < to`, oldest first: every persisted
+    /// record still on disk, or only the in-memory ones without persistence
+    pub fn export(&self, from: u64, to: u64) -> io::Result<Vec<QueryRecord>> {
+        let in_range = |r: &QueryRecord| (from..to).contains(&r.timestamp);
+        let Some(persistence) = &self.persistence else {
+            return Ok(self.records.lock().unwrap().iter().filter(|r| in_range(r)).cloned().collect());
+        };
+
+        // Held so a rotation can't move files mid-read
+        let persistence = persistence.lock().unwrap();
+        let mut records = Vec::new();
+        for file in rotated_files(&persistence.path, persistence.rotation.max_files)
+            .into_iter()
+            .rev()
+            .chain([persistence.path.clone()])
+        {
+            records.extend(read_records(&file)?.into_iter().filter(in_range));
+        }
+        Ok(records)
+    }
+
+    /// Most frequent queries, optionally only those that returned nothing
+    pub fn top_queries(&self, limit: usize, zero_results_only: bool) -> Vec<TopQuery> {
+        let records = self.records.lock().unwrap();
+        let mut by_query: HashMap<&str, TopQuery> = HashMap::new();
+
+        for record in records.iter() {
+            let entry = by_query.entry(&record.query).or_insert_with(|| TopQuery {
+                query: record.query.clone(),
+                count: 0,
+                zero_result_count: 0,
+                modes: BTreeMap::new(),
+                last_seen: 0,
+            });
+            entry.count += 1;
+            if record.zero_results {
+                entry.zero_result_count += 1;
+            }
+            *entry.modes.entry(record.mode.as_str().to_string()).or_default() += 1;
+            entry.last_seen = entry.last_seen.max(record.timestamp);
+        }
+
+        let mut top: Vec<TopQuery> = by_query
+            .into_values()
+            .filter(|q| !zero_results_only || q.zero_result_count > 0)
+            .collect();
+        top.sort_by(|a, b| {
+            let (a_count, b_count) = if zero_results_only {
+                (a.zero_result_count, b.zero_result_count)
+            } else {
+                (a.count, b.count)
+            };
+            b_count.cmp(&a_count).then_with(|| a.query.cmp(&b.query))
+        });
+        top.truncate(limit);
+        top
+    }
+
+    /// Number of queries currently retained
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Lowercase and collapse whitespace so equivalent queries aggregate together
+fn normalize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl Persistence {
+    /// Append a record, rotating first if it would push the file past
+    /// `max_bytes`
+    fn append(&mut self, record: &QueryRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        if self.size > 0 && self.size + line.len() as u64 > self.rotation.max_bytes {
+            self.rotate()?;
+        }
+        OpenOptions::new().create(true).append(true).open(&self.path)?.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `path.N` to `path.N+1` (dropping the oldest) and move the
+    /// active file to `path.1`
+    fn rotate(&mut self) -> io::Result<()> {
+        let files = rotated_files(&self.path, self.rotation.max_files);
+        match files.last() {
+            Some(oldest) if oldest.exists() => fs::remove_file(oldest)?,
+            _ => {}
+        }
+        for pair in files.windows(2).rev() {
+            if pair[0].exists() {
+                fs::rename(&pair[0], &pair[1])?;
+            }
+        }
+        match files.first() {
+            Some(newest) => fs::rename(&self.path, newest)?,
+            None => fs::remove_file(&self.path)?,
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Rotated file paths, newest (`path.1`) first
+fn rotated_files(path: &Path, max_files: usize) -> Vec<PathBuf> {
+    (1..=max_files)
+        .map(|n| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        })
+        .collect()
+}
+
+/// Records of an NDJSON file, skipping unparseable lines (empty if missing)
+fn read_records(path: &Path) -> io::Result<Vec<QueryRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut records = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        if let Ok(record) = serde_json::from_str::<QueryRecord>(&line?) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(log: &QueryLog, query: &str) {
+        log.record(query, SearchMode::Prefix, QueryFilters::default(), 1, Duration::ZERO);
+    }
+
+    fn queries(records: &[QueryRecord]) -> Vec<&str> {
+        records.iter().map(|r| r.query.as_str()).collect()
+    }
+
+    #[test]
+    fn rotates_at_the_size_limit_and_restores_across_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("query-log.ndjson");
+
+        // Measure one line, then allow exactly two per file
+        let probe = QueryLog::with_persistence(10, &path, Rotation::default()).unwrap();
+        record(&probe, "q0");
+        let line = fs::metadata(&path).unwrap().len();
+        fs::remove_file(&path).unwrap();
+
+        let rotation = Rotation { max_bytes: 2 * line, max_files: 2 };
+        let log = QueryLog::with_persistence(10, &path, rotation).unwrap();
+        for query in ["q1", "q2", "q3", "q4", "q5", "q6", "q7"] {
+            record(&log, query);
+        }
+        // q1-q2 were rotated out past max_files; a full file isn't rotated early
+        let file = |suffix: &str| read_records(Path::new(&format!("{}{}", path.display(), suffix))).unwrap();
+        assert_eq!(queries(&file("")), vec!["q7"]);
+        assert_eq!(queries(&file(".1")), vec!["q5", "q6"]);
+        assert_eq!(queries(&file(".2")), vec!["q3", "q4"]);
+        assert!(!Path::new(&format!("{}.3", path.display())).exists());
+
+        // Restored oldest first, keeping only the newest `capacity`
+        let restored = QueryLog::with_persistence(3, &path, rotation).unwrap();
+        let top: Vec<String> = restored.top_queries(10, false).into_iter().map(|q| q.query).collect();
+        assert_eq!(top, vec!["q5", "q6", "q7"]);
+    }
+
+    #[test]
+    fn export_reads_a_half_open_range_from_every_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("query-log.ndjson");
+        let line = |query: &str, timestamp: u64| {
+            format!(r#"{{"query":"{}","mode":"prefix","zero_results":false,"timestamp":{}}}"#, query, timestamp)
+        };
+        // .2 is missing; unparseable lines are skipped
+        fs::write(format!("{}.1", path.display()), format!("{}\nnot json\n{}\n", line("a", 100), line("b", 200))).unwrap();
+        fs::write(&path, format!("{}\n{}\n", line("c", 300), line("d", 400))).unwrap();
+
+        let log = QueryLog::with_persistence(10, &path, Rotation { max_bytes: DEFAULT_MAX_BYTES, max_files: 2 }).unwrap();
+        assert_eq!(queries(&log.export(0, u64::MAX).unwrap()), vec!["a", "b", "c", "d"]);
+        assert_eq!(queries(&log.export(200, 400).unwrap()), vec!["b", "c"]);
+        assert!(log.export(500, 600).unwrap().is_empty());
+    }
+
+    #[test]
+    fn export_without_persistence_uses_memory() {
+        let log = QueryLog::new(2);
+        for query in ["a", "b", "c"] {
+            record(&log, query);
+        }
+        assert_eq!(queries(&log.export(0, u64::MAX).unwrap()), vec!["b", "c"]);
+        assert!(log.export(0, 1).unwrap().is_empty());
+    }
+}
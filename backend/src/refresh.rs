@@ -0,0 +1,118 @@
+//! Periodic reloading of the live dataset
+//!
+//! With `IAM_REFRESH_INTERVAL_SECS` set, a background task reloads the
+//! dataset from its `Source` on that interval and swaps it into `AppState`
+//! when `last_updated` changes. Loading and indexing run on the server's
+//! system thread, not the HTTP workers, which keep answering from the old
+//! index until the swap. Failed refreshes keep the current index.
+
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::api::AppState;
+use crate::dataset::Source;
+
+/// Outcome counters for background refreshes
+#[derive(Default)]
+pub struct RefreshMetrics {
+    successes: AtomicU64,
+    failures: AtomicU64,
+    swaps: AtomicU64,
+    last: Mutex<LastRefresh>,
+}
+
+#[derive(Default)]
+struct LastRefresh {
+    attempt_at: Option<u64>,
+    success_at: Option<u64>,
+    duration_ms: Option<u64>,
+    error: Option<String>,
+}
+
+/// Payload for /api/v1/admin/refresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshStatus {
+    /// Refreshes that loaded a dataset, changed or not
+    pub successes: u64,
+    pub failures: u64,
+    /// Successful refreshes that found a new dataset and swapped it in
+    pub swaps: u64,
+    /// Unix seconds of the latest attempt
+    pub last_attempt_at: Option<u64>,
+    /// Unix seconds of the latest successful refresh
+    pub last_success_at: Option<u64>,
+    pub last_duration_ms: Option<u64>,
+    /// Error of the latest attempt, if it failed
+    pub last_error: Option<String>,
+}
+
+impl RefreshMetrics {
+    fn record(&self, started: Instant, result: Result<bool, String>) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).ok();
+        let mut last = self.last.lock().unwrap();
+        last.attempt_at = now;
+        last.duration_ms = Some(started.elapsed().as_millis() as u64);
+        match result {
+            Ok(swapped) => {
+                self.successes.fetch_add(1, Ordering::Relaxed);
+                if swapped {
+                    self.swaps.fetch_add(1, Ordering::Relaxed);
+                }
+                last.success_at = now;
+                last.error = None;
+            }
+            Err(e) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                last.error = Some(e);
+            }
+        }
+    }
+
+    pub fn status(&self) -> RefreshStatus {
+        let last = self.last.lock().unwrap();
+        RefreshStatus {
+            successes: self.successes.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            swaps: self.swaps.load(Ordering::Relaxed),
+            last_attempt_at: last.attempt_at,
+            last_success_at: last.success_at,
+            last_duration_ms: last.duration_ms,
+            last_error: last.error.clone(),
+        }
+    }
+}
+
+/// Reload `source` once and swap it in if it changed; returns whether it did
+pub async fn refresh(state: &AppState, source: &Source) -> anyhow::Result<bool> {
+    let dataset = source.load().await?;
+    if dataset.last_updated.is_some() && dataset.last_updated == state.live().last_updated {
+        return Ok(false);
+    }
+    state.replace_live(dataset);
+    Ok(true)
+}
+
+/// Refresh the live dataset from `source` every `interval`, starting one
+/// interval from now
+pub fn spawn(state: web::Data<AppState>, source: Source, interval: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval_at(
+            actix_web::rt::time::Instant::now() + interval,
+            interval,
+        );
+        loop {
+            ticker.tick().await;
+            let started = Instant::now();
+            let result = refresh(&state, &source).await;
+            match &result {
+                Ok(true) => log::info!("Refreshed dataset from {}", source),
+                Ok(false) => log::info!("Dataset unchanged at {}", source),
+                Err(e) => log::warn!("Dataset refresh failed: {:#}", e),
+            }
+            state.refresh.record(started, result.map_err(|e| format!("{:#}", e)));
+        }
+    });
+}
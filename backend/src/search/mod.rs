@@ -1,5 +0,0 @@
-/// Search engine implementation with multiple index types
-
-pub mod engine;
-
-pub use engine::SearchEngine;
@@ -0,0 +1,251 @@
+// ============================================
+// gcpiam - Offline GCP IAM Search CLI
+// ============================================
+
+mod mcp;
+mod output;
+mod tui;
+
+use anyhow::{bail, Context};
+use clap::{Parser, Subcommand, ValueEnum};
+use gcpiam_core::search::engine::{Permission, Role};
+use gcpiam_core::search::SearchOptions;
+use gcpiam_core::{IamDataFile, PrebuiltIndex, RoleComparison, SearchEngine};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(name = "gcpiam", version, about = "Search GCP IAM roles and permissions offline")]
+struct Cli {
+    /// iam-data.json or a prebuilt index (.bin)
+    #[arg(long, global = true, env = "IAM_DATA_PATH", default_value = "data/iam-data.json")]
+    data: PathBuf,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Search roles and permissions
+    Search {
+        query: String,
+        #[arg(long, value_enum, default_value_t = Mode::Prefix)]
+        mode: Mode,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Include roles in the DEPRECATED stage
+        #[arg(long)]
+        include_deprecated: bool,
+    },
+    /// Role details
+    Role {
+        #[command(subcommand)]
+        command: ShowCommand,
+    },
+    /// Permission details
+    Perm {
+        #[command(subcommand)]
+        command: ShowCommand,
+    },
+    /// Compare the permissions of two roles
+    Compare { first: String, second: String },
+    /// Interactive search with role and permission detail panes
+    Tui,
+    /// Serve the catalog as MCP tools over stdio
+    Mcp,
+}
+
+#[derive(Subcommand)]
+enum ShowCommand {
+    /// Show a single entry by name
+    Show { name: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    Exact,
+    Prefix,
+    Fuzzy,
+}
+
+impl Mode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Exact => "exact",
+            Mode::Prefix => "prefix",
+            Mode::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let engine = load_engine(&cli.data)?;
+
+    match cli.command {
+        Command::Search { query, mode, limit, include_deprecated } => {
+            let options = SearchOptions {
+                limit,
+                include_deprecated,
+                ..SearchOptions::default()
+            };
+            search(&engine, &query, mode, &options, cli.format)
+        }
+        Command::Role { command: ShowCommand::Show { name } } => {
+            let role = engine
+                .get_role(&name)
+                .with_context(|| format!("Role '{}' not found", name))?;
+            show_role(role, cli.format)
+        }
+        Command::Perm { command: ShowCommand::Show { name } } => {
+            let permission = engine
+                .get_permission(&name)
+                .with_context(|| format!("Permission '{}' not found", name))?;
+            show_permission(&engine, permission, cli.format)
+        }
+        Command::Compare { first, second } => {
+            let comparison = compare(&engine, &first, &second)?;
+            show_comparison(&comparison, cli.format)
+        }
+        Command::Tui => Ok(tui::run(&engine)?),
+        Command::Mcp => Ok(mcp::run(&engine)?),
+    }
+}
+
+/// Load iam-data.json, or a prebuilt rkyv index for any other extension
+fn load_engine(path: &Path) -> anyhow::Result<SearchEngine> {
+    let bytes = fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+
+    if path.extension().is_some_and(|ext| ext == "json") {
+        let data: IamDataFile = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        SearchEngine::from_dataset(data).with_context(|| format!("Invalid dataset {}", path.display()))
+    } else {
+        let index = PrebuiltIndex::from_bytes(&bytes)
+            .with_context(|| format!("Failed to decode index {}", path.display()))?;
+        index.to_engine().with_context(|| format!("Invalid index {}", path.display()))
+    }
+}
+
+fn search(engine: &SearchEngine, query: &str, mode: Mode, options: &SearchOptions, format: Format) -> anyhow::Result<()> {
+    let roles = engine.search_roles(query, mode.as_str(), options);
+    let permissions = engine.search_permissions(query, mode.as_str(), options);
+
+    match format {
+        Format::Json => print_json(&serde_json::json!({ "roles": roles, "permissions": permissions })),
+        Format::Table => {
+            let role_rows: Vec<Vec<String>> = roles
+                .iter()
+                .map(|r| vec![r.name.clone(), r.title.clone(), r.stage.clone(), r.permission_count.to_string()])
+                .collect();
+            let permission_rows: Vec<Vec<String>> = permissions
+                .iter()
+                .map(|p| {
+                    let granted = engine.get_permission(&p.name).map_or(0, |perm| perm.granted_by_roles.len());
+                    vec![p.name.clone(), p.service.clone(), granted.to_string()]
+                })
+                .collect();
+
+            println!("Roles ({})", role_rows.len());
+            print!("{}", output::table(&["NAME", "TITLE", "STAGE", "PERMISSIONS"], &role_rows));
+            println!("\nPermissions ({})", permission_rows.len());
+            print!("{}", output::table(&["NAME", "SERVICE", "GRANTED BY"], &permission_rows));
+            Ok(())
+        }
+    }
+}
+
+fn show_role(role: &Role, format: Format) -> anyhow::Result<()> {
+    match format {
+        Format::Json => print_json(role),
+        Format::Table => {
+            println!("{}", role.name);
+            println!("  Title:       {}", role.title);
+            println!("  Stage:       {}", role.stage);
+            println!("  Description: {}", role.description);
+            println!("\nPermissions ({})", role.included_permissions.len());
+            for permission in &role.included_permissions {
+                println!("  {}", permission);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn show_permission(engine: &SearchEngine, permission: &Permission, format: Format) -> anyhow::Result<()> {
+    match format {
+        Format::Json => print_json(permission),
+        Format::Table => {
+            println!("{}", permission.name);
+            println!("  Service:  {}", permission.service);
+            println!("  Resource: {}", permission.resource);
+            println!("  Action:   {}", permission.action);
+
+            let rows: Vec<Vec<String>> = permission
+                .granted_by_roles
+                .iter()
+                .filter_map(|name| engine.role_summary(name))
+                .map(|r| vec![r.name, r.title, r.stage])
+                .collect();
+            println!("\nGranted by ({})", rows.len());
+            print!("{}", output::table(&["ROLE", "TITLE", "STAGE"], &rows));
+            Ok(())
+        }
+    }
+}
+
+fn compare(engine: &SearchEngine, first: &str, second: &str) -> anyhow::Result<RoleComparison> {
+    let (Some(a), Some(b)) = (engine.get_role(first), engine.get_role(second)) else {
+        let missing = if engine.get_role(first).is_none() { first } else { second };
+        bail!("Role '{}' not found", missing);
+    };
+
+    Ok(RoleComparison::new(
+        &a.name,
+        a.included_permissions.iter().map(String::as_str),
+        &b.name,
+        b.included_permissions.iter().map(String::as_str),
+    ))
+}
+
+fn show_comparison(comparison: &RoleComparison, format: Format) -> anyhow::Result<()> {
+    match format {
+        Format::Json => print_json(comparison),
+        Format::Table => {
+            println!("{} vs {}", comparison.first, comparison.second);
+            println!("  Similarity: {:.2}", comparison.similarity);
+            println!("  Shared:     {}", comparison.shared.len());
+            if let Some((outer, inner)) = comparison.inclusion() {
+                println!("  Includes:   {} grants everything {} does", outer, inner);
+            }
+
+            println!("\nOnly in {} ({})", comparison.first, comparison.only_in_first.len());
+            for permission in &comparison.only_in_first {
+                println!("  + {}", permission);
+            }
+            println!("\nOnly in {} ({})", comparison.second, comparison.only_in_second.len());
+            for permission in &comparison.only_in_second {
+                println!("  + {}", permission);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
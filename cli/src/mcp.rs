@@ -0,0 +1,240 @@
+//! Model Context Protocol server (`gcpiam mcp`)
+//!
+//! Speaks newline-delimited JSON-RPC 2.0 on stdin/stdout and exposes the
+//! loaded catalog as read-only tools. Nothing outside the dataset passed via
+//! `--data` is reachable and result sizes are capped, so an assistant can be
+//! pointed at it without further sandboxing. Diagnostics go to stderr because
+//! stdout carries the protocol.
+
+use gcpiam_core::search::engine::MAX_EXACT_COVER_PERMISSIONS;
+use gcpiam_core::search::SearchOptions;
+use gcpiam_core::SearchEngine;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+use crate::compare;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Upper bound on `limit` for search tools
+const MAX_LIMIT: usize = 50;
+const DEFAULT_LIMIT: usize = 10;
+
+/// Upper bound on permissions accepted by recommend_roles
+const MAX_RECOMMEND_PERMISSIONS: usize = 500;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve requests until stdin closes
+pub fn run(engine: &SearchEngine) -> io::Result<()> {
+    let (permissions, roles) = engine.stats();
+    eprintln!("gcpiam MCP server ready ({} roles, {} permissions)", roles, permissions);
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(engine, &message),
+            Err(e) => Some(error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e))),
+        };
+
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Response for one message, or `None` for notifications
+fn handle_message(engine: &SearchEngine, message: &Value) -> Option<Value> {
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(error_response(id.unwrap_or(Value::Null), INVALID_REQUEST, "Missing method".to_string()));
+    };
+
+    // Notifications (no id) never get a reply
+    let id = id?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "gcpiam", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(engine, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("Method '{}' not found", method))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn tool_definitions() -> Value {
+    let search_schema = json!({
+        "type": "object",
+        "properties": {
+            "query": { "type": "string", "description": "Search text; supports \"quoted phrases\" and -exclusions" },
+            "mode": { "type": "string", "enum": ["exact", "prefix", "fuzzy"], "default": "prefix" },
+            "limit": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT, "default": DEFAULT_LIMIT },
+        },
+        "required": ["query"],
+    });
+
+    json!([
+        {
+            "name": "search_roles",
+            "description": "Search predefined GCP IAM roles by name or title",
+            "inputSchema": search_schema,
+        },
+        {
+            "name": "search_permissions",
+            "description": "Search GCP IAM permissions by name, with the roles that grant each",
+            "inputSchema": search_schema,
+        },
+        {
+            "name": "compare_roles",
+            "description": "Compare the permissions of two roles: shared, only in each, and Jaccard similarity",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "first": { "type": "string", "description": "Role name, e.g. roles/storage.objectViewer" },
+                    "second": { "type": "string", "description": "Role name, e.g. roles/storage.objectAdmin" },
+                },
+                "required": ["first", "second"],
+            },
+        },
+        {
+            "name": "recommend_roles",
+            "description": "Suggest a small set of predefined roles that together grant every listed permission",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "permissions": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "maxItems": MAX_RECOMMEND_PERMISSIONS,
+                    },
+                },
+                "required": ["permissions"],
+            },
+        },
+        {
+            "name": "minimal_role_cover",
+            "description": "Find the provably smallest combinations of predefined roles granting every listed permission, ranked by how many extra permissions each grants",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "permissions": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "maxItems": MAX_EXACT_COVER_PERMISSIONS,
+                    },
+                },
+                "required": ["permissions"],
+            },
+        },
+    ])
+}
+
+/// Run a tool; tool failures are reported in the result with `isError`
+fn call_tool(engine: &SearchEngine, params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+    let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+    let output = match name {
+        "search_roles" => search_args(&args).map(|(query, mode, options)| {
+            json!({ "roles": engine.search_roles(&query, &mode, &options) })
+        }),
+        "search_permissions" => search_args(&args).map(|(query, mode, options)| {
+            json!({ "permissions": engine.search_permissions(&query, &mode, &options) })
+        }),
+        "compare_roles" => match (str_arg(&args, "first"), str_arg(&args, "second")) {
+            (Some(first), Some(second)) => compare(engine, first, second)
+                .map(|comparison| json!(comparison))
+                .map_err(|e| e.to_string()),
+            _ => Err("Both 'first' and 'second' role names are required".to_string()),
+        },
+        "recommend_roles" => recommend_args(&args).map(|permissions| json!(engine.recommend_roles(&permissions))),
+        "minimal_role_cover" => recommend_args(&args).and_then(|permissions| {
+            engine
+                .minimal_role_cover(&permissions)
+                .map(|cover| json!(cover))
+                .ok_or(format!("At most {} distinct permissions are accepted", MAX_EXACT_COVER_PERMISSIONS))
+        }),
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool '{}'", name))),
+    };
+
+    Ok(match output {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+            "isError": false,
+        }),
+        Err(message) => json!({
+            "content": [{ "type": "text", "text": message }],
+            "isError": true,
+        }),
+    })
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(Value::as_str)
+}
+
+fn search_args(args: &Value) -> Result<(String, String, SearchOptions), String> {
+    let query = str_arg(args, "query").ok_or("'query' is required")?;
+    let mode = str_arg(args, "mode").unwrap_or("prefix");
+    if !matches!(mode, "exact" | "prefix" | "fuzzy") {
+        return Err(format!("Invalid mode '{}': expected exact, prefix or fuzzy", mode));
+    }
+    let limit = args
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_LIMIT, |l| l as usize)
+        .clamp(1, MAX_LIMIT);
+
+    let options = SearchOptions {
+        limit,
+        ..SearchOptions::default()
+    };
+    Ok((query.to_string(), mode.to_string(), options))
+}
+
+fn recommend_args(args: &Value) -> Result<Vec<String>, String> {
+    let permissions: Vec<String> = args
+        .get("permissions")
+        .and_then(Value::as_array)
+        .ok_or("'permissions' must be an array of permission names")?
+        .iter()
+        .filter_map(|p| p.as_str().map(String::from))
+        .collect();
+
+    if permissions.is_empty() {
+        return Err("At least one permission is required".to_string());
+    }
+    if permissions.len() > MAX_RECOMMEND_PERMISSIONS {
+        return Err(format!("At most {} permissions are accepted", MAX_RECOMMEND_PERMISSIONS));
+    }
+    Ok(permissions)
+}
@@ -0,0 +1,28 @@
+//! Plain-text table rendering
+
+/// Render rows under headers with columns padded to the widest cell
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    push_row(&mut out, headers.iter().map(|h| h.to_string()), &widths);
+    push_row(&mut out, widths.iter().map(|w| "-".repeat(*w)), &widths);
+    for row in rows {
+        push_row(&mut out, row.iter().cloned(), &widths);
+    }
+    out
+}
+
+fn push_row(out: &mut String, cells: impl Iterator<Item = String>, widths: &[usize]) {
+    let line: Vec<String> = cells
+        .zip(widths)
+        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+        .collect();
+    out.push_str(line.join("  ").trim_end());
+    out.push('\n');
+}
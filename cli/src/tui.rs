@@ -0,0 +1,261 @@
+//! Interactive terminal explorer (`gcpiam tui`)
+//!
+//! Searches incrementally as you type and shows the selected role or
+//! permission in a detail pane. Permission lists are copied to the system
+//! clipboard with an OSC 52 escape sequence, which works over SSH and needs
+//! no platform clipboard libraries.
+
+use base64::Engine as _;
+use gcpiam_core::search::SearchOptions;
+use gcpiam_core::SearchEngine;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::{self, Write};
+
+use crate::Mode;
+
+/// Results shown per kind while typing
+const RESULT_LIMIT: usize = 50;
+
+/// A row in the results list
+enum Item {
+    Role { name: String, title: String },
+    Permission { name: String },
+}
+
+impl Item {
+    fn name(&self) -> &str {
+        match self {
+            Item::Role { name, .. } | Item::Permission { name } => name,
+        }
+    }
+}
+
+struct App<'a> {
+    engine: &'a SearchEngine,
+    query: String,
+    mode: Mode,
+    items: Vec<Item>,
+    list: ListState,
+    detail_scroll: u16,
+    status: String,
+}
+
+impl<'a> App<'a> {
+    fn new(engine: &'a SearchEngine) -> Self {
+        App {
+            engine,
+            query: String::new(),
+            mode: Mode::Prefix,
+            items: Vec::new(),
+            list: ListState::default(),
+            detail_scroll: 0,
+            status: "Type to search · ↑↓ select · Tab mode · Ctrl+Y copy permissions · Esc quit".to_string(),
+        }
+    }
+
+    /// Re-run the search for the current query and mode
+    fn search(&mut self) {
+        let options = SearchOptions {
+            limit: RESULT_LIMIT,
+            ..SearchOptions::default()
+        };
+        let roles = self.engine.search_roles(&self.query, self.mode.as_str(), &options);
+        let permissions = self.engine.search_permissions(&self.query, self.mode.as_str(), &options);
+
+        self.items = roles
+            .into_iter()
+            .map(|r| Item::Role { name: r.name, title: r.title })
+            .chain(permissions.into_iter().map(|p| Item::Permission { name: p.name }))
+            .collect();
+        self.list.select(if self.items.is_empty() { None } else { Some(0) });
+        self.detail_scroll = 0;
+    }
+
+    fn selected(&self) -> Option<&Item> {
+        self.list.selected().and_then(|i| self.items.get(i))
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.items.len() as isize - 1);
+        self.list.select(Some(next as usize));
+        self.detail_scroll = 0;
+    }
+
+    /// Permissions to copy for the selected item, one per line
+    fn permission_list(&self) -> Option<String> {
+        match self.selected()? {
+            Item::Role { name, .. } => self
+                .engine
+                .get_role(name)
+                .map(|role| role.included_permissions.join("\n")),
+            Item::Permission { name } => Some(name.clone()),
+        }
+    }
+
+    /// Handle a key press; returns false when the app should exit
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<bool> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if ctrl => return Ok(false),
+            KeyCode::Char('y') if ctrl => match self.permission_list() {
+                Some(text) => {
+                    copy_to_clipboard(&text)?;
+                    self.status = format!("Copied {} line(s) to clipboard", text.lines().count());
+                }
+                None => self.status = "Nothing selected to copy".to_string(),
+            },
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.search();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.search();
+            }
+            KeyCode::Tab => {
+                self.mode = match self.mode {
+                    Mode::Prefix => Mode::Fuzzy,
+                    Mode::Fuzzy => Mode::Exact,
+                    Mode::Exact => Mode::Prefix,
+                };
+                self.search();
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.detail_scroll = self.detail_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.detail_scroll = self.detail_scroll.saturating_add(10),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1), Constraint::Length(1)])
+            .split(frame.area());
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(rows[1]);
+
+        let input = Paragraph::new(self.query.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Search ({}) ", self.mode.as_str())),
+        );
+        frame.render_widget(input, rows[0]);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Role { name, title } => ListItem::new(Line::from(vec![
+                    Span::styled("role ", Style::default().fg(Color::Cyan)),
+                    Span::raw(name.clone()),
+                    Span::styled(format!("  {}", title), Style::default().fg(Color::DarkGray)),
+                ])),
+                Item::Permission { name } => ListItem::new(Line::from(vec![
+                    Span::styled("perm ", Style::default().fg(Color::Green)),
+                    Span::raw(name.clone()),
+                ])),
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(" Results ({}) ", self.items.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, columns[0], &mut self.list);
+
+        let detail = Paragraph::new(self.detail_lines())
+            .block(Block::default().borders(Borders::ALL).title(" Details "))
+            .wrap(Wrap { trim: false })
+            .scroll((self.detail_scroll, 0));
+        frame.render_widget(detail, columns[1]);
+
+        frame.render_widget(Paragraph::new(self.status.as_str()), rows[2]);
+    }
+
+    fn detail_lines(&self) -> Vec<Line<'static>> {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let Some(item) = self.selected() else {
+            return vec![Line::from("No selection")];
+        };
+
+        match item {
+            Item::Role { .. } => {
+                let Some(role) = self.engine.get_role(item.name()) else {
+                    return vec![];
+                };
+                let mut lines = vec![
+                    Line::styled(role.name.clone(), bold),
+                    Line::from(format!("Title: {}", role.title)),
+                    Line::from(format!("Stage: {}", role.stage)),
+                    Line::from(""),
+                    Line::from(role.description.clone()),
+                    Line::from(""),
+                    Line::styled(format!("Permissions ({})", role.included_permissions.len()), bold),
+                ];
+                lines.extend(role.included_permissions.iter().map(|p| Line::from(format!("  {}", p))));
+                lines
+            }
+            Item::Permission { .. } => {
+                let Some(permission) = self.engine.get_permission(item.name()) else {
+                    return vec![];
+                };
+                let mut lines = vec![
+                    Line::styled(permission.name.clone(), bold),
+                    Line::from(format!("Service:  {}", permission.service)),
+                    Line::from(format!("Resource: {}", permission.resource)),
+                    Line::from(format!("Action:   {}", permission.action)),
+                    Line::from(""),
+                    Line::styled(format!("Granted by ({})", permission.granted_by_roles.len()), bold),
+                ];
+                lines.extend(
+                    permission
+                        .granted_by_roles
+                        .iter()
+                        .filter_map(|name| self.engine.role_summary(name))
+                        .map(|r| Line::from(format!("  {}  {}", r.name, r.title))),
+                );
+                lines
+            }
+        }
+    }
+}
+
+/// Run the explorer until the user quits
+pub fn run(engine: &SearchEngine) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, App::new(engine));
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !app.handle_key(key)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Copy text via the terminal's OSC 52 clipboard sequence
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+    stdout.flush()
+}
@@ -0,0 +1,42 @@
+//! Build the prebuilt rkyv index from iam-data.json
+//!
+//! Usage: cargo run -p gcpiam-core --example build_index -- <iam-data.json> <index.bin>
+//!
+//! Given services/index.json and a comma-separated service list instead,
+//! only those shards are indexed:
+//!     build_index -- data/services/index.json index.bin compute,storage
+
+use gcpiam_core::{IamDataFile, PrebuiltIndex, ShardIndex};
+use std::fs;
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let input = args.next().unwrap_or_else(|| "data/iam-data.json".to_string());
+    let output = args.next().unwrap_or_else(|| "index.bin".to_string());
+
+    let data: IamDataFile = match args.next() {
+        Some(services) => load_shards(Path::new(&input), &services)?,
+        None => serde_json::from_str(&fs::read_to_string(&input)?)?,
+    };
+    let encoded = PrebuiltIndex::build(&data).to_bytes()?;
+    fs::write(&output, &encoded)?;
+
+    println!("Wrote {} ({:.2} MB)", output, encoded.len() as f64 / 1024.0 / 1024.0);
+    Ok(())
+}
+
+/// The listed services' shards from a services/index.json, merged
+fn load_shards(index_path: &Path, services: &str) -> Result<IamDataFile, Box<dyn std::error::Error>> {
+    let index: ShardIndex = serde_json::from_str(&fs::read_to_string(index_path)?)?;
+    let dir = index_path.parent().unwrap_or(Path::new("."));
+    let mut shards = Vec::new();
+    for service in services.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let entry = index
+            .services
+            .get(service)
+            .ok_or_else(|| format!("No shard for service '{}' in {}", service, index_path.display()))?;
+        shards.push(serde_json::from_str(&fs::read_to_string(dir.join(&entry.path))?)?);
+    }
+    Ok(IamDataFile::merge(shards))
+}
@@ -0,0 +1,41 @@
+//! Build the sharded index (a routing table and one archive per service)
+//! from iam-data.json
+//!
+//! Usage: cargo run -p gcpiam-core --example build_shards -- <iam-data.json> <out-dir>
+//!
+//! Writes <out-dir>/routes.bin and <out-dir>/shards/<service>.bin, the
+//! layout the edge reads from its KV Store.
+
+use gcpiam_core::{IamDataFile, ShardedIndex};
+use std::fs;
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let input = args.next().unwrap_or_else(|| "data/iam-data.json".to_string());
+    let output = args.next().unwrap_or_else(|| "shards".to_string());
+
+    let data: IamDataFile = serde_json::from_str(&fs::read_to_string(&input)?)?;
+    let sharded = ShardedIndex::build(&data);
+
+    let dir = Path::new(&output);
+    fs::create_dir_all(dir.join("shards"))?;
+    let routes = sharded.routes.to_bytes()?;
+    fs::write(dir.join("routes.bin"), &routes)?;
+    let mut largest = 0;
+    for (route, shard) in sharded.routes.services.iter().zip(&sharded.shards) {
+        let encoded = shard.to_bytes()?;
+        largest = largest.max(encoded.len());
+        fs::write(dir.join("shards").join(format!("{}.bin", route.service)), &encoded)?;
+    }
+
+    println!(
+        "Wrote {} routes ({:.2} MB) and {} shards (largest {:.2} MB) to {}",
+        sharded.routes.roles.len(),
+        routes.len() as f64 / 1024.0 / 1024.0,
+        sharded.shards.len(),
+        largest as f64 / 1024.0 / 1024.0,
+        output
+    );
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Permission differences between two roles
+//!
+//! Shared by the CLI's `compare` command and the edge's comparison page and
+//! JSON endpoint so both report the same diff.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::hierarchy;
+
+/// How two compared roles relate by definition (see `hierarchy`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoleRelationship {
+    FirstIncludesSecond,
+    SecondIncludesFirst,
+}
+
+/// Permissions only in the first role, in both, and only in the second,
+/// each in name order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleComparison {
+    pub first: String,
+    pub second: String,
+    /// Jaccard similarity of the two permission sets
+    pub similarity: f64,
+    pub shared: Vec<String>,
+    pub only_in_first: Vec<String>,
+    pub only_in_second: Vec<String>,
+    /// Set when one role includes the other (`roles/owner` and
+    /// `roles/viewer`); the including role is then credited with all of the
+    /// other's permissions, so they appear as shared rather than missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationship: Option<RoleRelationship>,
+}
+
+impl RoleComparison {
+    pub fn new<'a>(
+        first: &str,
+        first_permissions: impl IntoIterator<Item = &'a str>,
+        second: &str,
+        second_permissions: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let mut a_perms: BTreeSet<&str> = first_permissions.into_iter().collect();
+        let mut b_perms: BTreeSet<&str> = second_permissions.into_iter().collect();
+        let relationship = if hierarchy::includes(first, second) {
+            a_perms.extend(&b_perms);
+            Some(RoleRelationship::FirstIncludesSecond)
+        } else if hierarchy::includes(second, first) {
+            b_perms.extend(&a_perms);
+            Some(RoleRelationship::SecondIncludesFirst)
+        } else {
+            None
+        };
+        let shared: Vec<String> = a_perms.intersection(&b_perms).map(|p| p.to_string()).collect();
+        let union = a_perms.union(&b_perms).count();
+
+        RoleComparison {
+            first: first.to_string(),
+            second: second.to_string(),
+            similarity: if union == 0 { 0.0 } else { shared.len() as f64 / union as f64 },
+            only_in_first: a_perms.difference(&b_perms).map(|p| p.to_string()).collect(),
+            only_in_second: b_perms.difference(&a_perms).map(|p| p.to_string()).collect(),
+            shared,
+            relationship,
+        }
+    }
+
+    /// The including and included role names, when one includes the other
+    pub fn inclusion(&self) -> Option<(&str, &str)> {
+        match self.relationship? {
+            RoleRelationship::FirstIncludesSecond => Some((&self.first, &self.second)),
+            RoleRelationship::SecondIncludesFirst => Some((&self.second, &self.first)),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! The `iam-data.json` file format written by the sync pipeline
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Top-level contents of iam-data.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IamDataFile {
+    pub roles: Vec<RoleData>,
+    pub permissions: Vec<PermissionData>,
+    pub metadata: MetadataData,
+    /// Recently deleted or vanished roles, kept out of `roles`
+    #[serde(default)]
+    pub deleted_roles: Vec<DeletedRoleData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleData {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub stage: String,
+    pub included_permissions: Vec<String>,
+    /// Operations an Azure role subtracts from its wildcard grants (notActions)
+    #[serde(default)]
+    pub excluded_permissions: Vec<String>,
+    /// Project- or organization-level role collected with --project/--organization
+    #[serde(default)]
+    pub custom: bool,
+    /// Cloud the role comes from ("gcp", "aws" for managed policies, "azure" for built-in roles)
+    #[serde(default = "default_provider")]
+    pub provider: String,
+}
+
+/// A role the IAM API marks deleted (`reason: "deleted"`) or no longer
+/// returns (`reason: "removed"`), as of `deleted_on`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedRoleData {
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub stage: String,
+    #[serde(default)]
+    pub included_permissions: Vec<String>,
+    #[serde(default)]
+    pub custom: bool,
+    #[serde(default)]
+    pub deleted_on: Option<String>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionData {
+    pub name: String,
+    pub service: String,
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    /// Resource and action as the scraper split the name, which knows AWS
+    /// and Azure name formats (empty in older files)
+    #[serde(default)]
+    pub resource: String,
+    #[serde(default)]
+    pub action: String,
+    /// Description from the public permissions reference, when documented
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Provider of datasets written before the provider field existed
+pub const DEFAULT_PROVIDER: &str = "gcp";
+
+pub(crate) fn default_provider() -> String {
+    DEFAULT_PROVIDER.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataData {
+    pub total_roles: usize,
+    pub total_permissions: usize,
+    #[serde(default)]
+    pub last_updated: Option<String>,
+}
+
+/// `services/index.json`, listing the per-service shards written by the
+/// scraper's --split-services
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardIndex {
+    #[serde(default)]
+    pub last_updated: Option<String>,
+    pub services: BTreeMap<String, ShardEntry>,
+}
+
+/// One shard: a complete iam-data.json narrowed to a service's permissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardEntry {
+    /// Relative to the index
+    pub path: String,
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    #[serde(default)]
+    pub roles: usize,
+    #[serde(default)]
+    pub permissions: usize,
+}
+
+impl IamDataFile {
+    /// Combine service shards into one dataset. A role granting permissions
+    /// of several services appears in each of their shards; its permissions
+    /// are joined back together.
+    pub fn merge(shards: impl IntoIterator<Item = IamDataFile>) -> IamDataFile {
+        let mut roles: Vec<RoleData> = Vec::new();
+        let mut role_positions: HashMap<String, usize> = HashMap::new();
+        let mut permissions: Vec<PermissionData> = Vec::new();
+        let mut seen_permissions: HashSet<String> = HashSet::new();
+        let mut last_updated = None;
+
+        for shard in shards {
+            last_updated = last_updated.or(shard.metadata.last_updated);
+            for role in shard.roles {
+                match role_positions.get(&role.name) {
+                    // Each permission lives in exactly one shard
+                    Some(&idx) => roles[idx].included_permissions.extend(role.included_permissions),
+                    None => {
+                        role_positions.insert(role.name.clone(), roles.len());
+                        roles.push(role);
+                    }
+                }
+            }
+            for permission in shard.permissions {
+                if seen_permissions.insert(permission.name.clone()) {
+                    permissions.push(permission);
+                }
+            }
+        }
+
+        IamDataFile {
+            metadata: MetadataData {
+                total_roles: roles.len(),
+                total_permissions: permissions.len(),
+                last_updated,
+            },
+            roles,
+            permissions,
+            deleted_roles: vec![],
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Roles that include other roles
+//!
+//! GCP's basic roles nest: `roles/owner` grants everything `roles/editor`
+//! does, which grants everything `roles/viewer` does, and each one gains new
+//! services' permissions as they launch rather than through a fixed list.
+//! Cloud Storage's legacy bucket and object roles nest the same way. The
+//! scraped permission lists are a point-in-time view, so comparisons and
+//! recommendations consult these chains instead of treating the roles as
+//! unrelated flat lists.
+
+/// Role chains, strongest first; each role includes every role after it
+pub const ROLE_HIERARCHIES: &[&[&str]] = &[
+    BASIC_ROLES,
+    &[
+        "roles/storage.legacyBucketOwner",
+        "roles/storage.legacyBucketWriter",
+        "roles/storage.legacyBucketReader",
+    ],
+    &["roles/storage.legacyObjectOwner", "roles/storage.legacyObjectReader"],
+];
+
+/// The basic (formerly "primitive") roles, strongest first
+pub const BASIC_ROLES: &[&str] = &["roles/owner", "roles/editor", "roles/viewer"];
+
+/// Whether `name` is one of the basic roles
+pub fn is_basic_role(name: &str) -> bool {
+    BASIC_ROLES.contains(&name)
+}
+
+/// Roles `name` includes by definition, strongest first (empty for roles
+/// outside every chain)
+pub fn included_roles(name: &str) -> &'static [&'static str] {
+    ROLE_HIERARCHIES
+        .iter()
+        .find_map(|chain| chain.iter().position(|r| *r == name).map(|i| &chain[i + 1..]))
+        .unwrap_or_default()
+}
+
+/// Whether role `outer` grants everything role `inner` does by definition
+pub fn includes(outer: &str, inner: &str) -> bool {
+    included_roles(outer).contains(&inner)
+}
@@ -0,0 +1,609 @@
+//! Compact prebuilt index embedded in the edge binary
+//!
+//! Built from iam-data.json at compile time and archived with rkyv, so the
+//! edge and the WASM module search the archived bytes in place
+//! (`ArchivedPrebuiltIndex`) rather than decoding them into owned
+//! collections first. Roles are referenced by position instead of by name to
+//! keep the artifact small. Searches apply the same query syntax, scores and
+//! ordering as `SearchEngine`.
+//!
+//! Nothing is deserialized, but the archive is still in memory: an
+//! embedded one is part of the binary's data (WASM linear memory), and an
+//! `IndexBuf` holds a heap copy. Readers that must stay within a memory
+//! limit as the catalog grows use per-service shards instead (`shards`).
+
+use rkyv::rancor::{self, Source};
+use rkyv::string::ArchivedString;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use crate::data::{IamDataFile, PermissionData, RoleData};
+use crate::search::engine::{
+    ArchivedRole, PermissionSearchResult, ResultWarning, Role, RoleSearchResult, RoleSummary,
+};
+use crate::search::options::stage_rank;
+use crate::search::query::ParsedQuery;
+use crate::search::ranking::MatchQuery;
+use crate::search::scoring::FieldMatch;
+use crate::search::{BuildError, SearchEngine, SearchOptions, SortField};
+
+/// Failure to encode, decode or validate index bytes
+pub type IndexError = rancor::Error;
+
+/// Layout version of the archived `PrebuiltIndex` (and of the shards and
+/// `ShardRoutes` built from it), bumped whenever its fields change so
+/// deployments can tell incompatible archives apart
+pub const INDEX_FORMAT_VERSION: u32 = 4;
+
+/// One page of search results and how many matched in all
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPage<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+}
+
+/// Permission with the indices (into `PrebuiltIndex::roles`) of roles granting it
+#[derive(Debug, Clone, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct IndexedPermission {
+    pub name: String,
+    pub service: String,
+    pub provider: String,
+    pub resource: String,
+    pub action: String,
+    pub granted_by_roles: Vec<u32>,
+    pub description: Option<String>,
+}
+
+/// Serializable search index
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct PrebuiltIndex {
+    // All permissions sorted for binary search
+    pub permissions: Vec<IndexedPermission>,
+    pub permission_names: Vec<String>,
+
+    // All roles
+    pub roles: Vec<Role>,
+    pub role_names: Vec<String>,
+    pub role_summaries: Vec<RoleSummary>,
+
+    // Service -> permission indices
+    pub service_to_permissions: HashMap<String, Vec<u32>>,
+
+    // Lowercase names for case-insensitive search
+    pub permission_names_lower: Vec<String>,
+    pub role_names_lower: Vec<String>,
+    pub role_titles_lower: Vec<String>,
+
+    /// Dataset timestamp (`metadata.last_updated`) the index was built from
+    pub last_updated: Option<String>,
+}
+
+impl PrebuiltIndex {
+    /// Build the index from a parsed iam-data.json
+    pub fn build(data: &IamDataFile) -> Self {
+        // Build role index and summaries
+        let mut roles: Vec<Role> = Vec::with_capacity(data.roles.len());
+        let mut role_names: Vec<String> = Vec::with_capacity(data.roles.len());
+        let mut role_summaries: Vec<RoleSummary> = Vec::with_capacity(data.roles.len());
+
+        for role_data in &data.roles {
+            roles.push(Role {
+                name: role_data.name.clone(),
+                title: role_data.title.clone(),
+                description: role_data.description.clone(),
+                stage: role_data.stage.clone(),
+                included_permissions: role_data.included_permissions.clone(),
+                custom: role_data.custom,
+                provider: role_data.provider.clone(),
+                excluded_permissions: role_data.excluded_permissions.clone(),
+            });
+            role_names.push(role_data.name.clone());
+            role_summaries.push(RoleSummary {
+                name: role_data.name.clone(),
+                title: role_data.title.clone(),
+                stage: role_data.stage.clone(),
+            });
+        }
+
+        // Build permission index with role mappings, taking providers,
+        // descriptions and name splits from the dataset's permission list
+        // like the engine does
+        let scraped: HashMap<&str, &PermissionData> = data.permissions.iter().map(|p| (p.name.as_str(), p)).collect();
+        let mut permission_map: HashMap<String, IndexedPermission> = HashMap::new();
+        for (role_idx, role_data) in data.roles.iter().enumerate() {
+            for perm_name in &role_data.included_permissions {
+                let entry = permission_map.entry(perm_name.clone()).or_insert_with(|| {
+                    let parts: Vec<&str> = perm_name.split('.').collect();
+                    let scraped = scraped.get(perm_name.as_str());
+                    let split = |part: Option<&String>, idx: usize| match part.filter(|p| !p.is_empty()) {
+                        Some(part) => part.clone(),
+                        None => parts.get(idx).unwrap_or(&"").to_string(),
+                    };
+                    IndexedPermission {
+                        name: perm_name.clone(),
+                        service: permission_service(perm_name, scraped.copied()).to_string(),
+                        provider: scraped.map_or(&role_data.provider, |p| &p.provider).clone(),
+                        resource: split(scraped.map(|p| &p.resource), 1),
+                        action: split(scraped.map(|p| &p.action), 2),
+                        granted_by_roles: vec![],
+                        description: scraped.and_then(|p| p.description.clone()).filter(|d| !d.is_empty()),
+                    }
+                });
+                entry.granted_by_roles.push(role_idx as u32);
+            }
+        }
+
+        // Sort permissions and build final structures
+        let mut permissions: Vec<IndexedPermission> = permission_map.into_values().collect();
+        permissions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut permission_names: Vec<String> = Vec::with_capacity(permissions.len());
+        let mut service_to_permissions: HashMap<String, Vec<u32>> = HashMap::new();
+        for (idx, perm) in permissions.iter().enumerate() {
+            permission_names.push(perm.name.clone());
+            service_to_permissions
+                .entry(perm.service.clone())
+                .or_default()
+                .push(idx as u32);
+        }
+
+        // Pre-compute lowercase versions for case-insensitive search
+        let permission_names_lower = permission_names.iter().map(|s| s.to_lowercase()).collect();
+        let role_names_lower = role_names.iter().map(|s| s.to_lowercase()).collect();
+        let role_titles_lower = roles.iter().map(|r| r.title.to_lowercase()).collect();
+
+        PrebuiltIndex {
+            permissions,
+            permission_names,
+            roles,
+            role_names,
+            role_summaries,
+            service_to_permissions,
+            permission_names_lower,
+            role_names_lower,
+            role_titles_lower,
+            last_updated: data.metadata.last_updated.clone(),
+        }
+    }
+
+    /// Encode the index as an rkyv archive
+    pub fn to_bytes(&self) -> Result<Vec<u8>, IndexError> {
+        rkyv::to_bytes::<IndexError>(self).map(AlignedVec::into_vec)
+    }
+
+    /// Validate an archive produced by `to_bytes` and view it in place. The
+    /// bytes must be 16-byte aligned; `IndexBuf` copies unaligned input.
+    pub fn access(bytes: &[u8]) -> Result<&ArchivedPrebuiltIndex, IndexError> {
+        rkyv::access::<ArchivedPrebuiltIndex, IndexError>(bytes)
+    }
+
+    /// Decode an archive produced by `to_bytes` into an owned index
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IndexError> {
+        rkyv::from_bytes::<Self, IndexError>(&aligned(bytes))
+    }
+
+    /// Full `SearchEngine` (fuzzy scoring, set comparisons) over the same
+    /// roles and permission details, validated like any other dataset
+    pub fn to_engine(&self) -> Result<SearchEngine, BuildError> {
+        let roles = self.roles.iter().map(|role| RoleData {
+            name: role.name.clone(),
+            title: role.title.clone(),
+            description: role.description.clone(),
+            stage: role.stage.clone(),
+            included_permissions: role.included_permissions.clone(),
+            excluded_permissions: role.excluded_permissions.clone(),
+            custom: role.custom,
+            provider: role.provider.clone(),
+        });
+        let permissions = self.permissions.iter().map(|perm| PermissionData {
+            name: perm.name.clone(),
+            service: perm.service.clone(),
+            provider: perm.provider.clone(),
+            resource: perm.resource.clone(),
+            action: perm.action.clone(),
+            description: perm.description.clone(),
+        });
+        SearchEngine::builder().with_roles(roles).with_permissions(permissions).build()
+    }
+}
+
+/// Service a permission is filed under: the scraped one, else the first
+/// segment of its name
+pub(crate) fn permission_service<'a>(name: &'a str, scraped: Option<&'a PermissionData>) -> &'a str {
+    match scraped.map(|p| p.service.as_str()).filter(|s| !s.is_empty()) {
+        Some(service) => service,
+        None => name.split('.').next().unwrap_or(""),
+    }
+}
+
+pub(crate) fn aligned(bytes: &[u8]) -> AlignedVec {
+    let mut buf = AlignedVec::with_capacity(bytes.len());
+    buf.extend_from_slice(bytes);
+    buf
+}
+
+/// Index bytes copied to an aligned buffer and validated once, then
+/// searched in place for as long as the buffer lives
+pub struct IndexBuf {
+    bytes: AlignedVec,
+}
+
+impl IndexBuf {
+    pub fn new(bytes: &[u8]) -> Result<Self, IndexError> {
+        let bytes = aligned(bytes);
+        PrebuiltIndex::access(&bytes)?;
+        Ok(IndexBuf { bytes })
+    }
+
+    /// Stream index bytes straight into the aligned buffer, so an archive
+    /// read from a KV Store isn't held twice while it's copied
+    pub fn read(mut reader: impl Read) -> Result<Self, IndexError> {
+        let mut bytes = AlignedVec::new();
+        io::copy(&mut reader, &mut bytes).map_err(IndexError::new)?;
+        PrebuiltIndex::access(&bytes)?;
+        Ok(IndexBuf { bytes })
+    }
+
+    /// Bytes the buffer holds
+    pub fn size(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn index(&self) -> &ArchivedPrebuiltIndex {
+        // SAFETY: `new` validated these bytes as an ArchivedPrebuiltIndex and
+        // they are never modified afterwards
+        unsafe { rkyv::access_unchecked::<ArchivedPrebuiltIndex>(&self.bytes) }
+    }
+}
+
+/// Compare two roles by a role-specific sort field, as `compare_roles` does
+fn compare_searchable_roles<R: SearchableRole>(a: &R, b: &R, field: SortField) -> Ordering {
+    match field {
+        SortField::Name => a.name().cmp(b.name()),
+        SortField::PermissionCount => a.permission_count().cmp(&b.permission_count()),
+        SortField::Stage => stage_rank(a.stage()).cmp(&stage_rank(b.stage())),
+        SortField::Score => Ordering::Equal,
+    }
+}
+
+impl ArchivedPrebuiltIndex {
+    /// Look up a permission by exact name
+    pub fn permission(&self, name: &str) -> Option<&ArchivedIndexedPermission> {
+        self.permission_names
+            .binary_search_by(|n| n.as_str().cmp(name))
+            .ok()
+            .map(|idx| &self.permissions[idx])
+    }
+
+    /// Look up a role by exact name
+    pub fn role(&self, name: &str) -> Option<&ArchivedRole> {
+        self.role_names
+            .iter()
+            .position(|n| n == name)
+            .map(|idx| &self.roles[idx])
+    }
+
+    /// Look up a permission by name, ignoring case
+    pub fn permission_ignore_case(&self, name: &str) -> Option<&ArchivedIndexedPermission> {
+        self.permission(name).or_else(|| {
+            let lower = name.to_lowercase();
+            self.permission_names_lower
+                .iter()
+                .position(|n| *n == lower)
+                .map(|idx| &self.permissions[idx])
+        })
+    }
+
+    /// Look up a role by name, ignoring case
+    pub fn role_ignore_case(&self, name: &str) -> Option<&ArchivedRole> {
+        self.role(name).or_else(|| {
+            let lower = name.to_lowercase();
+            self.role_names_lower
+                .iter()
+                .position(|n| *n == lower)
+                .map(|idx| &self.roles[idx])
+        })
+    }
+
+    /// Permissions of a service ("storage"), in name order
+    pub fn service_permissions(&self, service: &str) -> Vec<&ArchivedIndexedPermission> {
+        self.service_to_permissions
+            .get(service)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .filter_map(|idx| self.permissions.get(idx.to_native() as usize))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Predefined roles scoped to a service (`roles/{service}.*`), in name order
+    pub fn service_roles(&self, service: &str) -> Vec<&ArchivedRole> {
+        let prefix = format!("roles/{}.", service);
+        let mut roles: Vec<&ArchivedRole> = self
+            .roles
+            .iter()
+            .filter(|r| !r.custom && r.name.starts_with(&prefix))
+            .collect();
+        roles.sort_by(|a, b| a.name.cmp(&b.name));
+        roles
+    }
+
+    /// Search permissions with associated roles
+    pub fn search_permissions(&self, query: &str, mode: &str, options: &SearchOptions) -> Vec<PermissionSearchResult> {
+        self.search_permissions_page(query, mode, options).items
+    }
+
+    /// `search_permissions`, with the number of matches before pagination
+    pub fn search_permissions_page(
+        &self,
+        query: &str,
+        mode: &str,
+        options: &SearchOptions,
+    ) -> SearchPage<PermissionSearchResult> {
+        let parsed = ParsedQuery::parse(query);
+        if parsed.is_empty() {
+            return SearchPage { items: vec![], total: 0 };
+        }
+
+        let match_query = MatchQuery::new(&parsed.text, mode, options.min_score);
+        let mut matches: Vec<(usize, FieldMatch)> = if mode == "exact" && !parsed.text.is_empty() {
+            self.permission_names
+                .binary_search_by(|n| n.as_str().cmp(&parsed.text))
+                .map(|idx| vec![(idx, FieldMatch::exact())])
+                .unwrap_or_default()
+        } else {
+            (0..self.permissions.len())
+                .filter_map(|idx| {
+                    options.ranking
+                        .permission_match(&match_query, &self.permission_names[idx], &self.permission_names_lower[idx])
+                        .map(|m| (idx, m))
+                })
+                .collect()
+        };
+
+        matches.retain(|&(idx, _)| {
+            let fields = [self.permission_names[idx].as_str()];
+            parsed.matches_phrases(&fields) && !parsed.is_excluded(&fields)
+        });
+        if let Some(provider) = &options.provider {
+            matches.retain(|&(idx, _)| {
+                self.permissions[idx]
+                    .granted_by_roles
+                    .iter()
+                    .any(|role_idx| self.roles[role_idx.to_native() as usize].provider == *provider)
+            });
+        }
+
+        let mut matches: Vec<(usize, f64, FieldMatch)> = matches
+            .into_iter()
+            .map(|(idx, m)| (idx, options.ranking.score(&m, &self.permission_names[idx], &match_query.lower, None, None, options.popularity.as_deref()), m))
+            .collect();
+
+        // Permissions have no permission count or stage, so those keys fall back to score
+        let order = options.order();
+        matches.sort_by(|a, b| {
+            let ordering = match options.sort {
+                SortField::Name => self.permission_names[a.0].cmp(&self.permission_names[b.0]),
+                _ => a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal),
+            };
+            order
+                .apply(ordering)
+                .then_with(|| self.permission_names[a.0].cmp(&self.permission_names[b.0]))
+        });
+
+        let total = matches.len();
+        let items = matches
+            .into_iter()
+            .skip(options.offset)
+            .take(options.limit)
+            .map(|(idx, score, m)| {
+                let perm = &self.permissions[idx];
+                let granted_by_roles: Vec<RoleSummary> = perm
+                    .granted_by_roles
+                    .iter()
+                    .filter_map(|role_idx| self.role_summaries.get(role_idx.to_native() as usize))
+                    .map(|summary| RoleSummary {
+                        name: summary.name.to_string(),
+                        title: summary.title.to_string(),
+                        stage: summary.stage.to_string(),
+                    })
+                    .take(5) // Limit to 5 roles per permission
+                    .collect();
+
+                PermissionSearchResult {
+                    name: perm.name.to_string(),
+                    service: perm.service.to_string(),
+                    resource: perm.resource.to_string(),
+                    action: perm.action.to_string(),
+                    description: perm.description.as_ref().map(|d| d.to_string()),
+                    score,
+                    granted_by_roles,
+                    warnings: ResultWarning::deprecated_permission(perm.granted_by_roles.iter().map(|role_idx| {
+                        let role = &self.roles[role_idx.to_native() as usize];
+                        (role.name.as_str(), role.description.as_str(), role.is_deprecated())
+                    }))
+                    .into_iter()
+                    .collect(),
+                    explanation: options.explain.then(|| {
+                        options.ranking.explain(&m, &perm.name, &match_query.lower, None, None, options.popularity.as_deref())
+                    }),
+                }
+            })
+            .collect();
+        SearchPage { items, total }
+    }
+
+    /// Search roles with their permissions
+    pub fn search_roles(&self, query: &str, mode: &str, options: &SearchOptions) -> Vec<RoleSearchResult> {
+        self.search_roles_page(query, mode, options).items
+    }
+
+    /// `search_roles`, with the number of matches before pagination
+    pub fn search_roles_page(&self, query: &str, mode: &str, options: &SearchOptions) -> SearchPage<RoleSearchResult> {
+        search_role_page(&self.roles, &self.role_names_lower, &self.role_titles_lower, query, mode, options)
+    }
+}
+
+/// What role search reads of a role, so a full index and a shard routing
+/// table (`ShardRoutes`) rank roles alike
+pub(crate) trait SearchableRole {
+    fn name(&self) -> &str;
+    fn title(&self) -> &str;
+    fn description(&self) -> &str;
+    fn stage(&self) -> &str;
+    fn provider(&self) -> &str;
+    fn is_custom(&self) -> bool;
+    fn permission_count(&self) -> usize;
+    /// The first few permissions, shown with a search result
+    fn sample_permissions(&self) -> Vec<String>;
+
+    fn is_deprecated(&self) -> bool {
+        self.stage() == "DEPRECATED"
+    }
+}
+
+impl SearchableRole for ArchivedRole {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn stage(&self) -> &str {
+        &self.stage
+    }
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+    fn is_custom(&self) -> bool {
+        self.custom
+    }
+    fn permission_count(&self) -> usize {
+        self.included_permissions.len()
+    }
+    fn sample_permissions(&self) -> Vec<String> {
+        self.included_permissions.iter().take(SAMPLE_PERMISSIONS).map(|p| p.to_string()).collect()
+    }
+}
+
+/// Permissions listed with each role search result
+pub(crate) const SAMPLE_PERMISSIONS: usize = 5;
+
+/// Role search over `roles` and their lowercase names and titles
+pub(crate) fn search_role_page<R: SearchableRole>(
+    roles: &[R],
+    names_lower: &[ArchivedString],
+    titles_lower: &[ArchivedString],
+    query: &str,
+    mode: &str,
+    options: &SearchOptions,
+) -> SearchPage<RoleSearchResult> {
+    let parsed = ParsedQuery::parse(query);
+    if parsed.is_empty() {
+        return SearchPage { items: vec![], total: 0 };
+    }
+
+    let match_query = MatchQuery::new(&parsed.text, mode, options.min_score);
+    let mut matches: Vec<(usize, FieldMatch)> = if mode == "exact" && !parsed.text.is_empty() {
+        roles
+            .iter()
+            .position(|r| r.name() == parsed.text)
+            .map(|idx| vec![(idx, FieldMatch::exact())])
+            .unwrap_or_default()
+    } else {
+        (0..roles.len())
+            .filter_map(|idx| {
+                options.ranking
+                    .role_match(
+                        &match_query,
+                        roles[idx].name(),
+                        &names_lower[idx],
+                        &titles_lower[idx],
+                        || roles[idx].description().to_lowercase(),
+                    )
+                    .map(|m| (idx, m))
+            })
+            .collect()
+    };
+
+    matches.retain(|&(idx, _)| {
+        let role = &roles[idx];
+        let fields = [role.name(), role.title()];
+        (options.include_deprecated || !role.is_deprecated())
+            && options.provider.as_ref().is_none_or(|p| role.provider() == p)
+            && parsed.matches_phrases(&fields)
+            && !parsed.is_excluded(&fields)
+    });
+
+    let mut matches: Vec<(usize, f64, FieldMatch)> = matches
+        .into_iter()
+        .map(|(idx, m)| {
+            let role = &roles[idx];
+            let score = options.ranking.score(
+                &m,
+                role.name(),
+                &match_query.lower,
+                Some(role.stage()),
+                Some(role.permission_count()),
+                options.popularity.as_deref(),
+            );
+            (idx, score, m)
+        })
+        .collect();
+
+    let order = options.order();
+    matches.sort_by(|a, b| {
+        let (ra, rb) = (&roles[a.0], &roles[b.0]);
+        let ordering = match options.sort {
+            SortField::Score => a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal),
+            field => compare_searchable_roles(ra, rb, field),
+        };
+        order.apply(ordering).then_with(|| ra.name().cmp(rb.name()))
+    });
+
+    let total = matches.len();
+    let items = matches
+        .into_iter()
+        .skip(options.offset)
+        .take(options.limit)
+        .map(|(idx, score, m)| {
+            let role = &roles[idx];
+            RoleSearchResult {
+                name: role.name().to_string(),
+                title: role.title().to_string(),
+                description: role.description().to_string(),
+                stage: role.stage().to_string(),
+                score,
+                permission_count: role.permission_count(),
+                sample_permissions: role.sample_permissions(),
+                deprecated: role.is_deprecated(),
+                custom: role.is_custom(),
+                warnings: role
+                    .is_deprecated()
+                    .then(|| ResultWarning::deprecated_role(role.name(), role.description()))
+                    .into_iter()
+                    .collect(),
+                explanation: options.explain.then(|| {
+                    options.ranking.explain(
+                        &m,
+                        role.name(),
+                        &match_query.lower,
+                        Some(role.stage()),
+                        Some(role.permission_count()),
+                        options.popularity.as_deref(),
+                    )
+                }),
+            }
+        })
+        .collect();
+    SearchPage { items, total }
+}
@@ -0,0 +1,25 @@
+//! Shared GCP IAM search core
+//!
+//! Used by both the backend API and the Fastly edge service so they parse
+//! the same data, share one index format and rank results identically.
+//!
+//! # Modules
+//! - `compare` - Permission differences between two roles
+//! - `data` - The `iam-data.json` file format
+//! - `hierarchy` - Basic and legacy roles that include other roles
+//! - `index` - Compact prebuilt index searched in place
+//! - `search` - Search engine, query parsing and scoring
+//! - `shards` - The prebuilt index split by service behind a routing table
+
+pub mod compare;
+pub mod data;
+pub mod hierarchy;
+pub mod index;
+pub mod search;
+pub mod shards;
+
+pub use compare::{RoleComparison, RoleRelationship};
+pub use data::{DeletedRoleData, IamDataFile, ShardIndex};
+pub use index::{PrebuiltIndex, INDEX_FORMAT_VERSION};
+pub use search::SearchEngine;
+pub use shards::{ShardRoutes, ShardedIndex};
@@ -0,0 +1,83 @@
+//! Fixed-size bitsets over the permission index for fast set algebra
+
+/// Set of permission indices packed into 64-bit words
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionSet {
+    words: Vec<u64>,
+}
+
+impl PermissionSet {
+    /// Empty set able to hold indices below `capacity`
+    pub fn with_capacity(capacity: usize) -> Self {
+        PermissionSet {
+            words: vec![0; capacity.div_ceil(64)],
+        }
+    }
+
+    /// Approximate heap bytes held by the set
+    pub fn heap_bytes(&self) -> usize {
+        super::memory::vec(&self.words)
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        let word = index / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (index % 64);
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.words
+            .get(index / 64)
+            .is_some_and(|w| w & (1 << (index % 64)) != 0)
+    }
+
+    /// Number of set bits
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Whether every member of `other` is also in this set
+    pub fn is_superset(&self, other: &PermissionSet) -> bool {
+        other
+            .words
+            .iter()
+            .enumerate()
+            .all(|(i, b)| b & !self.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    /// Add every member of `other` to this set
+    pub fn union_with(&mut self, other: &PermissionSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    /// Size of the intersection with another set
+    pub fn intersection_len(&self, other: &PermissionSet) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// Jaccard similarity (|A ∩ B| / |A ∪ B|), 0.0 when both are empty
+    pub fn jaccard(&self, other: &PermissionSet) -> f64 {
+        let intersection = self.intersection_len(other);
+        let union = self.len() + other.len() - intersection;
+        if union == 0 {
+            0.0
+        } else {
+            intersection as f64 / union as f64
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Validated, one-step construction of a `SearchEngine`
+//!
+//! Indexing roles one at a time leaves the engine unusable until
+//! `finalize` runs, and accepts whatever names it is given. The builder
+//! checks every role up front and finalizes before handing the engine
+//! over, so a half-built or malformed index never escapes.
+
+use std::fmt;
+
+use super::engine::SearchEngine;
+use super::ranking::RankingConfig;
+use crate::data::{IamDataFile, PermissionData, RoleData};
+
+/// Why `SearchEngineBuilder::build` rejected its input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A role without a name, by its position in the input
+    EmptyRoleName { position: usize },
+    /// A permission that is empty, contains whitespace or has an empty
+    /// `.` separated segment, with the role listing it (`None` for the
+    /// dataset's permission list)
+    MalformedPermission { role: Option<String>, permission: String },
+    /// A ranking config failing `RankingConfig::validate`
+    InvalidRanking(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::EmptyRoleName { position } => write!(f, "Role #{} has an empty name", position + 1),
+            BuildError::MalformedPermission { role: Some(role), permission } => {
+                write!(f, "Role '{}' lists malformed permission '{}'", role, permission)
+            }
+            BuildError::MalformedPermission { role: None, permission } => write!(f, "Malformed permission '{}'", permission),
+            BuildError::InvalidRanking(msg) => write!(f, "Invalid ranking config: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Whether `name` looks like a permission of any supported provider
+///
+/// Deliberately loose, since GCP (`storage.objects.get`), AWS
+/// (`s3:GetObject`) and Azure (`Microsoft.Storage/storageAccounts/read`)
+/// names differ: non-empty, no whitespace, and no empty `.` segments.
+pub fn is_well_formed_permission(name: &str) -> bool {
+    !name.is_empty() && !name.contains(char::is_whitespace) && name.split('.').all(|segment| !segment.is_empty())
+}
+
+/// Check a role's name and permissions; `position` is only used to report
+/// an empty name
+pub fn validate_role(position: usize, name: &str, permissions: &[String]) -> Result<(), BuildError> {
+    if name.trim().is_empty() {
+        return Err(BuildError::EmptyRoleName { position });
+    }
+    match permissions.iter().find(|p| !is_well_formed_permission(p)) {
+        Some(permission) => Err(BuildError::MalformedPermission { role: Some(name.to_string()), permission: permission.clone() }),
+        None => Ok(()),
+    }
+}
+
+/// Collects roles and settings for a `SearchEngine` (see `SearchEngine::builder`)
+#[derive(Debug, Clone, Default)]
+pub struct SearchEngineBuilder {
+    roles: Vec<RoleData>,
+    permissions: Vec<PermissionData>,
+    ranking: RankingConfig,
+}
+
+impl SearchEngineBuilder {
+    /// Index the roles of an iam-data.json file, with the descriptions and
+    /// name splits of its permissions
+    pub fn with_dataset(self, data: IamDataFile) -> Self {
+        self.with_roles(data.roles).with_permissions(data.permissions)
+    }
+
+    /// Index these roles, after any already added; custom roles keep their
+    /// flag, and every role its provider and excluded operations
+    pub fn with_roles(mut self, roles: impl IntoIterator<Item = RoleData>) -> Self {
+        self.roles.extend(roles);
+        self
+    }
+
+    /// Apply these permissions' scraped details (see
+    /// `SearchEngine::apply_permission_data`) once the roles are indexed
+    pub fn with_permissions(mut self, permissions: impl IntoIterator<Item = PermissionData>) -> Self {
+        self.permissions.extend(permissions);
+        self
+    }
+
+    /// Default ranking for searches against the engine (see `SearchEngine::ranking`)
+    pub fn with_ranking(mut self, ranking: RankingConfig) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Validate everything, then index and finalize
+    ///
+    /// Nothing is indexed unless every role passes, so the first problem
+    /// found is the only one reported.
+    pub fn build(self) -> Result<SearchEngine, BuildError> {
+        self.ranking.validate().map_err(BuildError::InvalidRanking)?;
+        for (position, role) in self.roles.iter().enumerate() {
+            validate_role(position, &role.name, &role.included_permissions)?;
+        }
+        if let Some(permission) = self.permissions.iter().find(|p| !is_well_formed_permission(&p.name)) {
+            return Err(BuildError::MalformedPermission { role: None, permission: permission.name.clone() });
+        }
+
+        let mut engine = SearchEngine::new();
+        engine.set_ranking(self.ranking);
+        for role in self.roles {
+            engine.index_role_data(role);
+        }
+        for permission in self.permissions {
+            engine.apply_permission_data(permission);
+        }
+        engine.finalize();
+        Ok(engine)
+    }
+}
+
+impl SearchEngine {
+    /// Start building an engine from validated roles
+    pub fn builder() -> SearchEngineBuilder {
+        SearchEngineBuilder::default()
+    }
+
+    /// Engine over a parsed iam-data.json, with default ranking
+    pub fn from_dataset(data: IamDataFile) -> Result<SearchEngine, BuildError> {
+        SearchEngine::builder().with_dataset(data).build()
+    }
+}
@@ -0,0 +1,2053 @@
+//! High-performance hybrid search engine with role-permission associations
+//!
+//! Implements multiple search strategies:
+//! - Exact: O(1) hash map lookups
+//! - Prefix: Trie-based autocomplete
+//! - Fuzzy: N-gram based similarity matching
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use serde::{Serialize, Deserialize};
+
+use crate::data::{PermissionData, RoleData, DEFAULT_PROVIDER};
+use crate::hierarchy;
+use super::bitset::PermissionSet;
+use super::memory;
+use super::options::{stage_rank, SearchOptions, SortField, SortOrder};
+use super::query::ParsedQuery;
+use super::ranking::{MatchQuery, Popularity, RankingConfig, ScoreExplanation};
+use super::scoring::{self, FieldMatch};
+use super::spelling::SpellingIndex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult<T> {
+    pub item: T,
+    pub score: f64,
+}
+
+/// Role with its permissions
+#[derive(Debug, Clone, Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct Role {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub stage: String,
+    pub included_permissions: Vec<String>,
+    /// Organization-defined role uploaded through the custom-roles API
+    #[serde(default)]
+    pub custom: bool,
+    /// Cloud the role comes from ("gcp", "aws" or "azure")
+    #[serde(default = "crate::data::default_provider")]
+    pub provider: String,
+    /// Operations subtracted from the role's wildcard grants (Azure notActions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_permissions: Vec<String>,
+}
+
+impl Role {
+    /// Whether the role grants `permission`: listed outright or matched by
+    /// one of its wildcard grants (`*`, `s3:Get*`, `Microsoft.Storage/*`),
+    /// and not matched by one of its excluded operations
+    ///
+    /// `provider` is the permission's cloud (`None` when it isn't indexed);
+    /// wildcards only cover permissions of the role's own cloud, so Azure's
+    /// `*` doesn't grant GCP or AWS permissions.
+    pub fn grants(&self, permission: &str, provider: Option<&str>) -> bool {
+        let covers = |pattern: &String| {
+            (!pattern.contains('*') || provider == Some(self.provider.as_str())) && self.pattern_covers(pattern, permission)
+        };
+        self.included_permissions.iter().any(covers) && !self.excluded_permissions.iter().any(covers)
+    }
+
+    /// Whether the role's grants need matching rather than a name lookup
+    pub fn has_wildcard_grants(&self) -> bool {
+        !self.excluded_permissions.is_empty() || self.included_permissions.iter().any(|p| p.contains('*'))
+    }
+
+    /// AWS actions and Azure operations are case-insensitive; GCP
+    /// permissions aren't
+    fn pattern_covers(&self, pattern: &str, permission: &str) -> bool {
+        if pattern == permission {
+            return true;
+        }
+        if !pattern.contains('*') {
+            return self.provider != DEFAULT_PROVIDER && pattern.eq_ignore_ascii_case(permission);
+        }
+        if self.provider == DEFAULT_PROVIDER {
+            wildcard_match(pattern, permission)
+        } else {
+            wildcard_match(&pattern.to_ascii_lowercase(), &permission.to_ascii_lowercase())
+        }
+    }
+
+    /// Whether GCP has marked this role as deprecated
+    pub fn is_deprecated(&self) -> bool {
+        self.stage == "DEPRECATED"
+    }
+
+    /// Whether this is roles/owner, roles/editor or roles/viewer
+    pub fn is_basic(&self) -> bool {
+        hierarchy::is_basic_role(&self.name)
+    }
+
+    /// Whether this is a Google-managed service agent role
+    /// (`roles/run.serviceAgent`, `roles/dataplex.discoveryServiceAgent`)
+    pub fn is_service_agent(&self) -> bool {
+        self.name.to_ascii_lowercase().ends_with("serviceagent")
+    }
+
+    /// Roles this one includes by definition, strongest first
+    pub fn included_roles(&self) -> &'static [&'static str] {
+        hierarchy::included_roles(&self.name)
+    }
+}
+
+impl ArchivedRole {
+    /// Whether GCP has marked this role as deprecated
+    pub fn is_deprecated(&self) -> bool {
+        self.stage == "DEPRECATED"
+    }
+
+    /// Whether this is roles/owner, roles/editor or roles/viewer
+    pub fn is_basic(&self) -> bool {
+        hierarchy::is_basic_role(&self.name)
+    }
+
+    /// Roles this one includes by definition, strongest first
+    pub fn included_roles(&self) -> &'static [&'static str] {
+        hierarchy::included_roles(&self.name)
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` stands for any run of
+/// characters (case-sensitive, like permission names)
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: the whole pattern must match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Whether a role name lives in the custom namespace
+/// (`projects/{project}/roles/{role}` or `organizations/{org}/roles/{role}`)
+pub fn is_custom_role_name(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('/').collect();
+    matches!(parts.as_slice(), ["projects" | "organizations", parent, "roles", role]
+        if !parent.is_empty() && !role.is_empty())
+}
+
+/// Most distinct permissions `minimal_role_cover` will solve for
+pub const MAX_EXACT_COVER_PERMISSIONS: usize = 24;
+
+/// Most equally small combinations `minimal_role_cover` returns
+const MAX_COVER_SOLUTIONS: usize = 10;
+
+/// Search steps `minimal_role_cover` takes before settling for greedy
+const MAX_COVER_STEPS: usize = 1_000_000;
+
+/// Clouds a role's `provider` can name
+pub const PROVIDERS: &[&str] = &["gcp", "aws", "azure"];
+
+/// Compare two roles by a role-specific sort field
+pub(crate) fn compare_roles(a: &Role, b: &Role, field: SortField) -> Ordering {
+    match field {
+        SortField::Name => a.name.cmp(&b.name),
+        SortField::PermissionCount => a.included_permissions.len().cmp(&b.included_permissions.len()),
+        SortField::Stage => stage_rank(&a.stage).cmp(&stage_rank(&b.stage)),
+        SortField::Score => Ordering::Equal,
+    }
+}
+
+/// Permission with roles that grant it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Permission {
+    pub name: String,
+    pub service: String,
+    /// Cloud the permission comes from, as the scraper recorded it or else
+    /// from the first role granting it
+    #[serde(default = "crate::data::default_provider")]
+    pub provider: String,
+    pub resource: String,
+    pub action: String,
+    pub granted_by_roles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Search result for permissions including associated roles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionSearchResult {
+    pub name: String,
+    pub service: String,
+    pub resource: String,
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub score: f64,
+    pub granted_by_roles: Vec<RoleSummary>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ResultWarning>,
+    /// Score breakdown, when `SearchOptions::explain` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// Search result for roles including their permissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleSearchResult {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub stage: String,
+    pub score: f64,
+    pub permission_count: usize,
+    pub sample_permissions: Vec<String>,
+    pub deprecated: bool,
+    pub custom: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ResultWarning>,
+    /// Score breakdown, when `SearchOptions::explain` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// A caveat attached to a search result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultWarning {
+    /// "deprecated_role" or "deprecated_permission"
+    pub code: String,
+    pub message: String,
+    /// Role to use instead, when a deprecation notice names one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+impl ResultWarning {
+    /// Warning for a role in the DEPRECATED stage
+    pub fn deprecated_role(name: &str, description: &str) -> Self {
+        let replacement = deprecation_replacement(name, description).map(String::from);
+        let message = match &replacement {
+            Some(replacement) => format!("{} is deprecated; use {} instead", name, replacement),
+            None => format!("{} is deprecated", name),
+        };
+        ResultWarning { code: "deprecated_role".to_string(), message, replacement }
+    }
+
+    /// Warning for a permission granted only by deprecated roles, from its
+    /// granting roles' (name, description, deprecated); `None` when any of
+    /// them isn't deprecated, or none grant it
+    pub fn deprecated_permission<'a>(
+        granting_roles: impl IntoIterator<Item = (&'a str, &'a str, bool)>,
+    ) -> Option<Self> {
+        let mut replacement = None;
+        let mut any = false;
+        for (name, description, deprecated) in granting_roles {
+            if !deprecated {
+                return None;
+            }
+            any = true;
+            replacement = replacement.or_else(|| deprecation_replacement(name, description));
+        }
+        any.then(|| ResultWarning {
+            code: "deprecated_permission".to_string(),
+            message: match replacement {
+                Some(replacement) => format!("Only deprecated roles grant this permission; consider {}", replacement),
+                None => "Only deprecated roles grant this permission".to_string(),
+            },
+            replacement: replacement.map(String::from),
+        })
+    }
+}
+
+/// The role a deprecation notice in `description` points to
+/// ("Deprecated: use roles/storage.insightsCollectorService instead"), if
+/// it is one and names a role other than `name`
+pub fn deprecation_replacement<'a>(name: &str, description: &'a str) -> Option<&'a str> {
+    if !description.to_lowercase().contains("deprecat") {
+        return None;
+    }
+    description
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '(' | ')' | '"' | '\'' | '`'))
+        .map(|word| word.trim_end_matches(['.', ':']))
+        .find(|word| word.len() > "roles/".len() && word.starts_with("roles/") && *word != name)
+}
+
+/// Brief role info for permission results
+#[derive(Debug, Clone, Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct RoleSummary {
+    pub name: String,
+    pub title: String,
+    pub stage: String,
+}
+
+/// A role ranked by permission-set similarity to another role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarRole {
+    pub name: String,
+    pub title: String,
+    pub stage: String,
+    pub similarity: f64,
+    pub shared_permissions: usize,
+    pub permission_count: usize,
+}
+
+/// A role picked to cover part of a requested permission set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendedRole {
+    pub name: String,
+    pub title: String,
+    pub stage: String,
+    /// Requested permissions first covered by this role
+    pub covers: Vec<String>,
+    pub permission_count: usize,
+    /// Permissions the role grants beyond the requested set
+    pub extra_permissions: usize,
+}
+
+/// Roles that together grant a requested permission set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoleRecommendation {
+    pub roles: Vec<RecommendedRole>,
+    /// Requested permissions no predefined role grants
+    pub uncovered: Vec<String>,
+}
+
+/// One combination of roles granting a requested permission set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleCover {
+    pub roles: Vec<RoleSummary>,
+    /// Distinct permissions the roles grant together
+    pub permission_count: usize,
+    /// Permissions granted beyond the requested set (the over-grant)
+    pub extra_permissions: usize,
+}
+
+/// Fewest-role combinations granting a requested permission set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MinimalCover {
+    /// Combinations of the minimal size, least over-granting first
+    pub solutions: Vec<RoleCover>,
+    /// Whether the search finished, proving no smaller combination exists;
+    /// when false the only solution is the greedy recommendation
+    pub optimal: bool,
+    /// Requested permissions no predefined role grants
+    pub uncovered: Vec<String>,
+}
+
+/// Whether a member holding some roles has a permission
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessCheck {
+    pub permission: String,
+    pub granted: bool,
+    /// Held roles granting the permission, directly or through a role they
+    /// include (see `hierarchy`)
+    pub granted_by: Vec<String>,
+    /// Held roles not in the dataset
+    pub unknown_roles: Vec<String>,
+}
+
+/// A role granting every permission of a requested set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupersetRole {
+    pub name: String,
+    pub title: String,
+    pub stage: String,
+    pub permission_count: usize,
+    /// Permissions granted beyond the requested set
+    pub extra_permissions: usize,
+}
+
+/// Roles granting all of a permission set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SupersetRoles {
+    pub roles: Vec<SupersetRole>,
+    /// Requested permissions not in the dataset
+    pub unknown_permissions: Vec<String>,
+}
+
+/// Catalog breakdowns computed once at finalize()
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogStats {
+    pub permissions_by_service: BTreeMap<String, usize>,
+    pub roles_by_stage: BTreeMap<String, usize>,
+    pub average_permissions_per_role: f64,
+}
+
+/// Approximate heap bytes held by each engine structure, for sizing
+/// instances (see `SearchEngine::memory_usage`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexMemory {
+    /// Sorted permission and role name lists
+    pub names: usize,
+    /// Permission records, keyed by name
+    pub permissions: usize,
+    /// Role records with their permission lists, keyed by name
+    pub roles: usize,
+    /// Permission-to-role, service-to-permission and permission bit maps
+    pub maps: usize,
+    /// Per-role permission bitsets used for comparisons
+    pub role_sets: usize,
+    /// Spelling-correction deletion dictionary
+    pub spelling: usize,
+    pub total: usize,
+}
+
+/// High-performance hybrid search engine
+#[derive(Clone)]
+pub struct SearchEngine {
+    // Permission data
+    permissions: HashMap<String, Permission>,
+    all_permission_names: Vec<String>,
+
+    // Role data
+    roles: HashMap<String, Role>,
+    all_role_names: Vec<String>,
+
+    // Indexes
+    permission_to_roles: HashMap<String, Vec<String>>,
+    service_to_permissions: HashMap<String, Vec<String>>,
+
+    // Role permission sets as bitsets over all_permission_names, built at finalize()
+    role_permission_sets: HashMap<String, PermissionSet>,
+    // Bit position of each permission in those sets, built at finalize()
+    permission_bits: HashMap<String, usize>,
+
+    // Cached breakdowns for /stats, built at finalize()
+    catalog_stats: CatalogStats,
+
+    // Deletion dictionary over permission/role tokens, built at finalize()
+    spelling: SpellingIndex,
+
+    // Default ranking, set by SearchEngineBuilder
+    ranking: RankingConfig,
+}
+
+/// Depth-first state for `SearchEngine::minimal_role_cover`
+struct CoverSearch<'a> {
+    /// Mask with a bit per requested permission
+    full: u32,
+    /// Most requested permissions any one candidate grants
+    widest: u32,
+    candidates: &'a [(u32, &'a Role)],
+    chosen: Vec<usize>,
+    /// Candidate indices of each complete combination, sorted
+    found: HashSet<Vec<usize>>,
+    steps: usize,
+}
+
+impl CoverSearch<'_> {
+    /// Record every combination of `size` candidates completing `covered`;
+    /// false once the step budget runs out
+    fn run(&mut self, covered: u32, size: usize) -> bool {
+        self.steps += 1;
+        if self.steps > MAX_COVER_STEPS {
+            return false;
+        }
+        if covered == self.full {
+            let mut indices = self.chosen.clone();
+            indices.sort_unstable();
+            self.found.insert(indices);
+            return true;
+        }
+        let remaining = size - self.chosen.len();
+        let missing = (self.full & !covered).count_ones();
+        if missing > remaining as u32 * self.widest {
+            return true;
+        }
+
+        let bit = 1 << (self.full & !covered).trailing_zeros();
+        for (index, (mask, _)) in self.candidates.iter().enumerate() {
+            if mask & bit == 0 {
+                continue;
+            }
+            self.chosen.push(index);
+            let finished = self.run(covered | mask, size);
+            self.chosen.pop();
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl SearchEngine {
+    pub fn new() -> Self {
+        SearchEngine {
+            permissions: HashMap::new(),
+            all_permission_names: Vec::new(),
+            roles: HashMap::new(),
+            all_role_names: Vec::new(),
+            permission_to_roles: HashMap::new(),
+            service_to_permissions: HashMap::new(),
+            role_permission_sets: HashMap::new(),
+            permission_bits: HashMap::new(),
+            catalog_stats: CatalogStats::default(),
+            spelling: SpellingIndex::default(),
+            ranking: RankingConfig::default(),
+        }
+    }
+
+    /// Add a role with its permissions
+    ///
+    /// Indexing is an upsert: a role already indexed under `name` is
+    /// replaced, not duplicated, and returned, so reloading a dataset into
+    /// the same engine leaves the counts unchanged. Call `finalize` once
+    /// done; `SearchEngine::builder` does both for a fresh engine.
+    pub fn index_role(&mut self, name: String, title: String, description: String, stage: String, permissions: Vec<String>) -> Option<Role> {
+        self.insert_role(Role {
+            name,
+            title,
+            description,
+            stage,
+            included_permissions: permissions,
+            custom: false,
+            provider: DEFAULT_PROVIDER.to_string(),
+            excluded_permissions: Vec::new(),
+        })
+    }
+
+    /// Add a role as read from iam-data.json, keeping its provider,
+    /// exclusions and custom flag; otherwise like `index_role`
+    pub fn index_role_data(&mut self, data: RoleData) -> Option<Role> {
+        self.insert_role(Role {
+            name: data.name,
+            title: data.title,
+            description: data.description,
+            stage: data.stage,
+            included_permissions: data.included_permissions,
+            custom: data.custom,
+            provider: data.provider,
+            excluded_permissions: data.excluded_permissions,
+        })
+    }
+
+    fn insert_role(&mut self, role: Role) -> Option<Role> {
+        let name = role.name.clone();
+        let replaced = self.remove_role(&name);
+
+        // Index each permission and create reverse mapping
+        for perm_name in &role.included_permissions {
+            let granted_by = self.permission_to_roles.entry(perm_name.clone()).or_default();
+            // A role listing a permission twice still grants it once
+            if granted_by.last() != Some(&name) {
+                granted_by.push(name.clone());
+            }
+
+            // Auto-create permission if not exists
+            if !self.permissions.contains_key(perm_name) {
+                let parts: Vec<&str> = perm_name.split('.').collect();
+                let service = parts.first().unwrap_or(&"").to_string();
+                let resource = parts.get(1).unwrap_or(&"").to_string();
+                let action = parts.get(2).unwrap_or(&"").to_string();
+
+                self.permissions.insert(perm_name.clone(), Permission {
+                    name: perm_name.clone(),
+                    service: service.clone(),
+                    provider: role.provider.clone(),
+                    resource,
+                    action,
+                    granted_by_roles: vec![],
+                    description: None,
+                });
+                self.all_permission_names.push(perm_name.clone());
+
+                self.service_to_permissions
+                    .entry(service)
+                    .or_default()
+                    .push(perm_name.clone());
+            }
+        }
+
+        self.roles.insert(name.clone(), role);
+        self.all_role_names.push(name);
+        replaced
+    }
+
+    /// Add a custom role alongside the predefined catalog
+    ///
+    /// The caller is responsible for validating the name with
+    /// `is_custom_role_name` and calling `finalize` afterwards. Replaces
+    /// and returns any role of the same name, like `index_role`.
+    pub fn index_custom_role(&mut self, name: String, title: String, description: String, stage: String, permissions: Vec<String>) -> Option<Role> {
+        let replaced = self.index_role(name.clone(), title, description, stage, permissions);
+        if let Some(role) = self.roles.get_mut(&name) {
+            role.custom = true;
+        }
+        replaced
+    }
+
+    /// Replace a role's definition, or add it if missing, keeping whether it
+    /// is a custom role
+    ///
+    /// Like the other index methods, takes effect in searches and role
+    /// comparisons once `finalize` is called.
+    pub fn update_role(&mut self, name: String, title: String, description: String, stage: String, permissions: Vec<String>) -> Option<Role> {
+        let custom = self.roles.get(&name).is_some_and(|r| r.custom);
+        if custom {
+            self.index_custom_role(name, title, description, stage, permissions)
+        } else {
+            self.index_role(name, title, description, stage, permissions)
+        }
+    }
+
+    /// Remove a role, returning it if it was indexed
+    ///
+    /// Its permissions stay indexed, even those no other role grants; call
+    /// `finalize` afterwards.
+    pub fn remove_role(&mut self, name: &str) -> Option<Role> {
+        let role = self.roles.remove(name)?;
+        self.all_role_names.retain(|n| n != name);
+        for perm_name in &role.included_permissions {
+            if let Some(roles) = self.permission_to_roles.get_mut(perm_name) {
+                roles.retain(|n| n != name);
+                if roles.is_empty() {
+                    self.permission_to_roles.remove(perm_name);
+                }
+            }
+        }
+        Some(role)
+    }
+
+    /// Whether a role with this name is indexed
+    pub fn has_role(&self, name: &str) -> bool {
+        self.roles.contains_key(name)
+    }
+
+    /// Look up a role by full name, also accepting the bare ID of a predefined role
+    pub fn get_role(&self, name: &str) -> Option<&Role> {
+        self.roles
+            .get(name)
+            .or_else(|| self.roles.get(&format!("roles/{}", name)))
+    }
+
+    /// The `limit` roles whose permission sets are most similar (Jaccard) to the given role
+    pub fn similar_roles(&self, name: &str, limit: usize) -> Option<Vec<SimilarRole>> {
+        let role = self.get_role(name)?;
+        let target = self.role_permission_sets.get(&role.name)?;
+
+        let mut scored: Vec<(&String, &PermissionSet, f64)> = self.role_permission_sets
+            .iter()
+            .filter(|(other, _)| **other != role.name)
+            .map(|(other, set)| (other, set, target.jaccard(set)))
+            .filter(|(_, _, similarity)| *similarity > 0.0)
+            .collect();
+        scored.sort_by(|a, b| {
+            b.2.partial_cmp(&a.2)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.0.cmp(b.0))
+        });
+
+        Some(
+            scored
+                .into_iter()
+                .take(limit)
+                .filter_map(|(other, set, similarity)| {
+                    self.roles.get(other).map(|r| SimilarRole {
+                        name: r.name.clone(),
+                        title: r.title.clone(),
+                        stage: r.stage.clone(),
+                        similarity,
+                        shared_permissions: target.intersection_len(set),
+                        permission_count: self.permission_count(r),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Predefined roles that together grant every requested permission
+    ///
+    /// Greedy set cover: repeatedly takes the role covering the most
+    /// still-missing permissions, preferring smaller roles on ties so the
+    /// result over-grants as little as possible. Deprecated, custom and
+    /// Google-managed service agent roles are never recommended, nor are
+    /// basic roles: they grant every service's permissions, including ones
+    /// added later, however few were asked for.
+    pub fn recommend_roles(&self, permissions: &[String]) -> RoleRecommendation {
+        let requested: HashSet<&str> = permissions.iter().map(String::as_str).collect();
+        let mut missing: HashSet<&str> = requested
+            .iter()
+            .copied()
+            .filter(|p| self.permission_to_roles.contains_key(*p))
+            .collect();
+        let mut uncovered: Vec<String> = requested
+            .iter()
+            .filter(|p| !missing.contains(*p))
+            .map(|p| p.to_string())
+            .collect();
+
+        let candidates: Vec<&Role> = missing
+            .iter()
+            .flat_map(|p| &self.permission_to_roles[*p])
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|name| self.roles.get(name))
+            .filter(|r| !r.custom && !r.is_deprecated() && !r.is_basic() && !r.is_service_agent())
+            .collect();
+
+        let mut roles = Vec::new();
+        while !missing.is_empty() {
+            let best = candidates
+                .iter()
+                .map(|role| {
+                    let covers: Vec<&str> = role
+                        .included_permissions
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|p| missing.contains(p))
+                        .collect();
+                    (role, covers)
+                })
+                .filter(|(_, covers)| !covers.is_empty())
+                .max_by(|(ra, a), (rb, b)| {
+                    a.len()
+                        .cmp(&b.len())
+                        .then_with(|| rb.included_permissions.len().cmp(&ra.included_permissions.len()))
+                        .then_with(|| rb.name.cmp(&ra.name))
+                });
+
+            let Some((role, covers)) = best else { break };
+            for p in &covers {
+                missing.remove(p);
+            }
+            let granted_requested = requested
+                .iter()
+                .filter(|p| role.grants(p, self.permission_provider(p)))
+                .count();
+            let permission_count = self.permission_count(role);
+            let mut covers: Vec<String> = covers.into_iter().map(String::from).collect();
+            covers.sort();
+
+            roles.push(RecommendedRole {
+                name: role.name.clone(),
+                title: role.title.clone(),
+                stage: role.stage.clone(),
+                covers,
+                permission_count,
+                extra_permissions: permission_count.saturating_sub(granted_requested),
+            });
+        }
+
+        // Only custom, deprecated or service agent roles grant these
+        uncovered.extend(missing.into_iter().map(String::from));
+        uncovered.sort();
+
+        RoleRecommendation { roles, uncovered }
+    }
+
+    /// The provably smallest sets of predefined roles granting every
+    /// requested permission, or `None` beyond `MAX_EXACT_COVER_PERMISSIONS`
+    ///
+    /// Exact counterpart to `recommend_roles`, with the same candidate
+    /// roles. Each candidate is reduced to the requested permissions it
+    /// grants (keeping only the smallest role per distinct subset), then a
+    /// depth-first search tries ever larger combinations, always branching
+    /// on the first permission still missing, up to the greedy answer's
+    /// size. All combinations of the minimal size are ranked by over-grant.
+    /// Past `MAX_COVER_STEPS` the search gives up and returns the greedy
+    /// recommendation with `optimal` unset.
+    pub fn minimal_role_cover(&self, permissions: &[String]) -> Option<MinimalCover> {
+        let requested: BTreeSet<&str> = permissions.iter().map(String::as_str).collect();
+        if requested.len() > MAX_EXACT_COVER_PERMISSIONS {
+            return None;
+        }
+
+        let eligible = |r: &&Role| !r.custom && !r.is_deprecated() && !r.is_basic() && !r.is_service_agent();
+        let (targets, mut uncovered): (Vec<&str>, Vec<&str>) = requested.iter().partition(|p| {
+            self.permission_to_roles
+                .get(**p)
+                .is_some_and(|roles| roles.iter().filter_map(|r| self.roles.get(r)).any(|r| eligible(&r)))
+        });
+        uncovered.sort();
+        let uncovered: Vec<String> = uncovered.into_iter().map(String::from).collect();
+        if targets.is_empty() {
+            return Some(MinimalCover { solutions: Vec::new(), optimal: true, uncovered });
+        }
+
+        // Smallest candidate role per subset of the targets it grants
+        let mut by_mask: HashMap<u32, &Role> = HashMap::new();
+        for role in targets
+            .iter()
+            .flat_map(|p| &self.permission_to_roles[*p])
+            .filter_map(|name| self.roles.get(name))
+            .filter(eligible)
+        {
+            let Some(set) = self.role_permission_sets.get(&role.name) else { continue };
+            let mask = targets
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| self.permission_bits.get(**p).is_some_and(|i| set.contains(*i)))
+                .fold(0u32, |mask, (bit, _)| mask | 1 << bit);
+            by_mask
+                .entry(mask)
+                .and_modify(|best| {
+                    let key = |r: &Role| (r.included_permissions.len(), r.name.clone());
+                    if key(role) < key(best) {
+                        *best = role;
+                    }
+                })
+                .or_insert(role);
+        }
+        let mut candidates: Vec<(u32, &Role)> = by_mask.into_iter().collect();
+        candidates.sort_by(|a, b| b.0.count_ones().cmp(&a.0.count_ones()).then_with(|| a.1.name.cmp(&b.1.name)));
+
+        let greedy: Vec<&Role> = self
+            .recommend_roles(&targets.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+            .roles
+            .iter()
+            .filter_map(|r| self.roles.get(&r.name))
+            .collect();
+
+        let mut search = CoverSearch {
+            full: u32::MAX >> (32 - targets.len()),
+            widest: candidates.first().map_or(1, |(mask, _)| mask.count_ones()),
+            candidates: &candidates,
+            chosen: Vec::new(),
+            found: HashSet::new(),
+            steps: 0,
+        };
+        let mut optimal = false;
+        for size in 1..=greedy.len() {
+            if !search.run(0, size) {
+                break;
+            }
+            if !search.found.is_empty() {
+                optimal = true;
+                break;
+            }
+        }
+
+        let mut solutions: Vec<RoleCover> = if optimal {
+            search.found
+                .into_iter()
+                .map(|indices| self.role_cover(indices.iter().map(|i| candidates[*i].1), targets.len()))
+                .collect()
+        } else {
+            vec![self.role_cover(greedy.into_iter(), targets.len())]
+        };
+        solutions.sort_by(|a, b| {
+            a.extra_permissions
+                .cmp(&b.extra_permissions)
+                .then_with(|| {
+                    let names = |c: &RoleCover| c.roles.iter().map(|r| r.name.clone()).collect::<Vec<_>>();
+                    names(a).cmp(&names(b))
+                })
+        });
+        solutions.truncate(MAX_COVER_SOLUTIONS);
+
+        Some(MinimalCover { solutions, optimal, uncovered })
+    }
+
+    /// Summary and over-grant of a combination granting `covered` requested
+    /// permissions
+    fn role_cover<'a>(&self, roles: impl Iterator<Item = &'a Role>, covered: usize) -> RoleCover {
+        let mut granted = PermissionSet::with_capacity(self.all_permission_names.len());
+        let mut summaries: Vec<RoleSummary> = Vec::new();
+        for role in roles {
+            if let Some(set) = self.role_permission_sets.get(&role.name) {
+                granted.union_with(set);
+            }
+            summaries.push(RoleSummary {
+                name: role.name.clone(),
+                title: role.title.clone(),
+                stage: role.stage.clone(),
+            });
+        }
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        let permission_count = granted.len();
+        RoleCover {
+            roles: summaries,
+            permission_count,
+            extra_permissions: permission_count.saturating_sub(covered),
+        }
+    }
+
+    /// Permissions ordered by how many roles grant them, most first
+    pub fn most_granted_permissions(&self) -> Vec<&Permission> {
+        let mut permissions: Vec<&Permission> = self.all_permissions().collect();
+        permissions.sort_by(|a, b| {
+            b.granted_by_roles.len()
+                .cmp(&a.granted_by_roles.len())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        permissions
+    }
+
+    /// Permissions granted by exactly one role, alphabetically
+    pub fn single_role_permissions(&self) -> Vec<&Permission> {
+        let mut permissions: Vec<&Permission> = self.all_permissions()
+            .filter(|p| p.granted_by_roles.len() == 1)
+            .collect();
+        permissions.sort_by(|a, b| a.name.cmp(&b.name));
+        permissions
+    }
+
+    /// Which of the `held` roles grant `permission`
+    ///
+    /// Roles may be given by full name or, for predefined roles, bare ID.
+    /// Wildcard grants count, minus the role's excluded operations (see
+    /// `Role::grants`).
+    pub fn check_access(&self, held: &[String], permission: &str) -> AccessCheck {
+        let provider = self.permission_provider(permission);
+        let grants = |name: &str| self.roles.get(name).is_some_and(|r| r.grants(permission, provider));
+
+        let mut granted_by = Vec::new();
+        let mut unknown_roles = Vec::new();
+        for name in held {
+            match self.get_role(name) {
+                Some(role) => {
+                    if (grants(&role.name) || role.included_roles().iter().any(|r| grants(r)))
+                        && !granted_by.contains(&role.name)
+                    {
+                        granted_by.push(role.name.clone());
+                    }
+                }
+                None => unknown_roles.push(name.clone()),
+            }
+        }
+
+        AccessCheck {
+            permission: permission.to_string(),
+            granted: !granted_by.is_empty(),
+            granted_by,
+            unknown_roles,
+        }
+    }
+
+    /// Permissions matching a wildcard pattern, in name order
+    ///
+    /// `*` matches any run of characters, dots included, so
+    /// `compute.instances.*` is every instance permission and
+    /// `*.setIamPolicy` every service's. A pattern without `*` matches at
+    /// most the one permission it names.
+    pub fn expand_permissions(&self, pattern: &str) -> Vec<&Permission> {
+        // Patterns naming a service only need that service's permissions
+        let candidates: Box<dyn Iterator<Item = &String>> = match pattern.split_once('.') {
+            Some((service, _)) if !service.contains('*') => {
+                Box::new(self.service_to_permissions.get(service).into_iter().flatten())
+            }
+            _ => Box::new(self.all_permission_names.iter()),
+        };
+        let mut matches: Vec<&Permission> = candidates
+            .filter(|name| wildcard_match(pattern, name))
+            .filter_map(|name| self.permissions.get(name))
+            .collect();
+        matches.sort_by(|a, b| a.name.cmp(&b.name));
+        matches
+    }
+
+    /// Look up a permission by name
+    pub fn get_permission(&self, name: &str) -> Option<&Permission> {
+        self.permissions.get(name)
+    }
+
+    /// Brief info for a role, used when embedding roles in other results
+    pub fn role_summary(&self, name: &str) -> Option<RoleSummary> {
+        self.roles.get(name).map(|r| RoleSummary {
+            name: r.name.clone(),
+            title: r.title.clone(),
+            stage: r.stage.clone(),
+        })
+    }
+
+    /// Add a standalone permission (not from a role)
+    pub fn index_permission(&mut self, name: String, service: String) {
+        if self.permissions.contains_key(&name) {
+            return;
+        }
+
+        let parts: Vec<&str> = name.split('.').collect();
+        let resource = parts.get(1).unwrap_or(&"").to_string();
+        let action = parts.get(2).unwrap_or(&"").to_string();
+
+        self.permissions.insert(name.clone(), Permission {
+            name: name.clone(),
+            service: service.clone(),
+            provider: DEFAULT_PROVIDER.to_string(),
+            resource,
+            action,
+            granted_by_roles: vec![],
+            description: None,
+        });
+        self.all_permission_names.push(name.clone());
+
+        self.service_to_permissions
+            .entry(service)
+            .or_default()
+            .push(name);
+    }
+
+    /// Fill in a permission's scraped details: its provider and description,
+    /// and the service, resource and action where the scraper split the name
+    /// (indexing the permission first if no role grants it)
+    pub fn apply_permission_data(&mut self, data: PermissionData) {
+        self.index_permission(data.name.clone(), data.service.clone());
+        if let Some(perm) = self.permissions.get_mut(&data.name) {
+            if !data.service.is_empty() && perm.service != data.service {
+                let previous = std::mem::replace(&mut perm.service, data.service.clone());
+                if let Some(names) = self.service_to_permissions.get_mut(&previous) {
+                    names.retain(|n| *n != data.name);
+                    if names.is_empty() {
+                        self.service_to_permissions.remove(&previous);
+                    }
+                }
+                self.service_to_permissions.entry(data.service).or_default().push(data.name.clone());
+            }
+            perm.provider = data.provider;
+            if !data.resource.is_empty() {
+                perm.resource = data.resource;
+            }
+            if !data.action.is_empty() {
+                perm.action = data.action;
+            }
+            perm.description = data.description.filter(|d| !d.is_empty());
+        }
+    }
+
+    /// Bitset of the given permissions, skipping unknown ones
+    fn permission_set(&self, permissions: &[String]) -> PermissionSet {
+        let mut set = PermissionSet::with_capacity(self.permission_bits.len());
+        for perm in permissions {
+            if let Some(&idx) = self.permission_bits.get(perm) {
+                set.insert(idx);
+            }
+        }
+        set
+    }
+
+    /// Cloud of an indexed permission, matching AWS and Azure names
+    /// case-insensitively as their grants do
+    fn permission_provider(&self, name: &str) -> Option<&str> {
+        match self.permissions.get(name) {
+            Some(perm) => Some(&perm.provider),
+            None => self
+                .permissions
+                .values()
+                .find(|p| p.provider != DEFAULT_PROVIDER && p.name.eq_ignore_ascii_case(name))
+                .map(|p| p.provider.as_str()),
+        }
+    }
+
+    /// How many permissions a role grants: those it lists, or for wildcard
+    /// grants the indexed permissions they expand to
+    pub fn permission_count(&self, role: &Role) -> usize {
+        if !role.has_wildcard_grants() {
+            return role.included_permissions.len();
+        }
+        self.role_permission_sets
+            .get(&role.name)
+            .map_or_else(|| self.granted_set(role).len(), PermissionSet::len)
+    }
+
+    /// Bitset of the indexed permissions a role grants, expanding its
+    /// wildcard grants and leaving out its excluded operations
+    fn granted_set(&self, role: &Role) -> PermissionSet {
+        if !role.has_wildcard_grants() {
+            return self.permission_set(&role.included_permissions);
+        }
+        let mut set = PermissionSet::with_capacity(self.permission_bits.len());
+        for (idx, name) in self.all_permission_names.iter().enumerate() {
+            // Patterns a role lists are indexed by name too, but aren't permissions
+            if !name.contains('*') && role.grants(name, self.permissions.get(name).map(|p| p.provider.as_str())) {
+                set.insert(idx);
+            }
+        }
+        set
+    }
+
+    /// Roles granting every one of `permissions`, tightest fit first
+    ///
+    /// A single role that covers the whole set, instead of several stacked
+    /// ones. Deprecated and Google-managed service agent roles are left out,
+    /// as in `recommend_roles`. Requested permissions that aren't indexed
+    /// can't be granted by any role, so they leave the result empty and are
+    /// reported.
+    pub fn roles_granting_all(&self, permissions: &[String]) -> SupersetRoles {
+        let unknown_permissions: Vec<String> = permissions
+            .iter()
+            .filter(|p| !self.permission_bits.contains_key(*p))
+            .cloned()
+            .collect();
+        if !unknown_permissions.is_empty() {
+            return SupersetRoles { roles: Vec::new(), unknown_permissions };
+        }
+
+        let requested = self.permission_set(permissions);
+        let requested_len = requested.len();
+        let mut roles: Vec<SupersetRole> = self.role_permission_sets
+            .iter()
+            .filter(|(_, set)| set.is_superset(&requested))
+            .filter_map(|(name, set)| {
+                let role = self.roles.get(name).filter(|r| !r.is_deprecated() && !r.is_service_agent())?;
+                Some(SupersetRole {
+                    name: role.name.clone(),
+                    title: role.title.clone(),
+                    stage: role.stage.clone(),
+                    permission_count: self.permission_count(role),
+                    extra_permissions: set.len() - requested_len,
+                })
+            })
+            .collect();
+        roles.sort_by(|a, b| a.extra_permissions.cmp(&b.extra_permissions).then_with(|| a.name.cmp(&b.name)));
+
+        SupersetRoles { roles, unknown_permissions }
+    }
+
+    /// Finalize indexes after loading all data
+    ///
+    /// Rebuilds every derived index from scratch, so it is safe to call
+    /// again after adding, updating or removing roles.
+    pub fn finalize(&mut self) {
+        // Update permissions with their granting roles
+        for (perm_name, perm) in self.permissions.iter_mut() {
+            perm.granted_by_roles = self.permission_to_roles.get(perm_name).cloned().unwrap_or_default();
+        }
+
+        // Build role bitsets for set comparisons
+        self.permission_bits = self.all_permission_names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.clone(), idx))
+            .collect();
+        self.role_permission_sets = self.roles
+            .values()
+            .map(|role| (role.name.clone(), self.granted_set(role)))
+            .collect();
+
+        self.catalog_stats = self.compute_catalog_stats();
+
+        // Build the spelling dictionary from permission names, role names and titles
+        self.spelling = SpellingIndex::build(
+            self.all_permission_names
+                .iter()
+                .map(String::as_str)
+                .chain(self.roles.values().flat_map(|r| [r.name.as_str(), r.title.as_str()])),
+        );
+    }
+
+    /// Query with unknown tokens replaced by their closest indexed word, if any changed
+    pub fn correct_query(&self, query: &str) -> Option<String> {
+        self.spelling.correct_query(query)
+    }
+
+    /// Popularity of roles and permissions from how often each query was
+    /// searched
+    ///
+    /// A query counts towards an item when it names it outright: a role's
+    /// full name, bare ID ("editor" for `roles/editor`) or title, or a
+    /// permission name, all compared case-insensitively. Queries that only
+    /// match loosely say little about which result was wanted, so they add
+    /// nothing.
+    pub fn popularity<'a>(&self, query_counts: impl IntoIterator<Item = (&'a str, usize)>) -> Popularity {
+        let mut items: HashMap<String, Vec<&str>> = HashMap::new();
+        for role in self.roles.values() {
+            let bare = role.name.strip_prefix("roles/").unwrap_or(&role.name);
+            for key in [role.name.as_str(), bare, role.title.as_str()] {
+                let names = items.entry(key.to_lowercase()).or_default();
+                if !names.contains(&role.name.as_str()) {
+                    names.push(&role.name);
+                }
+            }
+        }
+        for name in &self.all_permission_names {
+            items.entry(name.to_lowercase()).or_default().push(name);
+        }
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (query, count) in query_counts {
+            for name in items.get(&query.trim().to_lowercase()).into_iter().flatten() {
+                *counts.entry(name.to_string()).or_default() += count;
+            }
+        }
+        Popularity::from_counts(counts)
+    }
+
+    /// Ranking the engine was built with, for callers assembling
+    /// `SearchOptions`
+    pub fn ranking(&self) -> RankingConfig {
+        self.ranking
+    }
+
+    pub(super) fn set_ranking(&mut self, ranking: RankingConfig) {
+        self.ranking = ranking;
+    }
+
+    /// Cached catalog breakdowns (empty until finalize() is called)
+    pub fn catalog_stats(&self) -> &CatalogStats {
+        &self.catalog_stats
+    }
+
+    fn compute_catalog_stats(&self) -> CatalogStats {
+        let permissions_by_service = self.service_to_permissions
+            .iter()
+            .map(|(service, perms)| (service.clone(), perms.len()))
+            .collect();
+
+        let mut roles_by_stage: BTreeMap<String, usize> = BTreeMap::new();
+        let mut total_grants = 0;
+        for role in self.roles.values() {
+            *roles_by_stage.entry(role.stage.clone()).or_default() += 1;
+            total_grants += role.included_permissions.len();
+        }
+
+        let average_permissions_per_role = if self.roles.is_empty() {
+            0.0
+        } else {
+            total_grants as f64 / self.roles.len() as f64
+        };
+
+        CatalogStats {
+            permissions_by_service,
+            roles_by_stage,
+            average_permissions_per_role,
+        }
+    }
+
+    /// Search permissions with associated roles
+    pub fn search_permissions(&self, query: &str, mode: &str, options: &SearchOptions) -> Vec<PermissionSearchResult> {
+        let parsed = ParsedQuery::parse(query);
+        if parsed.is_empty() {
+            return vec![];
+        }
+
+        let match_query = MatchQuery::new(&parsed.text, mode, options.min_score);
+        let mut matches = self.match_permissions(&match_query, options);
+        // Exclusions are applied to the positive match set
+        matches.retain(|(name, _)| {
+            let fields = [name.as_str()];
+            parsed.matches_phrases(&fields) && !parsed.is_excluded(&fields)
+        });
+        // A permission belongs to every cloud one of its granting roles comes from
+        if let Some(provider) = &options.provider {
+            matches.retain(|(name, _)| {
+                self.permission_to_roles
+                    .get(*name)
+                    .is_some_and(|roles| roles.iter().filter_map(|r| self.roles.get(r)).any(|r| r.provider == *provider))
+            });
+        }
+
+        let explained: HashMap<&String, FieldMatch> = if options.explain {
+            matches.iter().copied().collect()
+        } else {
+            HashMap::new()
+        };
+        let mut matches: Vec<(&String, f64)> = matches
+            .into_iter()
+            .map(|(name, m)| (name, options.ranking.score(&m, name, &match_query.lower, None, None, options.popularity.as_deref())))
+            .collect();
+
+        // Permissions have no permission count or stage, so those keys fall back to score
+        let field = match options.sort {
+            SortField::Name => SortField::Name,
+            _ => SortField::Score,
+        };
+        self.sort_matches(&mut matches, field, options.order());
+
+        matches
+            .into_iter()
+            .skip(options.offset)
+            .take(options.limit)
+            .filter_map(|(name, score)| {
+                self.permissions.get(name).map(|perm| {
+                    let granted_by_roles: Vec<RoleSummary> = self.permission_to_roles
+                        .get(name)
+                        .map(|role_names| {
+                            role_names.iter()
+                                .filter_map(|rn| self.role_summary(rn))
+                                .take(5) // Limit to 5 roles per permission
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    PermissionSearchResult {
+                        name: perm.name.clone(),
+                        service: perm.service.clone(),
+                        resource: perm.resource.clone(),
+                        action: perm.action.clone(),
+                        description: perm.description.clone(),
+                        score,
+                        granted_by_roles,
+                        warnings: self.permission_warnings(name).into_iter().collect(),
+                        explanation: explained
+                            .get(name)
+                            .map(|m| options.ranking.explain(m, name, &match_query.lower, None, None, options.popularity.as_deref())),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Search roles with their permissions
+    pub fn search_roles(&self, query: &str, mode: &str, options: &SearchOptions) -> Vec<RoleSearchResult> {
+        let parsed = ParsedQuery::parse(query);
+        if parsed.is_empty() {
+            return vec![];
+        }
+
+        let match_query = MatchQuery::new(&parsed.text, mode, options.min_score);
+        let mut matches = self.match_roles(&match_query, options);
+        // Exclusions are applied to the positive match set
+        matches.retain(|(name, _)| {
+            self.roles.get(*name).is_some_and(|r| {
+                let fields = [r.name.as_str(), r.title.as_str()];
+                parsed.matches_phrases(&fields) && !parsed.is_excluded(&fields)
+            })
+        });
+
+        if !options.include_deprecated {
+            matches.retain(|(name, _)| self.roles.get(*name).is_some_and(|r| !r.is_deprecated()));
+        }
+        if let Some(provider) = &options.provider {
+            matches.retain(|(name, _)| self.roles.get(*name).is_some_and(|r| r.provider == *provider));
+        }
+
+        let explained: HashMap<&String, FieldMatch> = if options.explain {
+            matches.iter().copied().collect()
+        } else {
+            HashMap::new()
+        };
+        let mut matches: Vec<(&String, f64)> = matches
+            .into_iter()
+            .filter_map(|(name, m)| {
+                let role = self.roles.get(name)?;
+                let score = options.ranking.score(
+                    &m,
+                    name,
+                    &match_query.lower,
+                    Some(&role.stage),
+                    Some(self.permission_count(role)),
+                    options.popularity.as_deref(),
+                );
+                Some((name, score))
+            })
+            .collect();
+
+        self.sort_matches(&mut matches, options.sort, options.order());
+
+        matches
+            .into_iter()
+            .skip(options.offset)
+            .take(options.limit)
+            .filter_map(|(name, score)| {
+                self.roles.get(name).map(|role| {
+                    RoleSearchResult {
+                        name: role.name.clone(),
+                        title: role.title.clone(),
+                        description: role.description.clone(),
+                        stage: role.stage.clone(),
+                        score,
+                        permission_count: self.permission_count(role),
+                        sample_permissions: role.included_permissions.iter().take(5).cloned().collect(),
+                        deprecated: role.is_deprecated(),
+                        custom: role.custom,
+                        warnings: role
+                            .is_deprecated()
+                            .then(|| ResultWarning::deprecated_role(&role.name, &role.description))
+                            .into_iter()
+                            .collect(),
+                        explanation: explained.get(name).map(|m| {
+                            options.ranking.explain(
+                                m,
+                                name,
+                                &match_query.lower,
+                                Some(&role.stage),
+                                Some(self.permission_count(role)),
+                                options.popularity.as_deref(),
+                            )
+                        }),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Deprecation warning for a permission only deprecated roles grant
+    fn permission_warnings(&self, name: &str) -> Option<ResultWarning> {
+        let roles = self.permission_to_roles.get(name)?;
+        ResultWarning::deprecated_permission(
+            roles
+                .iter()
+                .filter_map(|r| self.roles.get(r))
+                .map(|r| (r.name.as_str(), r.description.as_str(), r.is_deprecated())),
+        )
+    }
+
+    /// Permissions matching a query, each with how it matched; a
+    /// phrase-only query matches every permission
+    fn match_permissions(&self, query: &MatchQuery, options: &SearchOptions) -> Vec<(&String, FieldMatch)> {
+        if query.mode == "exact" && !query.text.is_empty() {
+            return self.permissions
+                .get(query.text)
+                .map(|perm| vec![(&perm.name, FieldMatch::exact())])
+                .unwrap_or_default();
+        }
+        self.all_permission_names
+            .iter()
+            .filter_map(|name| {
+                options.ranking
+                    .permission_match(query, name, &name.to_lowercase())
+                    .map(|m| (name, m))
+            })
+            .collect()
+    }
+
+    /// Roles matching a query, each with how it matched; a phrase-only query
+    /// matches every role
+    fn match_roles(&self, query: &MatchQuery, options: &SearchOptions) -> Vec<(&String, FieldMatch)> {
+        if query.mode == "exact" && !query.text.is_empty() {
+            return self.roles
+                .get(query.text)
+                .map(|role| vec![(&role.name, FieldMatch::exact())])
+                .unwrap_or_default();
+        }
+        self.all_role_names
+            .iter()
+            .filter_map(|name| {
+                let role = self.roles.get(name)?;
+                options.ranking
+                    .role_match(
+                        query,
+                        name,
+                        &name.to_lowercase(),
+                        &role.title.to_lowercase(),
+                        || role.description.to_lowercase(),
+                    )
+                    .map(|m| (name, m))
+            })
+            .collect()
+    }
+
+    /// Legacy exact search for backward compatibility
+    pub fn search_exact(&self, query: &str) -> Option<SearchResult<String>> {
+        self.permissions
+            .get(query)
+            .map(|perm| SearchResult {
+                item: perm.name.clone(),
+                score: 1.0,
+            })
+    }
+
+    /// Legacy prefix search
+    pub fn search_prefix(&self, query: &str) -> Vec<SearchResult<String>> {
+        let query_lower = query.to_lowercase();
+        self.all_permission_names
+            .iter()
+            .filter(|perm| perm.to_lowercase().starts_with(&query_lower))
+            .map(|perm| SearchResult {
+                item: perm.clone(),
+                score: 0.8,
+            })
+            .take(20)
+            .collect()
+    }
+
+    /// Legacy fuzzy search
+    pub fn search_fuzzy(&self, query: &str, threshold: f64) -> Vec<SearchResult<String>> {
+        let query_lower = query.to_lowercase();
+        let query_ngrams = self.extract_ngrams(&query_lower, 3);
+
+        self.all_permission_names
+            .iter()
+            .filter_map(|perm| {
+                let perm_lower = perm.to_lowercase();
+                // Substring match boost
+                if perm_lower.contains(&query_lower) {
+                    return Some(SearchResult {
+                        item: perm.clone(),
+                        score: 0.85,
+                    });
+                }
+                let perm_ngrams = self.extract_ngrams(&perm_lower, 3);
+                let score = self.calculate_similarity(&query_ngrams, &perm_ngrams);
+
+                if score >= threshold {
+                    Some(SearchResult {
+                        item: perm.clone(),
+                        score,
+                    })
+                } else {
+                    None
+                }
+            })
+            .take(20)
+            .collect()
+    }
+
+    /// Iterate over all permissions in index order
+    pub fn all_permissions(&self) -> impl Iterator<Item = &Permission> {
+        self.all_permission_names
+            .iter()
+            .filter_map(|name| self.permissions.get(name))
+    }
+
+    /// Iterate over all roles in index order
+    pub fn all_roles(&self) -> impl Iterator<Item = &Role> {
+        self.all_role_names
+            .iter()
+            .filter_map(|name| self.roles.get(name))
+    }
+
+    /// All permissions ordered by name when requested (other fields keep index order)
+    pub fn list_permissions(&self, sort: Option<(SortField, SortOrder)>) -> Vec<&Permission> {
+        let mut permissions: Vec<&Permission> = self.all_permissions().collect();
+        if let Some((SortField::Name, order)) = sort {
+            permissions.sort_by(|a, b| order.apply(a.name.cmp(&b.name)));
+        }
+        permissions
+    }
+
+    /// All roles ordered by the given field (index order when `None`)
+    pub fn list_roles(&self, sort: Option<(SortField, SortOrder)>) -> Vec<&Role> {
+        let mut roles: Vec<&Role> = self.all_roles().collect();
+        if let Some((field, order)) = sort {
+            roles.sort_by(|a, b| {
+                order
+                    .apply(compare_roles(a, b, field))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+        }
+        roles
+    }
+
+    /// Order (name, score) matches by the given field, breaking ties by name
+    fn sort_matches(&self, matches: &mut [(&String, f64)], field: SortField, order: SortOrder) {
+        matches.sort_by(|a, b| {
+            let ordering = match field {
+                SortField::Score => a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal),
+                SortField::Name => a.0.cmp(b.0),
+                SortField::PermissionCount | SortField::Stage => {
+                    match (self.roles.get(a.0), self.roles.get(b.0)) {
+                        (Some(ra), Some(rb)) => compare_roles(ra, rb, field),
+                        _ => Ordering::Equal,
+                    }
+                }
+            };
+            order.apply(ordering).then_with(|| a.0.cmp(b.0))
+        });
+    }
+
+    /// Approximate heap usage per structure (a lower bound; see `memory`)
+    pub fn memory_usage(&self) -> IndexMemory {
+        let names = memory::strings(&self.all_permission_names) + memory::strings(&self.all_role_names);
+        let permissions = memory::map(&self.permissions, |name, perm| {
+            [name, &perm.name, &perm.service, &perm.resource, &perm.action].into_iter().map(memory::string).sum::<usize>()
+                + memory::strings(&perm.granted_by_roles)
+        });
+        let roles = memory::map(&self.roles, |name, role| {
+            [name, &role.name, &role.title, &role.description, &role.stage].into_iter().map(memory::string).sum::<usize>()
+                + memory::strings(&role.included_permissions)
+        });
+        let maps = [&self.permission_to_roles, &self.service_to_permissions]
+            .into_iter()
+            .map(|map| memory::map(map, |key, names| memory::string(key) + memory::strings(names)))
+            .sum::<usize>()
+            + memory::map(&self.permission_bits, |name, _| memory::string(name));
+        let role_sets = memory::map(&self.role_permission_sets, |name, set| memory::string(name) + set.heap_bytes());
+        let spelling = self.spelling.heap_bytes();
+
+        IndexMemory {
+            names,
+            permissions,
+            roles,
+            maps,
+            role_sets,
+            spelling,
+            total: names + permissions + roles + maps + role_sets + spelling,
+        }
+    }
+
+    /// Get stats
+    pub fn stats(&self) -> (usize, usize) {
+        (self.permissions.len(), self.roles.len())
+    }
+
+    /// Extract character n-grams from a string
+    fn extract_ngrams(&self, text: &str, n: usize) -> Vec<String> {
+        scoring::ngrams(text, n)
+    }
+
+    /// Calculate Jaccard similarity between two n-gram sets
+    fn calculate_similarity(&self, set1: &[String], set2: &[String]) -> f64 {
+        scoring::similarity(set1, set2)
+    }
+}
+
+impl Default for SearchEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn ngrams_of_ascii_text() {
+        let engine = SearchEngine::new();
+        assert_eq!(engine.extract_ngrams("abcd", 3), vec!["abc", "bcd"]);
+        assert_eq!(engine.extract_ngrams("ab", 3), vec!["ab"]);
+    }
+
+    #[test]
+    fn ngrams_count_chars_not_bytes() {
+        let engine = SearchEngine::new();
+        // Two chars but six bytes: shorter than n, so returned whole
+        assert_eq!(engine.extract_ngrams("\u{201c}\u{201d}", 3), vec!["\u{201c}\u{201d}"]);
+        // Three chars but more than three bytes: exactly one n-gram
+        assert_eq!(engine.extract_ngrams("\u{201c}a\u{201d}", 3), vec!["\u{201c}a\u{201d}"]);
+    }
+
+    #[test]
+    fn ngrams_of_non_latin_text() {
+        let engine = SearchEngine::new();
+        assert_eq!(
+            engine.extract_ngrams("хранилище", 3),
+            vec!["хра", "ран", "ани", "нил", "или", "лищ", "ище"]
+        );
+        assert_eq!(engine.extract_ngrams("存储桶", 2), vec!["存储", "储桶"]);
+    }
+
+    #[test]
+    fn wildcards_expand_to_matching_permissions() {
+        assert!(wildcard_match("compute.instances.*", "compute.instances.get"));
+        assert!(wildcard_match("*.setIamPolicy", "storage.buckets.setIamPolicy"));
+        assert!(wildcard_match("compute.*.get", "compute.instances.get"));
+        assert!(!wildcard_match("compute.*.get", "compute.instances.getIamPolicy"));
+        assert!(!wildcard_match("compute.instances.get", "compute.instances.getIamPolicy"));
+        assert!(!wildcard_match("a*a", "a"));
+
+        let mut engine = SearchEngine::new();
+        index_catalog(&mut engine);
+        let names = |pattern| engine.expand_permissions(pattern).into_iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names("storage.buckets.*"), vec!["storage.buckets.delete", "storage.buckets.get"]);
+        assert_eq!(names("*.get"), vec!["storage.buckets.get"]);
+        assert!(names("compute.*").is_empty());
+    }
+
+    #[test]
+    fn minimal_cover_beats_greedy() {
+        let mut engine = SearchEngine::new();
+        let perms = |names: &[&str]| names.iter().map(|p| format!("svc.res.{}", p)).collect::<Vec<_>>();
+        for (name, granted) in [
+            ("roles/svc.big", perms(&["a", "b", "c", "d"])),
+            ("roles/svc.left", perms(&["a", "b", "e"])),
+            ("roles/svc.leftWide", perms(&["a", "b", "e", "x"])),
+            ("roles/svc.right", perms(&["c", "d", "f"])),
+        ] {
+            engine.index_role(name.to_string(), name.to_string(), String::new(), "GA".to_string(), granted);
+        }
+        engine.finalize();
+
+        let requested = [perms(&["a", "b", "c", "d", "e", "f"]), vec!["svc.res.unknown".to_string()]].concat();
+        // Greedy starts with the widest role and needs two more
+        assert_eq!(engine.recommend_roles(&requested).roles.len(), 3);
+
+        let cover = engine.minimal_role_cover(&requested).unwrap();
+        assert!(cover.optimal);
+        assert_eq!(cover.uncovered, vec!["svc.res.unknown"]);
+        let names: Vec<&str> = cover.solutions[0].roles.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["roles/svc.left", "roles/svc.right"]);
+        assert_eq!(cover.solutions[0].extra_permissions, 0);
+
+        let too_many: Vec<String> = (0..=MAX_EXACT_COVER_PERMISSIONS).map(|i| format!("svc.res.p{}", i)).collect();
+        assert!(engine.minimal_role_cover(&too_many).is_none());
+    }
+
+    #[test]
+    fn ranking_config_reorders_equal_matches() {
+        let mut engine = SearchEngine::new();
+        let perms = |n: usize| (0..n).map(|i| format!("storage.objects.p{}", i)).collect::<Vec<_>>();
+        engine.index_role("roles/storage.big".to_string(), "Storage Big".to_string(), String::new(), "GA".to_string(), perms(50));
+        engine.index_role("roles/storage.small".to_string(), "Storage Small".to_string(), String::new(), "BETA".to_string(), perms(2));
+        engine.index_role(
+            "roles/logging.writer".to_string(),
+            "Log Writer".to_string(),
+            "Write access to storage logs".to_string(),
+            "GA".to_string(),
+            perms(1),
+        );
+        engine.finalize();
+
+        let names = |mode, ranking| {
+            let options = SearchOptions { ranking, ..SearchOptions::default() };
+            engine.search_roles("storage", mode, &options).into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+        // Identical prefix scores fall back to name order
+        assert_eq!(names("prefix", RankingConfig::default()), vec!["roles/storage.big", "roles/storage.small"]);
+
+        let ga = RankingConfig { ga_boost: 0.1, ..RankingConfig::default() };
+        assert_eq!(names("prefix", ga), vec!["roles/storage.big", "roles/storage.small"]);
+        let damped = RankingConfig { ga_boost: 0.1, permission_count_damping: 0.5, ..RankingConfig::default() };
+        assert_eq!(names("prefix", damped), vec!["roles/storage.small", "roles/storage.big"]);
+
+        // Descriptions only match once weighted
+        assert!(!names("fuzzy", RankingConfig::default()).contains(&"roles/logging.writer".to_string()));
+        let described = RankingConfig { description_weight: 0.5, ..RankingConfig::default() };
+        assert_eq!(names("fuzzy", described).last().map(String::as_str), Some("roles/logging.writer"));
+
+        // Explanations account for the whole score
+        let options = SearchOptions { ranking: damped, explain: true, ..SearchOptions::default() };
+        let result = &engine.search_roles("storage", "prefix", &options)[0];
+        let explanation = result.explanation.as_ref().unwrap();
+        assert_eq!(explanation.field, Some(scoring::MatchField::Title)); // "roles/..." names never start with the query
+        assert_eq!(explanation.kind, scoring::MatchKind::Prefix);
+        assert_eq!(explanation.boosts.len(), 1); // BETA: damping only
+        assert_eq!(explanation.score, result.score);
+        assert!(engine.search_roles("storage", "prefix", &SearchOptions::default())[0].explanation.is_none());
+
+        assert!(RankingConfig { title_weight: -1.0, ..RankingConfig::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn popularity_lifts_frequently_searched_roles() {
+        let mut engine = SearchEngine::new();
+        let perms = vec!["storage.objects.get".to_string()];
+        engine.index_role("roles/storage.objectAdmin".to_string(), "Storage Object Admin".to_string(), String::new(), "GA".to_string(), perms.clone());
+        engine.index_role("roles/storage.objectViewer".to_string(), "Storage Object Viewer".to_string(), String::new(), "GA".to_string(), perms);
+        engine.finalize();
+
+        // Bare IDs and titles count towards the role; loose matches don't count at all
+        let popularity = engine.popularity([("objectViewer", 3), ("Storage Object Viewer", 2), ("storage", 50)]);
+        assert_eq!(popularity.get("roles/storage.objectViewer"), 1.0);
+        assert_eq!(popularity.get("roles/storage.objectAdmin"), 0.0);
+
+        let names = |ranking| {
+            let options = SearchOptions { ranking, popularity: Some(Arc::new(popularity.clone())), ..SearchOptions::default() };
+            engine.search_roles("storage object", "prefix", &options).into_iter().map(|r| r.name).collect::<Vec<_>>()
+        };
+        // Popularity is ignored until the boost is configured
+        assert_eq!(names(RankingConfig::default()), vec!["roles/storage.objectAdmin", "roles/storage.objectViewer"]);
+        let popular = RankingConfig { popularity_boost: 0.2, ..RankingConfig::default() };
+        assert_eq!(names(popular), vec!["roles/storage.objectViewer", "roles/storage.objectAdmin"]);
+    }
+
+    #[test]
+    fn builder_validates_and_finalizes() {
+        use crate::data::RoleData;
+        use crate::search::BuildError;
+
+        let role = |name: &str, permissions: &[&str]| RoleData {
+            name: name.to_string(),
+            title: "Storage Viewer".to_string(),
+            description: String::new(),
+            stage: "GA".to_string(),
+            included_permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            excluded_permissions: Vec::new(),
+            custom: false,
+            provider: "gcp".to_string(),
+        };
+
+        let engine = SearchEngine::builder().with_roles([role("roles/storage.viewer", &["storage.objects.get"])]).build().unwrap();
+        // Finalized: permissions know their roles without a separate call
+        assert_eq!(engine.get_permission("storage.objects.get").unwrap().granted_by_roles, vec!["roles/storage.viewer"]);
+
+        let err = SearchEngine::builder().with_roles([role("roles/a", &[]), role(" ", &[])]).build().err();
+        assert_eq!(err, Some(BuildError::EmptyRoleName { position: 1 }));
+        for malformed in ["", "storage..get", "storage.objects get"] {
+            let err = SearchEngine::builder().with_roles([role("roles/a", &[malformed])]).build().err();
+            assert!(matches!(err, Some(BuildError::MalformedPermission { .. })), "{:?}", malformed);
+        }
+        let bad_ranking = RankingConfig { ga_boost: -1.0, ..RankingConfig::default() };
+        assert!(matches!(SearchEngine::builder().with_ranking(bad_ranking).build(), Err(BuildError::InvalidRanking(_))));
+    }
+
+    #[test]
+    fn from_dataset_keeps_permission_details() {
+        let data: crate::IamDataFile = serde_json::from_value(serde_json::json!({
+            "roles": [{
+                "name": "roles/storage.azureReader",
+                "title": "Storage Account Reader",
+                "description": "",
+                "stage": "GA",
+                "provider": "azure",
+                "included_permissions": ["Microsoft.Storage/storageAccounts/read"],
+            }],
+            "permissions": [{
+                "name": "Microsoft.Storage/storageAccounts/read",
+                "service": "Microsoft.Storage",
+                "provider": "azure",
+                "resource": "storageAccounts",
+                "action": "read",
+                "description": "Returns the list of storage accounts",
+            }],
+            "metadata": {"total_roles": 1, "total_permissions": 1},
+        }))
+        .unwrap();
+
+        // The prebuilt index's engine must agree with one built directly
+        let index = crate::PrebuiltIndex::build(&data);
+        for engine in [SearchEngine::from_dataset(data).unwrap(), index.to_engine().unwrap()] {
+            let perm = engine.get_permission("Microsoft.Storage/storageAccounts/read").unwrap();
+            // The scraped service replaces the split of the name, in /stats too
+            assert_eq!((perm.service.as_str(), perm.provider.as_str()), ("Microsoft.Storage", "azure"));
+            assert_eq!(engine.catalog_stats().permissions_by_service.keys().collect::<Vec<_>>(), vec!["Microsoft.Storage"]);
+            assert_eq!((perm.resource.as_str(), perm.action.as_str()), ("storageAccounts", "read"));
+            assert_eq!(engine.get_role("roles/storage.azureReader").unwrap().provider, "azure");
+            assert_eq!(perm.granted_by_roles, vec!["roles/storage.azureReader"]);
+            let result = &engine.search_permissions("microsoft", "prefix", &SearchOptions::default())[0];
+            assert_eq!(result.description.as_deref(), Some("Returns the list of storage accounts"));
+        }
+    }
+
+    #[test]
+    fn provider_filter_uses_the_stored_provider() {
+        let data: crate::IamDataFile = serde_json::from_value(serde_json::json!({
+            "roles": [
+                {"name": "roles/storage.objectViewer", "title": "Storage Object Viewer", "description": "", "stage": "GA",
+                 "included_permissions": ["storage.objects.get"]},
+                // Names only a provider field can place
+                {"name": "ReadOnlyAccess", "title": "Storage Read Only", "description": "", "stage": "GA",
+                 "included_permissions": ["s3:GetObject"], "provider": "aws"},
+            ],
+            "permissions": [],
+            "metadata": {"total_roles": 2, "total_permissions": 2},
+        }))
+        .unwrap();
+
+        let index = crate::PrebuiltIndex::build(&data);
+        let engine = SearchEngine::from_dataset(data).unwrap();
+        let options = |provider: &str| SearchOptions { provider: Some(provider.to_string()), ..SearchOptions::default() };
+        let roles: Vec<String> = engine.search_roles("storage", "prefix", &options("aws")).into_iter().map(|r| r.name).collect();
+        assert_eq!(roles, vec!["ReadOnlyAccess"]);
+        let permissions: Vec<String> = engine.search_permissions("s3", "prefix", &options("aws")).into_iter().map(|p| p.name).collect();
+        assert_eq!(permissions, vec!["s3:GetObject"]);
+        assert!(engine.search_permissions("s3", "prefix", &options("gcp")).is_empty());
+
+        let index = crate::index::IndexBuf::new(&index.to_bytes().unwrap()).unwrap();
+        let roles: Vec<String> = index.index().search_roles("storage", "prefix", &options("aws")).into_iter().map(|r| r.name).collect();
+        assert_eq!(roles, vec!["ReadOnlyAccess"]);
+    }
+
+    #[test]
+    fn excluded_operations_limit_wildcard_grants() {
+        let data: crate::IamDataFile = serde_json::from_value(serde_json::json!({
+            "roles": [
+                {"name": "/providers/Microsoft.Authorization/roleDefinitions/contributor", "title": "Contributor",
+                 "description": "", "stage": "GA", "provider": "azure",
+                 "included_permissions": ["*"],
+                 "excluded_permissions": ["Microsoft.Authorization/*/Delete", "Microsoft.Authorization/*/Write"]},
+                {"name": "/providers/Microsoft.Authorization/roleDefinitions/reader", "title": "Reader",
+                 "description": "", "stage": "GA", "provider": "azure",
+                 "included_permissions": ["Microsoft.Storage/storageAccounts/read", "Microsoft.Authorization/roleAssignments/write"]},
+                // Other clouds' permissions, which Azure's `*` must not cover
+                {"name": "roles/storage.objectViewer", "title": "Storage Object Viewer", "description": "", "stage": "GA",
+                 "included_permissions": ["storage.objects.get", "storage.objects.list"]},
+                {"name": "AmazonS3ReadOnlyAccess", "title": "S3 Read Only", "description": "", "stage": "GA", "provider": "aws",
+                 "included_permissions": ["s3:GetObject"]},
+            ],
+            "permissions": [],
+            "metadata": {"total_roles": 4, "total_permissions": 6},
+        }))
+        .unwrap();
+        let engine = SearchEngine::from_dataset(data).unwrap();
+        let contributor = "/providers/Microsoft.Authorization/roleDefinitions/contributor".to_string();
+
+        let check = |permission: &str| engine.check_access(std::slice::from_ref(&contributor), permission).granted;
+        assert!(check("Microsoft.Storage/storageAccounts/read"));
+        // Azure operations match case-insensitively
+        assert!(!check("Microsoft.Authorization/roleAssignments/write"));
+        assert!(!check("microsoft.authorization/roleassignments/delete"));
+
+        // Set comparisons see the expanded grants, less the exclusions
+        let superset = engine.roles_granting_all(&["Microsoft.Storage/storageAccounts/read".to_string()]);
+        assert!(superset.roles.iter().any(|r| r.name == contributor));
+        let superset = engine.roles_granting_all(&["Microsoft.Authorization/roleAssignments/write".to_string()]);
+        assert!(superset.roles.iter().all(|r| r.name != contributor));
+
+        // Wildcards stay within the role's cloud, and counts are of the expansion
+        assert!(!check("storage.objects.get"));
+        assert!(!check("s3:GetObject"));
+        let superset = engine.roles_granting_all(&["storage.objects.get".to_string(), "storage.objects.list".to_string()]);
+        assert!(superset.roles.iter().all(|r| r.name != contributor));
+        assert!(engine.similar_roles("roles/storage.objectViewer", 5).unwrap().iter().all(|r| r.name != contributor));
+        let superset = engine.roles_granting_all(&["Microsoft.Storage/storageAccounts/read".to_string()]);
+        let granting = superset.roles.iter().find(|r| r.name == contributor).unwrap();
+        assert_eq!((granting.permission_count, granting.extra_permissions), (1, 0));
+    }
+
+    #[test]
+    fn deprecated_results_carry_warnings() {
+        let mut engine = SearchEngine::new();
+        engine.index_role(
+            "roles/storage.old".to_string(),
+            "Storage Old".to_string(),
+            "Deprecated: use roles/storage.new instead.".to_string(),
+            "DEPRECATED".to_string(),
+            vec!["storage.legacy.get".to_string(), "storage.objects.get".to_string()],
+        );
+        engine.index_role(
+            "roles/storage.new".to_string(),
+            "Storage New".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec!["storage.objects.get".to_string()],
+        );
+        engine.finalize();
+
+        let options = SearchOptions { include_deprecated: true, ..SearchOptions::default() };
+        let roles = engine.search_roles("roles/storage.old", "exact", &options);
+        assert_eq!(roles[0].warnings[0].code, "deprecated_role");
+        assert_eq!(roles[0].warnings[0].replacement.as_deref(), Some("roles/storage.new"));
+        assert!(engine.search_roles("roles/storage.new", "exact", &options)[0].warnings.is_empty());
+
+        let legacy = engine.search_permissions("storage.legacy.get", "exact", &options);
+        assert_eq!(legacy[0].warnings[0].code, "deprecated_permission");
+        assert_eq!(legacy[0].warnings[0].replacement.as_deref(), Some("roles/storage.new"));
+        // Still granted by a current role
+        assert!(engine.search_permissions("storage.objects.get", "exact", &options)[0].warnings.is_empty());
+
+        assert_eq!(deprecation_replacement("roles/a", "Full access to ServiceBroker resources."), None);
+        assert_eq!(deprecation_replacement("roles/a", "This role is deprecated (see roles/a)."), None);
+    }
+
+    #[test]
+    fn fuzzy_search_accepts_multibyte_queries() {
+        let mut engine = SearchEngine::new();
+        engine.index_role(
+            "roles/storage.admin".to_string(),
+            "Storage Admin".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec!["storage.buckets.get".to_string()],
+        );
+        engine.finalize();
+
+        let options = SearchOptions::default();
+        let roles = engine.search_roles("\u{201c}storage admin\u{201d}", "fuzzy", &options);
+        assert_eq!(roles.first().map(|r| r.name.as_str()), Some("roles/storage.admin"));
+        assert!(engine.search_permissions("存储", "fuzzy", &options).is_empty());
+    }
+
+    fn index_catalog(engine: &mut SearchEngine) {
+        engine.index_role(
+            "roles/storage.admin".to_string(),
+            "Storage Admin".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec!["storage.buckets.get".to_string(), "storage.buckets.delete".to_string()],
+        );
+        engine.index_role(
+            "roles/storage.viewer".to_string(),
+            "Storage Viewer".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec!["storage.buckets.get".to_string()],
+        );
+        engine.finalize();
+    }
+
+    #[test]
+    fn negated_terms_drop_matching_results() {
+        let mut engine = SearchEngine::new();
+        index_catalog(&mut engine);
+        let options = SearchOptions::default();
+
+        for mode in ["exact", "prefix", "fuzzy"] {
+            let roles: Vec<String> = engine.search_roles("storage -admin", mode, &options).into_iter().map(|r| r.name).collect();
+            assert!(!roles.contains(&"roles/storage.admin".to_string()), "{}: {:?}", mode, roles);
+        }
+        let roles: Vec<String> = engine.search_roles("storage -admin", "prefix", &options).into_iter().map(|r| r.name).collect();
+        assert_eq!(roles, vec!["roles/storage.viewer"]);
+        let permissions: Vec<String> = engine
+            .search_permissions("storage.buckets -delete", "prefix", &options)
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(permissions, vec!["storage.buckets.get"]);
+        assert!(engine.search_roles("-admin", "prefix", &options).is_empty());
+    }
+
+    #[test]
+    fn reloading_a_dataset_does_not_duplicate_roles() {
+        let mut engine = SearchEngine::new();
+        index_catalog(&mut engine);
+        index_catalog(&mut engine);
+
+        assert_eq!(engine.stats(), (2, 2));
+        assert_eq!(engine.all_roles().count(), 2);
+        assert_eq!(
+            engine.get_permission("storage.buckets.get").unwrap().granted_by_roles,
+            vec!["roles/storage.admin", "roles/storage.viewer"]
+        );
+        assert_eq!(engine.catalog_stats().roles_by_stage.get("GA"), Some(&2));
+        assert_eq!(engine.catalog_stats().average_permissions_per_role, 1.5);
+    }
+
+    #[test]
+    fn indexing_an_existing_role_replaces_it() {
+        let mut engine = SearchEngine::new();
+        index_catalog(&mut engine);
+
+        let replaced = engine.index_role(
+            "roles/storage.viewer".to_string(),
+            "Storage Object Viewer".to_string(),
+            String::new(),
+            "BETA".to_string(),
+            vec!["storage.objects.get".to_string(), "storage.objects.get".to_string()],
+        );
+        engine.finalize();
+
+        assert_eq!(replaced.map(|r| r.title), Some("Storage Viewer".to_string()));
+        assert_eq!(engine.get_role("roles/storage.viewer").unwrap().stage, "BETA");
+        assert_eq!(engine.get_permission("storage.buckets.get").unwrap().granted_by_roles, vec!["roles/storage.admin"]);
+        assert_eq!(engine.get_permission("storage.objects.get").unwrap().granted_by_roles, vec!["roles/storage.viewer"]);
+        assert!(engine.index_custom_role(
+            "projects/p/roles/reader".to_string(),
+            "Reader".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec![],
+        ).is_none());
+    }
+
+    #[test]
+    fn roles_update_and_remove_incrementally() {
+        let mut engine = SearchEngine::new();
+        for name in ["roles/storage.admin", "roles/storage.viewer"] {
+            engine.index_role(
+                name.to_string(),
+                String::new(),
+                String::new(),
+                "GA".to_string(),
+                vec!["storage.buckets.get".to_string()],
+            );
+        }
+        engine.finalize();
+
+        engine.update_role(
+            "roles/storage.admin".to_string(),
+            "Storage Admin".to_string(),
+            String::new(),
+            "GA".to_string(),
+            vec!["storage.buckets.delete".to_string()],
+        );
+        engine.finalize();
+        assert_eq!(engine.stats(), (2, 2));
+        assert_eq!(engine.get_permission("storage.buckets.get").unwrap().granted_by_roles, vec!["roles/storage.viewer"]);
+        assert_eq!(engine.get_permission("storage.buckets.delete").unwrap().granted_by_roles, vec!["roles/storage.admin"]);
+
+        assert!(engine.remove_role("roles/storage.viewer").is_some());
+        assert!(engine.remove_role("roles/storage.viewer").is_none());
+        engine.finalize();
+        engine.finalize();
+        assert_eq!(engine.all_roles().count(), 1);
+        assert!(engine.get_permission("storage.buckets.get").unwrap().granted_by_roles.is_empty());
+        assert!(engine.similar_roles("roles/storage.viewer", 5).is_none());
+    }
+}
@@ -0,0 +1,30 @@
+//! Approximate heap usage of index structures, for sizing reports
+//!
+//! Each helper counts the heap allocations a value owns (not its inline
+//! size, which its container already counts). Allocator overhead and hash
+//! map control bytes beyond one per slot are ignored, so figures are lower
+//! bounds good for spotting regressions rather than exact totals.
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+/// Heap bytes of a string
+pub(crate) fn string(s: &String) -> usize {
+    s.capacity()
+}
+
+/// Heap bytes of a vector of strings, including each string's own
+pub(crate) fn strings(v: &Vec<String>) -> usize {
+    vec(v) + v.iter().map(string).sum::<usize>()
+}
+
+/// Heap bytes of a vector's buffer
+pub(crate) fn vec<T>(v: &Vec<T>) -> usize {
+    v.capacity() * size_of::<T>()
+}
+
+/// Heap bytes of a hash map's slots, plus `heap` for each entry's own
+/// allocations
+pub(crate) fn map<K, V>(map: &HashMap<K, V>, heap: impl Fn(&K, &V) -> usize) -> usize {
+    map.capacity() * (size_of::<(K, V)>() + 1) + map.iter().map(|(k, v)| heap(k, v)).sum::<usize>()
+}
@@ -0,0 +1,16 @@
+//! Search engine implementation with multiple index types
+
+pub mod bitset;
+pub mod builder;
+pub mod engine;
+mod memory;
+pub mod options;
+pub mod query;
+pub mod ranking;
+pub mod scoring;
+pub mod spelling;
+
+pub use builder::{BuildError, SearchEngineBuilder};
+pub use engine::SearchEngine;
+pub use options::{SearchOptions, SortField, SortOrder, DEFAULT_MIN_SCORE};
+pub use ranking::{Popularity, RankingConfig};
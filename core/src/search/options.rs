@@ -0,0 +1,105 @@
+//! Per-query options controlling ranking, ordering and pagination
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use super::ranking::{Popularity, RankingConfig};
+
+/// Field used to order results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    Score,
+    Name,
+    PermissionCount,
+    Stage,
+}
+
+impl SortField {
+    /// Direction used when the request doesn't specify one
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            SortField::Score | SortField::PermissionCount => SortOrder::Desc,
+            SortField::Name | SortField::Stage => SortOrder::Asc,
+        }
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    /// Apply this direction to an ascending comparison
+    pub fn apply(&self, ordering: Ordering) -> Ordering {
+        match self {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+/// Default minimum n-gram similarity for fuzzy matches
+pub const DEFAULT_MIN_SCORE: f64 = 0.2;
+
+/// Per-query options: fuzzy threshold, ordering, then offset/limit
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub min_score: f64,
+    pub include_deprecated: bool,
+    pub sort: SortField,
+    pub order: Option<SortOrder>,
+    pub limit: usize,
+    pub offset: usize,
+    /// Only results from this cloud ("gcp", "aws" or "azure"), by the roles' `provider`
+    pub provider: Option<String>,
+    /// Relevance weights and boosts applied to match scores
+    pub ranking: RankingConfig,
+    /// Attach a `ScoreExplanation` to each result
+    pub explain: bool,
+    /// Search counts behind `RankingConfig::popularity_boost`
+    pub popularity: Option<Arc<Popularity>>,
+}
+
+impl SearchOptions {
+    /// Effective sort direction
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or_else(|| self.sort.default_order())
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            min_score: DEFAULT_MIN_SCORE,
+            include_deprecated: false,
+            sort: SortField::Score,
+            order: None,
+            limit: 20,
+            offset: 0,
+            provider: None,
+            ranking: RankingConfig::default(),
+            explain: false,
+            popularity: None,
+        }
+    }
+}
+
+/// Rank of a launch stage, most stable first
+pub fn stage_rank(stage: &str) -> u8 {
+    match stage {
+        "GA" => 0,
+        "BETA" => 1,
+        "ALPHA" => 2,
+        "EAP" => 3,
+        "DEPRECATED" => 4,
+        "DISABLED" => 5,
+        _ => 6,
+    }
+}
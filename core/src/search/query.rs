@@ -0,0 +1,155 @@
+//! Query parsing shared by all search modes
+//!
+//! A raw query is split into free text, which is matched according to the
+//! search mode, `"quoted phrases"`, which must appear contiguously in a
+//! matched item's name or title, and `-excluded` terms, which remove matches
+//! containing them.
+
+/// A search query broken into its components
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// Unquoted text, whitespace-collapsed
+    pub text: String,
+    /// Quoted phrases, lowercased and whitespace-collapsed
+    pub phrases: Vec<String>,
+    /// Terms prefixed with '-', lowercased
+    pub excluded: Vec<String>,
+}
+
+impl ParsedQuery {
+    /// Parse a raw query; an unterminated quote runs to the end of the query
+    pub fn parse(raw: &str) -> Self {
+        let mut text = Vec::new();
+        let mut phrases = Vec::new();
+        let mut excluded = Vec::new();
+
+        for (i, segment) in raw.split('"').enumerate() {
+            // Odd segments are inside quotes
+            if i % 2 == 1 {
+                let phrase = collapse_whitespace(&segment.to_lowercase());
+                if !phrase.is_empty() {
+                    phrases.push(phrase);
+                }
+            } else {
+                for word in segment.split_whitespace() {
+                    match word.strip_prefix('-') {
+                        Some(term) if !term.is_empty() => excluded.push(term.to_lowercase()),
+                        Some(_) => {}
+                        None => text.push(word),
+                    }
+                }
+            }
+        }
+
+        ParsedQuery {
+            text: text.join(" "),
+            phrases,
+            excluded,
+        }
+    }
+
+    /// Whether there is nothing to match on (exclusions alone match nothing)
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.phrases.is_empty()
+    }
+
+    /// Whether every phrase appears contiguously in at least one of the fields
+    pub fn matches_phrases(&self, fields: &[&str]) -> bool {
+        if self.phrases.is_empty() {
+            return true;
+        }
+
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|f| collapse_whitespace(&f.to_lowercase()))
+            .collect();
+        self.phrases
+            .iter()
+            .all(|phrase| fields.iter().any(|f| f.contains(phrase.as_str())))
+    }
+
+    /// Whether any excluded term appears in one of the fields
+    pub fn is_excluded(&self, fields: &[&str]) -> bool {
+        self.excluded.iter().any(|term| {
+            fields.iter().any(|f| f.to_lowercase().contains(term.as_str()))
+        })
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_text_phrases_and_exclusions() {
+        let parsed = ParsedQuery::parse(r#"storage  "Object   Viewer" buckets -admin"#);
+        assert_eq!(parsed.text, "storage buckets");
+        assert_eq!(parsed.phrases, vec!["object viewer"]);
+        assert_eq!(parsed.excluded, vec!["admin"]);
+        assert!(!parsed.is_empty());
+
+        assert_eq!(ParsedQuery::parse("  "), ParsedQuery::default());
+        assert!(ParsedQuery::parse(r#""" -admin"#).is_empty());
+    }
+
+    #[test]
+    fn unterminated_quotes_run_to_the_end() {
+        let parsed = ParsedQuery::parse(r#"compute "instance  Admin"#);
+        assert_eq!(parsed.text, "compute");
+        assert_eq!(parsed.phrases, vec!["instance admin"]);
+
+        // A closed phrase followed by an unterminated one
+        let parsed = ParsedQuery::parse(r#""object viewer" and "legacy"#);
+        assert_eq!(parsed.text, "and");
+        assert_eq!(parsed.phrases, vec!["object viewer", "legacy"]);
+    }
+
+    #[test]
+    fn dashes_inside_phrases_are_literal() {
+        let parsed = ParsedQuery::parse(r#""storage -admin" viewer"#);
+        assert_eq!(parsed.phrases, vec!["storage -admin"]);
+        assert!(parsed.excluded.is_empty());
+        assert_eq!(parsed.text, "viewer");
+    }
+
+    #[test]
+    fn phrases_match_contiguously_in_any_field() {
+        let parsed = ParsedQuery::parse(r#""object viewer""#);
+        assert!(parsed.matches_phrases(&["roles/storage.objectViewer", "Storage Object  Viewer"]));
+        assert!(!parsed.matches_phrases(&["roles/storage.objectViewer", "Storage Object Legacy Viewer"]));
+
+        // Every phrase must match, though not necessarily in the same field
+        let parsed = ParsedQuery::parse(r#""storage.objects" "object viewer""#);
+        assert!(parsed.matches_phrases(&["storage.objects.get", "Storage Object Viewer"]));
+        assert!(!parsed.matches_phrases(&["storage.buckets.get", "Storage Object Viewer"]));
+
+        assert!(ParsedQuery::parse("anything").matches_phrases(&[]));
+    }
+
+    #[test]
+    fn exclusions_match_any_field_ignoring_case() {
+        let parsed = ParsedQuery::parse("storage -ADMIN");
+        assert_eq!(parsed.excluded, vec!["admin"]);
+        assert!(parsed.is_excluded(&["roles/storage.admin", "Storage Admin"]));
+        assert!(parsed.is_excluded(&["roles/storage.objectViewer", "Storage Object Admin"]));
+        assert!(!parsed.is_excluded(&["roles/storage.objectViewer", "Storage Object Viewer"]));
+
+        assert!(!ParsedQuery::parse("storage").is_excluded(&["roles/storage.admin"]));
+    }
+
+    #[test]
+    fn bare_dashes_are_ignored() {
+        let parsed = ParsedQuery::parse("storage - admin -");
+        assert_eq!(parsed.text, "storage admin");
+        assert!(parsed.excluded.is_empty());
+
+        // Only a leading dash negates
+        let parsed = ParsedQuery::parse("service-agent");
+        assert_eq!(parsed.text, "service-agent");
+        assert!(parsed.excluded.is_empty());
+    }
+}
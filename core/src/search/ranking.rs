@@ -0,0 +1,288 @@
+//! Tunable relevance weights applied on top of match scores
+//!
+//! The defaults reproduce the fixed scores in `scoring`, so a deployment
+//! only changes ranking by loading a config that says so.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::scoring::{fuzzy_match, ngrams, FieldMatch, MatchField, MatchKind, PREFIX_SCORE, SUBSTRING_SCORE};
+
+/// Weights and boosts shaping result scores
+///
+/// Field weights multiply the score of a match in that field (the best
+/// weighted field wins); a weight of 0 stops the field matching at all.
+/// Boosts then scale the winning score. Exact-mode lookups ignore field
+/// weights, since they only ever match the name.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RankingConfig {
+    /// Weight of role and permission name matches
+    pub name_weight: f64,
+    /// Weight of role title matches
+    pub title_weight: f64,
+    /// Weight of fuzzy-mode substring matches in role descriptions (off by
+    /// default: descriptions are long and match loosely)
+    pub description_weight: f64,
+    /// Fraction added to the score of GA roles (0.1 ranks them 10% higher)
+    pub ga_boost: f64,
+    /// Fraction added when the query equals a whole `.` or `/` separated
+    /// segment of the name, e.g. "storage" in "roles/storage.admin"
+    pub exact_segment_boost: f64,
+    /// How strongly large roles are pushed down: scores are divided by
+    /// `1 + damping * ln(1 + permission_count)`
+    pub permission_count_damping: f64,
+    /// Fraction added for the most searched-for items, scaled down for
+    /// less popular ones (see `Popularity`); 0 turns popularity off
+    pub popularity_boost: f64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        RankingConfig {
+            name_weight: 1.0,
+            title_weight: 1.0,
+            description_weight: 0.0,
+            ga_boost: 0.0,
+            exact_segment_boost: 0.0,
+            permission_count_damping: 0.0,
+            popularity_boost: 0.0,
+        }
+    }
+}
+
+impl RankingConfig {
+    /// Reject negative or non-finite values, naming the offending field
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            ("name_weight", self.name_weight),
+            ("title_weight", self.title_weight),
+            ("description_weight", self.description_weight),
+            ("ga_boost", self.ga_boost),
+            ("exact_segment_boost", self.exact_segment_boost),
+            ("permission_count_damping", self.permission_count_damping),
+            ("popularity_boost", self.popularity_boost),
+        ];
+        match fields.iter().find(|(_, value)| !value.is_finite() || *value < 0.0) {
+            Some((field, value)) => Err(format!("{} must be a non-negative number, got {}", field, value)),
+            None => Ok(()),
+        }
+    }
+
+    /// How a permission matches `query`, if it does
+    pub fn permission_match(&self, query: &MatchQuery, name: &str, name_lower: &str) -> Option<FieldMatch> {
+        if query.text.is_empty() {
+            return Some(FieldMatch::phrase());
+        }
+        match query.mode {
+            "exact" => (name == query.text).then(FieldMatch::exact),
+            "prefix" => self.prefix_match(name_lower.starts_with(&query.lower), false),
+            _ => fuzzy_match(
+                &query.lower,
+                &query.ngrams,
+                &[(MatchField::Name, name_lower, self.name_weight)],
+                query.min_score,
+            ),
+        }
+    }
+
+    /// How a role matches `query` by its lowercase name and title, or its
+    /// description when `description_weight` is set (fuzzy mode only;
+    /// computed lazily as descriptions are only matched by substring)
+    pub fn role_match(
+        &self,
+        query: &MatchQuery,
+        name: &str,
+        name_lower: &str,
+        title_lower: &str,
+        description_lower: impl FnOnce() -> String,
+    ) -> Option<FieldMatch> {
+        if query.text.is_empty() {
+            return Some(FieldMatch::phrase());
+        }
+        match query.mode {
+            "exact" => (name == query.text).then(FieldMatch::exact),
+            "prefix" => self.prefix_match(name_lower.starts_with(&query.lower), title_lower.starts_with(&query.lower)),
+            _ => {
+                let fields = fuzzy_match(
+                    &query.lower,
+                    &query.ngrams,
+                    &[(MatchField::Name, name_lower, self.name_weight), (MatchField::Title, title_lower, self.title_weight)],
+                    query.min_score,
+                );
+                let description = (self.description_weight > 0.0 && description_lower().contains(&query.lower)).then_some(FieldMatch {
+                    field: Some(MatchField::Description),
+                    kind: MatchKind::Substring,
+                    base_score: SUBSTRING_SCORE,
+                    weight: self.description_weight,
+                });
+                FieldMatch::best(fields, description)
+            }
+        }
+    }
+
+    /// Prefix-mode match of an item whose name and/or title starts with the query
+    fn prefix_match(&self, name_matches: bool, title_matches: bool) -> Option<FieldMatch> {
+        [(MatchField::Name, name_matches, self.name_weight), (MatchField::Title, title_matches, self.title_weight)]
+            .into_iter()
+            .filter(|&(_, matches, weight)| matches && weight > 0.0)
+            .map(|(field, _, weight)| FieldMatch { field: Some(field), kind: MatchKind::Prefix, base_score: PREFIX_SCORE, weight })
+            .fold(None, |best, m| FieldMatch::best(best, Some(m)))
+    }
+
+    /// Factor of each boost for an item; `stage` and `permission_count` are
+    /// `None` for permissions
+    fn boosts(
+        &self,
+        name: &str,
+        query_lower: &str,
+        stage: Option<&str>,
+        permission_count: Option<usize>,
+        popularity: Option<&Popularity>,
+    ) -> [AppliedBoost; 4] {
+        let factor = |applies: bool, factor: f64| if applies { factor } else { 1.0 };
+        [
+            AppliedBoost { boost: Boost::GaStage, factor: factor(stage == Some("GA"), 1.0 + self.ga_boost) },
+            AppliedBoost {
+                boost: Boost::ExactSegment,
+                factor: factor(is_exact_segment(name, query_lower), 1.0 + self.exact_segment_boost),
+            },
+            AppliedBoost {
+                boost: Boost::PermissionCountDamping,
+                factor: permission_count.map_or(1.0, |count| 1.0 / (1.0 + self.permission_count_damping * (1.0 + count as f64).ln())),
+            },
+            AppliedBoost {
+                boost: Boost::Popularity,
+                factor: 1.0 + self.popularity_boost * popularity.map_or(0.0, |p| p.get(name)),
+            },
+        ]
+    }
+
+    /// Final score of a match after boosts
+    pub fn score(
+        &self,
+        m: &FieldMatch,
+        name: &str,
+        query_lower: &str,
+        stage: Option<&str>,
+        permission_count: Option<usize>,
+        popularity: Option<&Popularity>,
+    ) -> f64 {
+        self.boosts(name, query_lower, stage, permission_count, popularity)
+            .iter()
+            .fold(m.score(), |score, b| score * b.factor)
+    }
+
+    /// Breakdown of `score` for the same arguments
+    pub fn explain(
+        &self,
+        m: &FieldMatch,
+        name: &str,
+        query_lower: &str,
+        stage: Option<&str>,
+        permission_count: Option<usize>,
+        popularity: Option<&Popularity>,
+    ) -> ScoreExplanation {
+        ScoreExplanation {
+            field: m.field,
+            kind: m.kind,
+            base_score: m.base_score,
+            field_weight: m.weight,
+            ngram_similarity: (m.kind == MatchKind::Ngram).then_some(m.base_score),
+            boosts: self
+                .boosts(name, query_lower, stage, permission_count, popularity)
+                .into_iter()
+                .filter(|b| b.factor != 1.0)
+                .collect(),
+            score: self.score(m, name, query_lower, stage, permission_count, popularity),
+        }
+    }
+}
+
+/// How often roles and permissions are searched for, as scores from 0 (never)
+/// to 1 (the most searched-for item), on a log scale so a handful of very
+/// common queries don't flatten everything else
+#[derive(Debug, Clone, Default)]
+pub struct Popularity {
+    scores: HashMap<String, f64>,
+}
+
+impl Popularity {
+    /// Normalize per-item search counts
+    pub fn from_counts(counts: HashMap<String, usize>) -> Self {
+        let max = counts.values().copied().max().unwrap_or(0);
+        if max == 0 {
+            return Popularity::default();
+        }
+        let scale = (1.0 + max as f64).ln();
+        let scores = counts
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| (name, (1.0 + count as f64).ln() / scale))
+            .collect();
+        Popularity { scores }
+    }
+
+    /// Score of an item by name, 0 when never searched for
+    pub fn get(&self, name: &str) -> f64 {
+        self.scores.get(name).copied().unwrap_or(0.0)
+    }
+}
+
+/// A query's free text, prepared once for matching every item
+pub struct MatchQuery<'a> {
+    pub text: &'a str,
+    pub lower: String,
+    pub ngrams: Vec<String>,
+    pub mode: &'a str,
+    pub min_score: f64,
+}
+
+impl<'a> MatchQuery<'a> {
+    pub fn new(text: &'a str, mode: &'a str, min_score: f64) -> Self {
+        let lower = text.to_lowercase();
+        MatchQuery { ngrams: ngrams(&lower, 3), lower, text, mode, min_score }
+    }
+}
+
+/// A ranking adjustment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Boost {
+    GaStage,
+    ExactSegment,
+    PermissionCountDamping,
+    Popularity,
+}
+
+/// A boost and the factor it multiplied the score by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AppliedBoost {
+    pub boost: Boost,
+    pub factor: f64,
+}
+
+/// How a result's score was produced (`?explain=true`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Field that matched; `None` when only quoted phrases matched
+    pub field: Option<MatchField>,
+    pub kind: MatchKind,
+    /// Score of the match kind before weighting
+    pub base_score: f64,
+    pub field_weight: f64,
+    /// Trigram overlap with the query, for n-gram matches
+    pub ngram_similarity: Option<f64>,
+    /// Boosts that changed the score, in the order applied
+    pub boosts: Vec<AppliedBoost>,
+    /// `base_score * field_weight` times every boost factor
+    pub score: f64,
+}
+
+/// Whether lowercase `query` is a whole `.` or `/` separated segment of `name`
+pub fn is_exact_segment(name: &str, query_lower: &str) -> bool {
+    !query_lower.is_empty()
+        && name
+            .split(['.', '/'])
+            .any(|segment| segment.eq_ignore_ascii_case(query_lower))
+}
@@ -0,0 +1,138 @@
+//! Scores shared by every search implementation so results rank identically
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Score of an exact name match
+pub const EXACT_SCORE: f64 = 1.0;
+
+/// Score given to items matched only by quoted phrases
+pub const PHRASE_SCORE: f64 = 0.95;
+
+/// Score of a name (or role title) prefix match
+pub const PREFIX_SCORE: f64 = 0.9;
+
+/// Score of a case-insensitive substring match in fuzzy mode
+pub const SUBSTRING_SCORE: f64 = 0.85;
+
+/// Extract character n-grams from a string
+///
+/// Lengths are measured in chars, not bytes, so multibyte input such as
+/// smart quotes or non-Latin text yields whole-character n-grams.
+pub fn ngrams(text: &str, n: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() < n {
+        return vec![text.to_string()];
+    }
+
+    chars
+        .windows(n)
+        .map(|window| window.iter().collect::<String>())
+        .collect()
+}
+
+/// Jaccard similarity between two n-gram sets
+pub fn similarity(set1: &[String], set2: &[String]) -> f64 {
+    if set1.is_empty() && set2.is_empty() {
+        return 1.0;
+    }
+
+    let set1_unique: HashSet<_> = set1.iter().collect();
+    let set2_unique: HashSet<_> = set2.iter().collect();
+
+    let intersection = set1_unique.intersection(&set2_unique).count();
+    let union = set1_unique.union(&set2_unique).count();
+
+    if union == 0 {
+        0.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
+
+/// Field of a role or permission a query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    Name,
+    Title,
+    Description,
+}
+
+/// How a query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Exact,
+    /// Only quoted phrases, no free text
+    Phrase,
+    Prefix,
+    Substring,
+    /// Trigram similarity
+    Ngram,
+}
+
+/// The best-scoring field match of one item, before ranking boosts
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FieldMatch {
+    /// `None` for phrase-only matches, which aren't tied to one field
+    pub field: Option<MatchField>,
+    pub kind: MatchKind,
+    /// Score of the match kind, or the similarity for n-gram matches
+    pub base_score: f64,
+    /// Ranking weight of the field
+    pub weight: f64,
+}
+
+impl FieldMatch {
+    /// An exact name match
+    pub fn exact() -> Self {
+        FieldMatch { field: Some(MatchField::Name), kind: MatchKind::Exact, base_score: EXACT_SCORE, weight: 1.0 }
+    }
+
+    /// A match on quoted phrases alone
+    pub fn phrase() -> Self {
+        FieldMatch { field: None, kind: MatchKind::Phrase, base_score: PHRASE_SCORE, weight: 1.0 }
+    }
+
+    /// Weighted score
+    pub fn score(&self) -> f64 {
+        self.base_score * self.weight
+    }
+
+    /// The higher-scoring of two optional matches, `self` on ties
+    pub fn best(first: Option<Self>, second: Option<Self>) -> Option<Self> {
+        match (first, second) {
+            (Some(a), Some(b)) if b.score() > a.score() => Some(b),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Fuzzy-mode match of lowercase `candidates` (a name, and a role's title)
+/// against a lowercase query and its trigrams: SUBSTRING_SCORE when any
+/// contains the query, else the best n-gram similarity if it reaches
+/// `min_score`. Each candidate's score is scaled by its weight, the best
+/// weighted candidate wins and zero-weight candidates never match.
+pub fn fuzzy_match(
+    query_lower: &str,
+    query_ngrams: &[String],
+    candidates: &[(MatchField, &str, f64)],
+    min_score: f64,
+) -> Option<FieldMatch> {
+    let candidates = candidates.iter().filter(|(_, _, weight)| *weight > 0.0);
+    let containing = candidates
+        .clone()
+        .filter(|(_, c, _)| c.contains(query_lower))
+        .map(|&(field, _, weight)| FieldMatch { field: Some(field), kind: MatchKind::Substring, base_score: SUBSTRING_SCORE, weight })
+        .fold(None, |best, m| FieldMatch::best(best, Some(m)));
+    if containing.is_some() {
+        return containing;
+    }
+    candidates
+        .filter_map(|&(field, c, weight)| {
+            let score = similarity(query_ngrams, &ngrams(c, 3));
+            (score >= min_score).then_some(FieldMatch { field: Some(field), kind: MatchKind::Ngram, base_score: score, weight })
+        })
+        .fold(None, |best, m| FieldMatch::best(best, Some(m)))
+}
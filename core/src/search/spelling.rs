@@ -0,0 +1,253 @@
+//! SymSpell-style spelling correction over the indexed vocabulary
+//!
+//! Every vocabulary token is expanded into the set of strings reachable by
+//! deleting up to `MAX_EDIT_DISTANCE` characters from its prefix. A misspelled
+//! token is expanded the same way; shared deletes yield candidates, which are
+//! then verified with a real edit distance.
+
+use std::collections::{HashMap, HashSet};
+
+use super::memory;
+
+/// Maximum edit distance considered for a correction
+const MAX_EDIT_DISTANCE: usize = 2;
+
+/// Only the first characters of a word generate deletes (SymSpell prefix optimization)
+const PREFIX_LENGTH: usize = 7;
+
+/// Tokens shorter than this are neither indexed nor corrected
+const MIN_TOKEN_LENGTH: usize = 3;
+
+/// Deletion dictionary built from permission and role tokens
+#[derive(Debug, Clone, Default)]
+pub struct SpellingIndex {
+    words: Vec<String>,
+    frequencies: Vec<u32>,
+    word_ids: HashMap<String, u32>,
+    deletes: HashMap<String, Vec<u32>>,
+}
+
+impl SpellingIndex {
+    /// Build the dictionary from raw text such as permission names and role titles
+    pub fn build<'a>(texts: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut index = SpellingIndex::default();
+
+        for text in texts {
+            for token in tokenize(text) {
+                match index.word_ids.get(&token) {
+                    Some(&id) => index.frequencies[id as usize] += 1,
+                    None => {
+                        let id = index.words.len() as u32;
+                        index.word_ids.insert(token.clone(), id);
+                        index.words.push(token);
+                        index.frequencies.push(1);
+                    }
+                }
+            }
+        }
+
+        for (id, word) in index.words.iter().enumerate() {
+            for delete in deletes(&prefix(word)) {
+                index.deletes.entry(delete).or_default().push(id as u32);
+            }
+        }
+
+        index
+    }
+
+    /// Approximate heap bytes held by the dictionary
+    pub fn heap_bytes(&self) -> usize {
+        memory::strings(&self.words)
+            + memory::vec(&self.frequencies)
+            + memory::map(&self.word_ids, |word, _| memory::string(word))
+            + memory::map(&self.deletes, |delete, ids| memory::string(delete) + memory::vec(ids))
+    }
+
+    /// Whether a (lowercase) token appears in the vocabulary
+    pub fn contains(&self, token: &str) -> bool {
+        self.word_ids.contains_key(token)
+    }
+
+    /// Closest vocabulary word to `token`, preferring smaller distance then higher frequency
+    pub fn correct(&self, token: &str) -> Option<&str> {
+        let token = token.to_lowercase();
+        if token.chars().count() < MIN_TOKEN_LENGTH || self.contains(&token) {
+            return None;
+        }
+
+        let candidates: HashSet<u32> = deletes(&prefix(&token))
+            .iter()
+            .filter_map(|d| self.deletes.get(d))
+            .flatten()
+            .copied()
+            .collect();
+
+        candidates
+            .into_iter()
+            .filter_map(|id| {
+                let word = &self.words[id as usize];
+                let distance = edit_distance(&token, word);
+                (distance <= MAX_EDIT_DISTANCE).then_some((distance, id))
+            })
+            .min_by(|a, b| {
+                a.0.cmp(&b.0)
+                    .then_with(|| self.frequencies[b.1 as usize].cmp(&self.frequencies[a.1 as usize]))
+                    .then_with(|| self.words[a.1 as usize].cmp(&self.words[b.1 as usize]))
+            })
+            .map(|(_, id)| self.words[id as usize].as_str())
+    }
+
+    /// Correct each unknown token in a query, keeping separators intact
+    ///
+    /// Returns `None` when nothing was changed.
+    pub fn correct_query(&self, query: &str) -> Option<String> {
+        let mut corrected = String::with_capacity(query.len());
+        let mut changed = false;
+        let mut token = String::new();
+
+        let mut flush = |token: &mut String, corrected: &mut String| {
+            if token.is_empty() {
+                return;
+            }
+            match self.correct(token) {
+                Some(word) => {
+                    corrected.push_str(word);
+                    changed = true;
+                }
+                None => corrected.push_str(token),
+            }
+            token.clear();
+        };
+
+        for c in query.chars() {
+            if c.is_alphanumeric() {
+                token.push(c);
+            } else {
+                flush(&mut token, &mut corrected);
+                corrected.push(c);
+            }
+        }
+        flush(&mut token, &mut corrected);
+
+        changed.then_some(corrected)
+    }
+}
+
+/// Split text into lowercase alphanumeric tokens long enough to index
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= MIN_TOKEN_LENGTH)
+        .map(|t| t.to_lowercase())
+}
+
+fn prefix(word: &str) -> String {
+    word.chars().take(PREFIX_LENGTH).collect()
+}
+
+/// All strings obtained by deleting up to MAX_EDIT_DISTANCE characters (including the word itself)
+fn deletes(word: &str) -> HashSet<String> {
+    let mut result = HashSet::new();
+    result.insert(word.to_string());
+
+    let mut frontier = vec![word.to_string()];
+    for _ in 0..MAX_EDIT_DISTANCE {
+        let mut next = Vec::new();
+        for w in &frontier {
+            let chars: Vec<char> = w.chars().collect();
+            for i in 0..chars.len() {
+                let delete: String = chars[..i].iter().chain(&chars[i + 1..]).collect();
+                if result.insert(delete.clone()) {
+                    next.push(delete);
+                }
+            }
+        }
+        frontier = next;
+    }
+
+    result
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletes_reach_two_edits() {
+        let mut expected: Vec<&str> = vec!["abc", "ab", "ac", "bc", "a", "b", "c"];
+        expected.sort();
+        let mut found: Vec<String> = deletes("abc").into_iter().collect();
+        found.sort();
+        assert_eq!(found, expected);
+        assert_eq!(prefix("storage.buckets"), "storage");
+    }
+
+    #[test]
+    fn dictionary_indexes_long_enough_tokens() {
+        let index = SpellingIndex::build(["storage.buckets.get", "Storage Admin", "iam.roles.get"]);
+        for word in ["storage", "buckets", "get", "admin", "iam", "roles"] {
+            assert!(index.contains(word), "{}", word);
+        }
+        assert_eq!(index.frequencies[index.word_ids["storage"] as usize], 2);
+        assert_eq!(index.frequencies[index.word_ids["get"] as usize], 2);
+
+        // Every word is reachable from its own deletes
+        let id = index.word_ids["buckets"];
+        assert!(index.deletes["bucket"].contains(&id));
+        assert!(index.deletes["bucke"].contains(&id));
+        assert!(!index.deletes.contains_key("buck"));
+
+        assert!(!SpellingIndex::build(["a.b cd"]).contains("cd"));
+    }
+
+    #[test]
+    fn corrections_prefer_distance_then_frequency() {
+        let index = SpellingIndex::build(["storage.buckets.get", "bucket bucket socket", "computeinstances"]);
+        assert_eq!(index.correct("strage"), Some("storage"));
+        assert_eq!(index.correct("Storgae"), Some("storage"));
+        assert_eq!(index.correct("bocket"), Some("bucket"));
+        // Past the prefix, differences are found by the edit distance
+        assert_eq!(index.correct("computeinstancez"), Some("computeinstances"));
+
+        assert_eq!(index.correct("storage"), None);
+        assert_eq!(index.correct("bk"), None);
+        assert_eq!(index.correct("networking"), None);
+        assert_eq!(edit_distance("ocmpute", "compute"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn query_correction_keeps_separators() {
+        let index = SpellingIndex::build(["storage.buckets.get", "Storage Object Viewer"]);
+        assert_eq!(index.correct_query("strage.bukets get").as_deref(), Some("storage.buckets get"));
+        assert_eq!(index.correct_query("\"objetc viewer\" -admn").as_deref(), Some("\"object viewer\" -admn"));
+        assert_eq!(index.correct_query("storage viewer"), None);
+    }
+}
@@ -0,0 +1,570 @@
+//! The prebuilt index split into per-service shards behind a routing table
+//!
+//! Every service's permissions, and the roles granting them (narrowed to
+//! those permissions), form one `PrebuiltIndex` shard. The routing table
+//! (`ShardRoutes`) holds what a query needs before it touches a shard:
+//! the services, every role's metadata with the shards its permissions
+//! are in, and the few permissions whose name doesn't start with their
+//! service. Role search runs on the table alone; a permission query reads
+//! only the shards its text can match (`ShardRoutes::shards_for_query`),
+//! through a `ShardSource` that decides where shards live and for how
+//! long, so a reader holds the table and the shards in use rather than
+//! the whole catalog.
+
+use rkyv::rancor::Source;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+use std::ops::{Deref, Range};
+
+use crate::data::{IamDataFile, PermissionData, RoleData};
+use crate::index::{
+    aligned, permission_service, search_role_page, ArchivedPrebuiltIndex, IndexError, PrebuiltIndex, SearchPage,
+    SearchableRole, SAMPLE_PERMISSIONS,
+};
+use crate::search::engine::{PermissionSearchResult, RoleSearchResult};
+use crate::search::query::ParsedQuery;
+use crate::search::{SearchOptions, SortField};
+
+/// A shard, in `ShardRoutes::services` order
+#[derive(Debug, Clone, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct ServiceRoute {
+    pub service: String,
+    /// Provider of the service's first permission
+    pub provider: String,
+    pub permissions: u32,
+}
+
+/// How many of a role's permissions one shard holds
+#[derive(Debug, Clone, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct RoleShard {
+    pub shard: u32,
+    pub permissions: u32,
+}
+
+/// A role's metadata, and the shards its permissions are in
+#[derive(Debug, Clone, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct RoleRoute {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub stage: String,
+    pub custom: bool,
+    pub provider: String,
+    pub permission_count: u32,
+    pub sample_permissions: Vec<String>,
+    /// In service order, which is the order `ShardedView::role_permissions`
+    /// lists the role's permissions in
+    pub shards: Vec<RoleShard>,
+}
+
+/// A permission whose lowercase name doesn't start with its service's, so
+/// routing by name alone would miss its shard
+#[derive(Debug, Clone, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct StrayRoute {
+    pub name_lower: String,
+    pub shard: u32,
+}
+
+/// Routing table of a sharded index
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize)]
+pub struct ShardRoutes {
+    /// Sorted by service, so a shard's number is its position here
+    pub services: Vec<ServiceRoute>,
+    /// Every role, in dataset order
+    pub roles: Vec<RoleRoute>,
+    pub strays: Vec<StrayRoute>,
+
+    // Lowercase names for case-insensitive search
+    pub role_names_lower: Vec<String>,
+    pub role_titles_lower: Vec<String>,
+
+    /// Dataset timestamp (`metadata.last_updated`) the index was built from
+    pub last_updated: Option<String>,
+}
+
+/// A routing table and its shards, as built from a dataset
+#[derive(Debug, Clone, Default)]
+pub struct ShardedIndex {
+    pub routes: ShardRoutes,
+    /// One per `routes.services` entry, in the same order
+    pub shards: Vec<PrebuiltIndex>,
+}
+
+/// Roles and scraped permissions of one service, while building
+#[derive(Default)]
+struct ShardData {
+    roles: Vec<RoleData>,
+    permissions: Vec<PermissionData>,
+}
+
+impl ShardedIndex {
+    /// Shard a parsed iam-data.json by the services `PrebuiltIndex::build`
+    /// files its permissions under
+    pub fn build(data: &IamDataFile) -> Self {
+        let scraped: HashMap<&str, &PermissionData> = data.permissions.iter().map(|p| (p.name.as_str(), p)).collect();
+        let mut services: BTreeMap<&str, ShardData> = BTreeMap::new();
+        for permission in &data.permissions {
+            let service = permission_service(&permission.name, Some(permission));
+            services.entry(service).or_default().permissions.push(permission.clone());
+        }
+
+        // Each role's permissions by service, keeping their order
+        let mut role_services: Vec<Vec<(&str, u32)>> = Vec::with_capacity(data.roles.len());
+        for role in &data.roles {
+            let mut by_service: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+            for name in &role.included_permissions {
+                let service = permission_service(name, scraped.get(name.as_str()).copied());
+                by_service.entry(service).or_default().push(name.clone());
+            }
+            role_services.push(by_service.iter().map(|(service, names)| (*service, names.len() as u32)).collect());
+            for (service, included_permissions) in by_service {
+                services.entry(service).or_default().roles.push(RoleData {
+                    name: role.name.clone(),
+                    title: role.title.clone(),
+                    description: role.description.clone(),
+                    stage: role.stage.clone(),
+                    included_permissions,
+                    excluded_permissions: role.excluded_permissions.clone(),
+                    custom: role.custom,
+                    provider: role.provider.clone(),
+                });
+            }
+        }
+        // Services only the permission list names have nothing to search
+        services.retain(|_, shard| !shard.roles.is_empty());
+
+        let positions: HashMap<&str, u32> = services.keys().enumerate().map(|(idx, s)| (*s, idx as u32)).collect();
+        let mut routes = ShardRoutes { last_updated: data.metadata.last_updated.clone(), ..ShardRoutes::default() };
+        let mut shards = Vec::with_capacity(services.len());
+        for (position, (service, shard)) in services.into_iter().enumerate() {
+            let index = PrebuiltIndex::build(&IamDataFile {
+                roles: shard.roles,
+                permissions: shard.permissions,
+                metadata: data.metadata.clone(),
+                deleted_roles: vec![],
+            });
+            let service_lower = service.to_lowercase();
+            routes.strays.extend(
+                index
+                    .permission_names_lower
+                    .iter()
+                    .filter(|name| !name.starts_with(&service_lower))
+                    .map(|name| StrayRoute { name_lower: name.clone(), shard: position as u32 }),
+            );
+            routes.services.push(ServiceRoute {
+                service: service.to_string(),
+                provider: index.permissions.first().map(|p| p.provider.clone()).unwrap_or_default(),
+                permissions: index.permissions.len() as u32,
+            });
+            shards.push(index);
+        }
+
+        for (role, held) in data.roles.iter().zip(role_services) {
+            routes.roles.push(RoleRoute {
+                name: role.name.clone(),
+                title: role.title.clone(),
+                description: role.description.clone(),
+                stage: role.stage.clone(),
+                custom: role.custom,
+                provider: role.provider.clone(),
+                permission_count: role.included_permissions.len() as u32,
+                sample_permissions: role.included_permissions.iter().take(SAMPLE_PERMISSIONS).cloned().collect(),
+                shards: held
+                    .into_iter()
+                    .map(|(service, permissions)| RoleShard { shard: positions[service], permissions })
+                    .collect(),
+            });
+            routes.role_names_lower.push(role.name.to_lowercase());
+            routes.role_titles_lower.push(role.title.to_lowercase());
+        }
+
+        ShardedIndex { routes, shards }
+    }
+}
+
+impl ShardRoutes {
+    /// Encode the table as an rkyv archive
+    pub fn to_bytes(&self) -> Result<Vec<u8>, IndexError> {
+        rkyv::to_bytes::<IndexError>(self).map(AlignedVec::into_vec)
+    }
+
+    /// Validate an archive produced by `to_bytes` and view it in place. The
+    /// bytes must be 16-byte aligned; `RoutesBuf` copies unaligned input.
+    pub fn access(bytes: &[u8]) -> Result<&ArchivedShardRoutes, IndexError> {
+        rkyv::access::<ArchivedShardRoutes, IndexError>(bytes)
+    }
+}
+
+/// Routing table bytes copied to an aligned buffer and validated once
+pub struct RoutesBuf {
+    bytes: AlignedVec,
+}
+
+impl RoutesBuf {
+    pub fn new(bytes: &[u8]) -> Result<Self, IndexError> {
+        let bytes = aligned(bytes);
+        ShardRoutes::access(&bytes)?;
+        Ok(RoutesBuf { bytes })
+    }
+
+    /// Stream table bytes straight into the aligned buffer
+    pub fn read(mut reader: impl Read) -> Result<Self, IndexError> {
+        let mut bytes = AlignedVec::new();
+        io::copy(&mut reader, &mut bytes).map_err(IndexError::new)?;
+        ShardRoutes::access(&bytes)?;
+        Ok(RoutesBuf { bytes })
+    }
+
+    pub fn routes(&self) -> &ArchivedShardRoutes {
+        // SAFETY: `new` and `read` validated these bytes as an
+        // ArchivedShardRoutes and they are never modified afterwards
+        unsafe { rkyv::access_unchecked::<ArchivedShardRoutes>(&self.bytes) }
+    }
+}
+
+impl SearchableRole for ArchivedRoleRoute {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn stage(&self) -> &str {
+        &self.stage
+    }
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+    fn is_custom(&self) -> bool {
+        self.custom
+    }
+    fn permission_count(&self) -> usize {
+        self.permission_count.to_native() as usize
+    }
+    fn sample_permissions(&self) -> Vec<String> {
+        self.sample_permissions.iter().map(|p| p.to_string()).collect()
+    }
+}
+
+impl ArchivedRoleRoute {
+    pub fn is_deprecated(&self) -> bool {
+        self.stage == "DEPRECATED"
+    }
+}
+
+impl ArchivedShardRoutes {
+    /// The shard of a service, by exact name
+    pub fn service(&self, service: &str) -> Option<usize> {
+        self.services.binary_search_by(|s| s.service.as_str().cmp(service)).ok()
+    }
+
+    /// The shard of a service, by name ignoring case
+    pub fn service_ignore_case(&self, service: &str) -> Option<usize> {
+        self.service(service).or_else(|| {
+            let lower = service.to_lowercase();
+            self.services.iter().position(|s| s.service.to_lowercase() == lower)
+        })
+    }
+
+    /// Look up a role by exact name
+    pub fn role(&self, name: &str) -> Option<&ArchivedRoleRoute> {
+        self.roles.iter().find(|r| r.name == name)
+    }
+
+    /// Look up a role by name, ignoring case
+    pub fn role_ignore_case(&self, name: &str) -> Option<&ArchivedRoleRoute> {
+        self.role(name).or_else(|| {
+            let lower = name.to_lowercase();
+            self.role_names_lower
+                .iter()
+                .position(|n| *n == lower)
+                .map(|idx| &self.roles[idx])
+        })
+    }
+
+    /// Predefined roles scoped to a service (`roles/{service}.*`), in name order
+    pub fn service_roles(&self, service: &str) -> Vec<&ArchivedRoleRoute> {
+        let prefix = format!("roles/{}.", service);
+        let mut roles: Vec<&ArchivedRoleRoute> = self
+            .roles
+            .iter()
+            .filter(|r| !r.custom && r.name.starts_with(&prefix))
+            .collect();
+        roles.sort_by(|a, b| a.name.cmp(&b.name));
+        roles
+    }
+
+    /// Permissions across all shards
+    pub fn permission_count(&self) -> usize {
+        self.services.iter().map(|s| s.permissions.to_native() as usize).sum()
+    }
+
+    /// Shards that could hold the permission `name`, ignoring case
+    pub fn shards_for_permission(&self, name: &str) -> Vec<usize> {
+        let lower = name.to_lowercase();
+        self.routed(|service| lower.starts_with(service), |stray| stray == lower)
+    }
+
+    /// Shards that could hold permissions whose lowercase name starts with
+    /// `prefix`'s
+    pub fn shards_for_prefix(&self, prefix: &str) -> Vec<usize> {
+        let lower = prefix.to_lowercase();
+        self.routed(
+            |service| service.starts_with(&lower) || lower.starts_with(service),
+            |stray| stray.starts_with(&lower),
+        )
+    }
+
+    /// Shards a permission search could match in: those its text routes to
+    /// in exact and prefix mode, every shard for fuzzy or phrase-only
+    /// queries
+    pub fn shards_for_query(&self, query: &str, mode: &str) -> Vec<usize> {
+        let parsed = ParsedQuery::parse(query);
+        match mode {
+            _ if parsed.text.is_empty() => (0..self.services.len()).collect(),
+            "exact" => self.shards_for_permission(&parsed.text),
+            "prefix" => self.shards_for_prefix(&parsed.text),
+            _ => (0..self.services.len()).collect(),
+        }
+    }
+
+    fn routed(&self, service: impl Fn(&str) -> bool, stray: impl Fn(&str) -> bool) -> Vec<usize> {
+        let mut shards: Vec<usize> = (0..self.services.len())
+            .filter(|&idx| service(&self.services[idx].service.to_lowercase()))
+            .chain(self.strays.iter().filter(|s| stray(&s.name_lower)).map(|s| s.shard.to_native() as usize))
+            .collect();
+        shards.sort_unstable();
+        shards.dedup();
+        shards
+    }
+
+    /// Search roles, as `ArchivedPrebuiltIndex::search_roles` would
+    pub fn search_roles(&self, query: &str, mode: &str, options: &SearchOptions) -> Vec<RoleSearchResult> {
+        self.search_roles_page(query, mode, options).items
+    }
+
+    /// `search_roles`, with the number of matches before pagination
+    pub fn search_roles_page(&self, query: &str, mode: &str, options: &SearchOptions) -> SearchPage<RoleSearchResult> {
+        search_role_page(&self.roles, &self.role_names_lower, &self.role_titles_lower, query, mode, options)
+    }
+}
+
+/// Where a `ShardedView` reads shards from
+pub trait ShardSource {
+    type Shard: Deref<Target = ArchivedPrebuiltIndex>;
+    type Error;
+
+    /// The shard at `position` in `ShardRoutes::services`
+    fn shard(&self, position: usize) -> Result<Self::Shard, Self::Error>;
+}
+
+/// A routing table and the source of its shards, answering the queries
+/// that need permissions. Shards are requested one at a time and dropped
+/// once read, so a source that doesn't cache holds at most one.
+pub struct ShardedView<'a, S> {
+    pub routes: &'a ArchivedShardRoutes,
+    pub source: S,
+}
+
+impl<'a, S: ShardSource> ShardedView<'a, S> {
+    pub fn new(routes: &'a ArchivedShardRoutes, source: S) -> Self {
+        ShardedView { routes, source }
+    }
+
+    /// The shard holding a permission, by exact name
+    pub fn permission_shard(&self, name: &str) -> Result<Option<S::Shard>, S::Error> {
+        self.find_shard(name, |shard| shard.permission(name).is_some())
+    }
+
+    /// The shard holding a permission, by name ignoring case
+    pub fn permission_shard_ignore_case(&self, name: &str) -> Result<Option<S::Shard>, S::Error> {
+        self.find_shard(name, |shard| shard.permission_ignore_case(name).is_some())
+    }
+
+    fn find_shard(&self, name: &str, holds: impl Fn(&ArchivedPrebuiltIndex) -> bool) -> Result<Option<S::Shard>, S::Error> {
+        for position in self.routes.shards_for_permission(name) {
+            let shard = self.source.shard(position)?;
+            if holds(&shard) {
+                return Ok(Some(shard));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Search permissions, as `ArchivedPrebuiltIndex::search_permissions`
+    /// would over the whole catalog
+    pub fn search_permissions(&self, query: &str, mode: &str, options: &SearchOptions) -> Result<Vec<PermissionSearchResult>, S::Error> {
+        Ok(self.search_permissions_page(query, mode, options)?.items)
+    }
+
+    /// `search_permissions`, with the number of matches before pagination
+    pub fn search_permissions_page(
+        &self,
+        query: &str,
+        mode: &str,
+        options: &SearchOptions,
+    ) -> Result<SearchPage<PermissionSearchResult>, S::Error> {
+        self.search_permissions_in(&self.routes.shards_for_query(query, mode), query, mode, options)
+    }
+
+    /// `search_permissions_page` over only these shards
+    ///
+    /// Each shard returns its best `offset + limit` matches, which are
+    /// merged in the order a single index would rank them.
+    pub fn search_permissions_in(
+        &self,
+        shards: &[usize],
+        query: &str,
+        mode: &str,
+        options: &SearchOptions,
+    ) -> Result<SearchPage<PermissionSearchResult>, S::Error> {
+        let wanted = options.offset.saturating_add(options.limit);
+        let per_shard = SearchOptions { offset: 0, limit: wanted, ..options.clone() };
+        let order = options.order();
+        let compare = |a: &PermissionSearchResult, b: &PermissionSearchResult| {
+            let ordering = match options.sort {
+                SortField::Name => a.name.cmp(&b.name),
+                _ => a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal),
+            };
+            order.apply(ordering).then_with(|| a.name.cmp(&b.name))
+        };
+
+        let mut items: Vec<PermissionSearchResult> = Vec::new();
+        let mut total = 0;
+        for &position in shards {
+            let page = self.source.shard(position)?.search_permissions_page(query, mode, &per_shard);
+            total += page.total;
+            items.extend(page.items);
+            items.sort_by(compare);
+            items.truncate(wanted);
+        }
+        let items = items.into_iter().skip(options.offset).collect();
+        Ok(SearchPage { items, total })
+    }
+
+    /// The permissions of `role` within `range` of its list, reading only
+    /// the shards the range spans
+    pub fn role_permissions(&self, role: &ArchivedRoleRoute, range: Range<usize>) -> Result<Vec<String>, S::Error> {
+        let mut permissions = Vec::new();
+        let mut start = 0;
+        for held in role.shards.iter() {
+            let count = held.permissions.to_native() as usize;
+            let (from, to) = (range.start.max(start), range.end.min(start + count));
+            if from < to {
+                let shard = self.source.shard(held.shard.to_native() as usize)?;
+                if let Some(narrowed) = shard.role(&role.name) {
+                    permissions.extend(narrowed.included_permissions.iter().skip(from - start).take(to - from).map(|p| p.to_string()));
+                }
+            }
+            start += count;
+        }
+        Ok(permissions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexBuf;
+
+    /// Shards of a test index, validated and held in memory
+    struct TestShards(Vec<IndexBuf>);
+
+    impl<'a> ShardSource for &'a TestShards {
+        type Shard = &'a ArchivedPrebuiltIndex;
+        type Error = std::convert::Infallible;
+
+        fn shard(&self, position: usize) -> Result<Self::Shard, Self::Error> {
+            Ok(self.0[position].index())
+        }
+    }
+
+    fn json<T: Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    fn sharded_dataset() -> IamDataFile {
+        serde_json::from_value(serde_json::json!({
+            "roles": [
+                {"name": "roles/editor", "title": "Editor", "description": "", "stage": "GA",
+                 "included_permissions": ["compute.instances.get", "storage.buckets.get", "storage.objects.get", "storagetransfer.jobs.get"]},
+                {"name": "roles/storage.objectViewer", "title": "Storage Object Viewer", "description": "", "stage": "GA",
+                 "included_permissions": ["storage.objects.get", "storage.objects.list"]},
+                {"name": "roles/storage.legacyReader", "title": "Storage Legacy Reader", "description": "Use roles/storage.objectViewer",
+                 "stage": "DEPRECATED", "included_permissions": ["storage.buckets.get", "storage.objects.get"]},
+                {"name": "ReadOnlyAccess", "title": "Read Only", "description": "", "stage": "GA", "provider": "aws",
+                 "included_permissions": ["s3:GetObject"]},
+            ],
+            // Filed under a service its name doesn't start with
+            "permissions": [{"name": "s3:GetObject", "service": "amazons3", "resource": "object", "action": "GetObject"}],
+            "metadata": {"total_roles": 4, "total_permissions": 7},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn sharded_search_matches_the_full_index() {
+        let data = sharded_dataset();
+        let full = IndexBuf::new(&PrebuiltIndex::build(&data).to_bytes().unwrap()).unwrap();
+        let sharded = ShardedIndex::build(&data);
+        let routes = RoutesBuf::new(&sharded.routes.to_bytes().unwrap()).unwrap();
+        let shards = TestShards(
+            sharded.shards.iter().map(|s| IndexBuf::new(&s.to_bytes().unwrap()).unwrap()).collect(),
+        );
+        let view = ShardedView::new(routes.routes(), &shards);
+
+        let services: Vec<&str> = view.routes.services.iter().map(|s| s.service.as_str()).collect();
+        assert_eq!(services, vec!["amazons3", "compute", "storage", "storagetransfer"]);
+        assert_eq!(view.routes.shards_for_query("storage.obj", "prefix"), vec![2]);
+        assert_eq!(view.routes.shards_for_query("s3:", "prefix"), vec![0]);
+        assert_eq!(view.routes.permission_count(), full.index().permissions.len());
+
+        let paged = |offset, limit, sort| SearchOptions { offset, limit, sort, include_deprecated: true, ..SearchOptions::default() };
+        for (query, mode) in [("storage", "prefix"), ("st", "prefix"), ("storage.objects.get", "exact"), ("objects get", "fuzzy"), ("s3", "prefix")] {
+            for options in [paged(0, 20, SortField::Score), paged(1, 2, SortField::Score), paged(0, 3, SortField::Name)] {
+                let expected = full.index().search_permissions_page(query, mode, &options);
+                let Ok(found) = view.search_permissions_page(query, mode, &options);
+                assert_eq!(found.total, expected.total, "{} ({})", query, mode);
+                assert_eq!(json(&found.items), json(&expected.items), "{} ({})", query, mode);
+
+                let expected = full.index().search_roles_page(query, mode, &options);
+                let found = view.routes.search_roles_page(query, mode, &options);
+                assert_eq!((found.total, json(&found.items)), (expected.total, json(&expected.items)));
+            }
+        }
+
+        let Ok(shard) = view.permission_shard_ignore_case("S3:getobject");
+        assert_eq!(shard.unwrap().permission("s3:GetObject").unwrap().service, "amazons3");
+    }
+
+    #[test]
+    fn role_permissions_read_only_the_shards_in_range() {
+        let sharded = ShardedIndex::build(&sharded_dataset());
+        let routes = RoutesBuf::new(&sharded.routes.to_bytes().unwrap()).unwrap();
+        let shards = TestShards(
+            sharded.shards.iter().map(|s| IndexBuf::new(&s.to_bytes().unwrap()).unwrap()).collect(),
+        );
+        let view = ShardedView::new(routes.routes(), &shards);
+
+        let editor = view.routes.role("roles/editor").unwrap();
+        assert_eq!(editor.permission_count, 4);
+        let Ok(all) = view.role_permissions(editor, 0..4);
+        assert_eq!(all, vec!["compute.instances.get", "storage.buckets.get", "storage.objects.get", "storagetransfer.jobs.get"]);
+        let Ok(middle) = view.role_permissions(editor, 1..3);
+        assert_eq!(middle, vec!["storage.buckets.get", "storage.objects.get"]);
+
+        // A shard holds only its service's permissions, granted by the
+        // roles that grant them there
+        let storage = view.routes.service("storage").unwrap();
+        let roles: Vec<&str> = shards.0[storage].index().roles.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(roles, vec!["roles/editor", "roles/storage.objectViewer", "roles/storage.legacyReader"]);
+        assert_eq!(shards.0[storage].index().role("roles/editor").unwrap().included_permissions.len(), 2);
+    }
+}
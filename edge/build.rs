@@ -1,210 +1,224 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use gcpiam_core::data::DEFAULT_PROVIDER;
+use gcpiam_core::{IamDataFile, ShardIndex, ShardedIndex};
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::Path;
 
-#[derive(Debug, Deserialize)]
-struct IamDataFile {
-    roles: Vec<RoleData>,
-    #[allow(dead_code)]
-    permissions: Vec<PermissionData>,
-    #[allow(dead_code)]
-    metadata: MetadataData,
-}
-
-#[derive(Debug, Deserialize)]
-struct RoleData {
-    name: String,
-    title: String,
-    description: String,
-    stage: String,
-    included_permissions: Vec<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct PermissionData {
-    name: String,
-    #[allow(dead_code)]
-    service: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct MetadataData {
-    #[allow(dead_code)]
-    total_roles: usize,
-    #[allow(dead_code)]
-    total_permissions: usize,
-    last_updated: String,
-}
-
-// Serializable search index structures
-#[derive(Debug, Clone, Serialize)]
-struct Role {
-    name: String,
-    title: String,
-    description: String,
-    stage: String,
-    included_permissions: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct Permission {
-    name: String,
-    service: String,
-    resource: String,
-    action: String,
-    granted_by_roles: Vec<u32>, // Role indices for compact storage
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct RoleSummary {
-    name: String,
-    title: String,
-    stage: String,
-}
-
-#[derive(Debug, Serialize)]
-struct PrebuiltIndex {
-    // All permissions sorted for binary search
-    permissions: Vec<Permission>,
-    permission_names: Vec<String>,
-
-    // All roles
-    roles: Vec<Role>,
-    role_names: Vec<String>,
-    role_summaries: Vec<RoleSummary>,
-
-    // Service -> permission indices
-    service_to_permissions: HashMap<String, Vec<u32>>,
-
-    // Lowercase names for case-insensitive search
-    permission_names_lower: Vec<String>,
-    role_names_lower: Vec<String>,
-    role_titles_lower: Vec<String>,
-}
+/// Site the sitemap lists pages of
+const BASE_URL: &str = "https://gcpiam.com";
+/// Search engines read at most this many URLs per sitemap file
+const MAX_URLS_PER_SITEMAP: usize = 50_000;
 
 fn main() {
     println!("cargo:rerun-if-changed=../data/iam-data.json");
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=GCPIAM_SERVICES");
+    println!("cargo:rerun-if-env-changed=GCPIAM_EMBED_SHARDS");
 
     let data_path = Path::new("../data/iam-data.json");
     let out_dir = std::env::var("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("prebuilt_index.bin");
+    compress_assets(Path::new(&out_dir));
+    let timestamp_path = Path::new(&out_dir).join("timestamp.rs");
+
+    // GCPIAM_SERVICES=compute,storage embeds only those services' shards
+    // (data/services/, written by the scraper's --split-services)
+    let services = std::env::var("GCPIAM_SERVICES").ok().filter(|s| !s.is_empty());
 
-    if !data_path.exists() {
+    if services.is_none() && !data_path.exists() {
         eprintln!("Warning: iam-data.json not found, creating empty index");
-        let empty_index = PrebuiltIndex {
-            permissions: vec![],
-            permission_names: vec![],
-            roles: vec![],
-            role_names: vec![],
-            role_summaries: vec![],
-            service_to_permissions: HashMap::new(),
-            permission_names_lower: vec![],
-            role_names_lower: vec![],
-            role_titles_lower: vec![],
-        };
-        let encoded = bincode::serialize(&empty_index).unwrap();
-        fs::write(&dest_path, encoded).unwrap();
+        write_index(Path::new(&out_dir), &ShardedIndex::default());
+        fs::write(&timestamp_path, "pub const LAST_UPDATED: &str = \"\";\n").unwrap();
+        write_sitemaps(Path::new(&out_dir), &[], "");
         return;
     }
 
-    eprintln!("Building search index from iam-data.json...");
-
-    let content = fs::read_to_string(data_path).expect("Failed to read iam-data.json");
-    let data: IamDataFile = serde_json::from_str(&content).expect("Failed to parse JSON");
+    let data: IamDataFile = match services {
+        Some(services) => load_shards(Path::new("../data/services"), &services),
+        None => {
+            eprintln!("Building search index from iam-data.json...");
+            let content = fs::read_to_string(data_path).expect("Failed to read iam-data.json");
+            serde_json::from_str(&content).expect("Failed to parse JSON")
+        }
+    };
 
     // Extract and generate timestamp constant
-    let last_updated = &data.metadata.last_updated;
+    let last_updated = data.metadata.last_updated.as_deref().unwrap_or_default();
     let timestamp_code = format!(
         "pub const LAST_UPDATED: &str = \"{}\";\n",
         last_updated
     );
-    let timestamp_path = Path::new(&out_dir).join("timestamp.rs");
     fs::write(&timestamp_path, timestamp_code).expect("Failed to write timestamp constant");
 
-    // Build role index and summaries
-    let mut roles: Vec<Role> = Vec::with_capacity(data.roles.len());
-    let mut role_names: Vec<String> = Vec::with_capacity(data.roles.len());
-    let mut role_summaries: Vec<RoleSummary> = Vec::with_capacity(data.roles.len());
-    let mut role_name_to_idx: HashMap<String, u32> = HashMap::new();
-
-    for role_data in &data.roles {
-        let idx = roles.len() as u32;
-        role_name_to_idx.insert(role_data.name.clone(), idx);
-
-        roles.push(Role {
-            name: role_data.name.clone(),
-            title: role_data.title.clone(),
-            description: role_data.description.clone(),
-            stage: role_data.stage.clone(),
-            included_permissions: role_data.included_permissions.clone(),
-        });
-        role_names.push(role_data.name.clone());
-        role_summaries.push(RoleSummary {
-            name: role_data.name.clone(),
-            title: role_data.title.clone(),
-            stage: role_data.stage.clone(),
-        });
-    }
+    let index = ShardedIndex::build(&data);
+    eprintln!(
+        "Indexed {} roles and {} services",
+        index.routes.roles.len(),
+        index.routes.services.len()
+    );
+    write_index(Path::new(&out_dir), &index);
 
-    // Build permission index with role mappings
-    let mut permission_map: HashMap<String, Permission> = HashMap::new();
-    let mut service_to_permissions: HashMap<String, Vec<u32>> = HashMap::new();
-
-    for role_data in &data.roles {
-        let role_idx = *role_name_to_idx.get(&role_data.name).unwrap();
-
-        for perm_name in &role_data.included_permissions {
-            let entry = permission_map.entry(perm_name.clone()).or_insert_with(|| {
-                let parts: Vec<&str> = perm_name.split('.').collect();
-                Permission {
-                    name: perm_name.clone(),
-                    service: parts.first().unwrap_or(&"").to_string(),
-                    resource: parts.get(1).unwrap_or(&"").to_string(),
-                    action: parts.get(2).unwrap_or(&"").to_string(),
-                    granted_by_roles: vec![],
-                }
-            });
-            entry.granted_by_roles.push(role_idx);
+    write_sitemaps(Path::new(&out_dir), &page_paths(&data), last_updated.get(..10).unwrap_or_default());
+}
+
+/// Write routes.bin, each shard as shards/{position}.bin and shards.rs
+/// embedding them, or none of them with GCPIAM_EMBED_SHARDS=0 (the edge
+/// then reads every shard from its KV Store)
+fn write_index(out_dir: &Path, index: &ShardedIndex) {
+    let routes = index.routes.to_bytes().expect("Failed to serialize routing table");
+    eprintln!("Routing table size: {} bytes ({:.2} MB)", routes.len(), routes.len() as f64 / 1024.0 / 1024.0);
+    fs::write(out_dir.join("routes.bin"), routes).expect("Failed to write routing table");
+
+    let embed = std::env::var("GCPIAM_EMBED_SHARDS").map_or(true, |v| v != "0");
+    let shards_dir = out_dir.join("shards");
+    fs::create_dir_all(&shards_dir).expect("Failed to create shards directory");
+    let mut code = String::from(
+        "/// Shards embedded in the binary, by position in the routing table\nstatic EMBEDDED_SHARDS: &[&Aligned<[u8]>] = &[\n",
+    );
+    if embed {
+        let mut total = 0;
+        for (position, shard) in index.shards.iter().enumerate() {
+            let encoded = shard.to_bytes().expect("Failed to serialize shard");
+            total += encoded.len();
+            let path = shards_dir.join(format!("{}.bin", position));
+            fs::write(&path, encoded).expect("Failed to write shard");
+            code.push_str(&format!("    &Aligned(*include_bytes!({:?})),\n", path));
         }
+        eprintln!("Embedded {} shards ({:.2} MB)", index.shards.len(), total as f64 / 1024.0 / 1024.0);
+    } else {
+        eprintln!("Not embedding shards (GCPIAM_EMBED_SHARDS=0)");
     }
+    code.push_str("];\n");
+    fs::write(out_dir.join("shards.rs"), code).expect("Failed to write shards.rs");
+}
 
-    // Sort permissions and build final structures
-    let mut permissions: Vec<Permission> = permission_map.into_values().collect();
-    permissions.sort_by(|a, b| a.name.cmp(&b.name));
-
-    let mut permission_names: Vec<String> = Vec::with_capacity(permissions.len());
-    for (idx, perm) in permissions.iter().enumerate() {
-        permission_names.push(perm.name.clone());
-        service_to_permissions
-            .entry(perm.service.clone())
-            .or_insert_with(Vec::new)
-            .push(idx as u32);
+/// Frontend files embedded in the binary, each also written as a
+/// brotli-compressed `{name}.br` for clients that accept it
+const ASSETS: &[&str] = &["index.html", "styles.css", "app.js"];
+
+fn compress_assets(out_dir: &Path) {
+    let params = brotli::enc::BrotliEncoderParams { quality: 11, ..Default::default() };
+    for name in ASSETS {
+        let path = Path::new("../frontend/public").join(name);
+        println!("cargo:rerun-if-changed={}", path.display());
+        let content = fs::read(&path).expect("Failed to read frontend asset");
+        let mut compressed = Vec::new();
+        brotli::BrotliCompress(&mut content.as_slice(), &mut compressed, &params).expect("Failed to compress asset");
+        eprintln!("{}: {} bytes, {} compressed", name, content.len(), compressed.len());
+        fs::write(out_dir.join(format!("{}.br", name)), compressed).expect("Failed to write compressed asset");
     }
+}
 
-    // Pre-compute lowercase versions for case-insensitive search
-    let permission_names_lower: Vec<String> = permission_names.iter().map(|s| s.to_lowercase()).collect();
-    let role_names_lower: Vec<String> = role_names.iter().map(|s| s.to_lowercase()).collect();
-    let role_titles_lower: Vec<String> = roles.iter().map(|r| r.title.to_lowercase()).collect();
-
-    let index = PrebuiltIndex {
-        permissions,
-        permission_names,
-        roles,
-        role_names,
-        role_summaries,
-        service_to_permissions,
-        permission_names_lower,
-        role_names_lower,
-        role_titles_lower,
-    };
+/// Paths of the pages worth crawling: predefined GCP roles, GCP
+/// permissions, their services and the browse pages listing them, matching the static site (custom roles are
+/// organization-internal; other clouds' entries have no canonical pages)
+fn page_paths(data: &IamDataFile) -> Vec<String> {
+    let roles = data
+        .roles
+        .iter()
+        .filter(|r| r.provider == DEFAULT_PROVIDER && !r.custom)
+        .map(|r| format!("/roles/{}", encode_path(&r.name)));
+    let permissions = data
+        .permissions
+        .iter()
+        .filter(|p| p.provider == DEFAULT_PROVIDER)
+        .map(|p| format!("/permissions/{}", encode_path(&p.name)));
+    let services: BTreeSet<&str> = data
+        .permissions
+        .iter()
+        .filter(|p| p.provider == DEFAULT_PROVIDER)
+        .map(|p| p.service.as_str())
+        .collect();
+    let letters: BTreeSet<char> = data
+        .roles
+        .iter()
+        .filter(|r| r.provider == DEFAULT_PROVIDER && !r.custom)
+        .filter_map(|r| r.name.strip_prefix("roles/")?.chars().next())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let browse = ["/roles/browse".to_string(), "/permissions/browse".to_string()]
+        .into_iter()
+        .chain(letters.into_iter().map(|l| format!("/roles/browse/{}", encode_path(&l.to_string()))))
+        .chain(services.iter().map(|s| format!("/permissions/browse/{}", encode_path(s))));
+    let services = services.iter().map(|s| format!("/services/{}", encode_path(s)));
+    roles.chain(permissions).chain(services).chain(browse).collect()
+}
+
+/// Percent-encode each segment, keeping the slashes the page routes expect
+fn encode_path(name: &str) -> String {
+    name.split('/').map(|segment| urlencoding::encode(segment).into_owned()).collect::<Vec<_>>().join("/")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn urlset(entries: &[(String, &str)], lastmod: &str) -> String {
+    let lastmod = if lastmod.is_empty() { String::new() } else { format!("    <lastmod>{}</lastmod>\n", lastmod) };
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for (loc, priority) in entries {
+        xml.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n{}    <priority>{}</priority>\n  </url>\n",
+            xml_escape(loc),
+            lastmod,
+            priority
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
 
-    eprintln!("Indexed {} permissions and {} roles", index.permission_names.len(), index.role_names.len());
+/// Write sitemap.xml, split into sitemap-N.xml parts behind a sitemap index
+/// past MAX_URLS_PER_SITEMAP, and sitemaps.rs embedding them
+fn write_sitemaps(out_dir: &Path, paths: &[String], lastmod: &str) {
+    let mut entries = vec![(format!("{}/", BASE_URL), "1.0")];
+    entries.extend(paths.iter().map(|path| (format!("{}{}", BASE_URL, path), "0.8")));
 
-    let encoded = bincode::serialize(&index).expect("Failed to serialize index");
-    eprintln!("Index size: {} bytes ({:.2} MB)", encoded.len(), encoded.len() as f64 / 1024.0 / 1024.0);
+    let parts: Vec<&[(String, &str)]> = if entries.len() <= MAX_URLS_PER_SITEMAP {
+        vec![]
+    } else {
+        entries.chunks(MAX_URLS_PER_SITEMAP).collect()
+    };
+    let sitemap = if parts.is_empty() {
+        urlset(&entries, lastmod)
+    } else {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+        for n in 1..=parts.len() {
+            xml.push_str(&format!("  <sitemap>\n    <loc>{}/sitemap-{}.xml</loc>\n  </sitemap>\n", BASE_URL, n));
+        }
+        xml.push_str("</sitemapindex>\n");
+        xml
+    };
+    fs::write(out_dir.join("sitemap.xml"), sitemap).expect("Failed to write sitemap");
+
+    let mut code = String::from("pub static SITEMAP_XML: &str = include_str!(concat!(env!(\"OUT_DIR\"), \"/sitemap.xml\"));\n");
+    code.push_str("/// sitemap-1.xml, sitemap-2.xml, ... when sitemap.xml is an index\npub static SITEMAP_PARTS: &[&str] = &[\n");
+    for (i, part) in parts.iter().enumerate() {
+        let name = format!("sitemap-{}.xml", i + 1);
+        fs::write(out_dir.join(&name), urlset(part, lastmod)).expect("Failed to write sitemap part");
+        code.push_str(&format!("    include_str!(concat!(env!(\"OUT_DIR\"), \"/{}\")),\n", name));
+    }
+    code.push_str("];\n");
+    fs::write(out_dir.join("sitemaps.rs"), code).expect("Failed to write sitemaps.rs");
+    eprintln!("Sitemap lists {} URLs in {} file(s)", entries.len(), parts.len().max(1));
+}
 
-    fs::write(&dest_path, encoded).expect("Failed to write index");
-    eprintln!("Wrote prebuilt index to {:?}", dest_path);
+/// The listed services' shards, merged into one dataset
+fn load_shards(dir: &Path, services: &str) -> IamDataFile {
+    eprintln!("Building search index from service shards: {}", services);
+    let index_path = dir.join("index.json");
+    println!("cargo:rerun-if-changed={}", index_path.display());
+    let content = fs::read_to_string(&index_path).expect("Failed to read services/index.json");
+    let index: ShardIndex = serde_json::from_str(&content).expect("Failed to parse services/index.json");
+
+    let shards = services.split(',').map(str::trim).filter(|s| !s.is_empty()).map(|service| {
+        let entry = index
+            .services
+            .get(service)
+            .unwrap_or_else(|| panic!("No shard for service '{}' in services/index.json", service));
+        let path = dir.join(&entry.path);
+        println!("cargo:rerun-if-changed={}", path.display());
+        let content = fs::read_to_string(&path).expect("Failed to read service shard");
+        serde_json::from_str(&content).expect("Failed to parse service shard")
+    });
+    IamDataFile::merge(shards.collect::<Vec<IamDataFile>>())
 }
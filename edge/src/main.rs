@@ -1,81 +1,244 @@
+use fastly::geo::geo_lookup;
 use fastly::http::{Method, StatusCode};
-use fastly::{Error, Request, Response};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use fastly::kv_store::{KVStore, KVStoreError};
+use fastly::log::Endpoint;
+use fastly::{ConfigStore, Error, Request, Response};
+use gcpiam_core::data::DEFAULT_PROVIDER;
+use gcpiam_core::search::engine::{PermissionSearchResult, RoleSearchResult};
+use gcpiam_core::search::SearchOptions;
+use gcpiam_core::index::{ArchivedPrebuiltIndex, IndexBuf};
+use gcpiam_core::shards::{ArchivedRoleRoute, ArchivedServiceRoute, ArchivedShardRoutes, RoutesBuf, ShardSource, ShardedView};
+use gcpiam_core::{PrebuiltIndex, RoleComparison, ShardRoutes};
+use serde::Serialize;
+use std::cell::{OnceCell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Aligned storage for the embedded archives, which rkyv reads in place
+#[repr(C, align(16))]
+struct Aligned<T: ?Sized>(T);
+
+// Include the routing table built at compile time
+static ROUTES_DATA: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/routes.bin")));
+
+// Include the shards built at compile time (EMBEDDED_SHARDS)
+include!(concat!(env!("OUT_DIR"), "/shards.rs"));
+
+/// KV Store the sharded index is published to: key "version" holds the
+/// dataset's last_updated, "routes/{version}" its routing table and
+/// "shards/{version}/{service}" each service's shard
+const INDEX_STORE: &str = "gcpiam_index";
+
+/// Bytes of KV Store shards an instance keeps once read, beyond the ones
+/// in use
+const SHARD_CACHE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Routing table an instance serves, the dataset timestamp it was built
+/// from, and where its shards are read from
+struct Catalog {
+    routes: &'static ArchivedShardRoutes,
+    last_updated: &'static str,
+    /// Whether the shards are the binary's own, rather than the KV Store's
+    embedded: bool,
+    store: Option<KVStore>,
+    /// KV Store shards, least recently read first
+    cache: RefCell<VecDeque<(usize, Rc<IndexBuf>)>>,
+}
 
-// Include pre-built index at compile time
-static INDEX_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/prebuilt_index.bin"));
+/// A shard, embedded or read from the KV Store
+enum Shard {
+    Embedded(&'static ArchivedPrebuiltIndex),
+    Loaded(Rc<IndexBuf>),
+}
 
-// Include generated timestamp constant
-include!(concat!(env!("OUT_DIR"), "/timestamp.rs"));
+impl Deref for Shard {
+    type Target = ArchivedPrebuiltIndex;
 
-// Include frontend files at compile time
-static INDEX_HTML: &str = include_str!("../../frontend/public/index.html");
-static STYLES_CSS: &str = include_str!("../../frontend/public/styles.css");
-static APP_JS: &str = include_str!("../../frontend/public/app.js");
-
-// Deserializable search index structures (must match build.rs)
-#[derive(Debug, Clone, Deserialize)]
-struct Role {
-    name: String,
-    title: String,
-    description: String,
-    stage: String,
-    included_permissions: Vec<String>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-struct Permission {
-    name: String,
-    service: String,
-    resource: String,
-    action: String,
-    granted_by_roles: Vec<u32>,
-}
-
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct RoleSummary {
-    name: String,
-    title: String,
-    stage: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct PrebuiltIndex {
-    permissions: Vec<Permission>,
-    permission_names: Vec<String>,
-    roles: Vec<Role>,
-    role_names: Vec<String>,
-    role_summaries: Vec<RoleSummary>,
-    #[allow(dead_code)]
-    service_to_permissions: HashMap<String, Vec<u32>>,
-    permission_names_lower: Vec<String>,
-    role_names_lower: Vec<String>,
-    role_titles_lower: Vec<String>,
+    fn deref(&self) -> &ArchivedPrebuiltIndex {
+        match self {
+            Shard::Embedded(index) => index,
+            Shard::Loaded(buf) => buf.index(),
+        }
+    }
 }
 
-// API response types
-#[derive(Serialize)]
-struct PermissionSearchResult {
-    name: String,
-    service: String,
-    resource: String,
-    action: String,
-    score: f64,
-    granted_by_roles: Vec<RoleSummary>,
+impl ShardSource for &Catalog {
+    type Shard = Shard;
+    type Error = String;
+
+    fn shard(&self, position: usize) -> Result<Shard, String> {
+        if self.embedded {
+            let data = EMBEDDED_SHARDS.get(position).ok_or_else(|| format!("No embedded shard {}", position))?;
+            return PrebuiltIndex::access(&data.0).map(Shard::Embedded).map_err(|e| e.to_string());
+        }
+
+        let mut cache = self.cache.borrow_mut();
+        if let Some(entry) = cache.iter().position(|(cached, _)| *cached == position).and_then(|found| cache.remove(found)) {
+            let buf = entry.1.clone();
+            cache.push_back(entry);
+            return Ok(Shard::Loaded(buf));
+        }
+
+        let service = &self.routes.services.get(position).ok_or_else(|| format!("No shard {}", position))?.service;
+        let key = format!("shards/{}/{}", self.last_updated, service);
+        let store = self.store.as_ref().ok_or_else(|| format!("{}: no {} KV Store", key, INDEX_STORE))?;
+        let body = store.lookup(&key).map_err(|e| format!("{}: {}", key, e))?.take_body();
+        let buf = Rc::new(IndexBuf::read(body).map_err(|e| format!("{}: {}", key, e))?);
+
+        cache.push_back((position, buf.clone()));
+        let mut held: usize = cache.iter().map(|(_, cached)| cached.size()).sum();
+        while held > SHARD_CACHE_BYTES && cache.len() > 1 {
+            if let Some((_, evicted)) = cache.pop_front() {
+                held -= evicted.size();
+            }
+        }
+        Ok(Shard::Loaded(buf))
+    }
 }
 
-#[derive(Serialize)]
-struct RoleSearchResult {
-    name: String,
-    title: String,
-    description: String,
-    stage: String,
-    score: f64,
-    permission_count: usize,
-    sample_permissions: Vec<String>,
+impl Catalog {
+    /// Queries that read shards, lazily and one at a time
+    fn view(&self) -> ShardedView<'_, &Catalog> {
+        ShardedView::new(self.routes, self)
+    }
+}
+
+thread_local! {
+    /// Loaded and validated by the first request an instance serves
+    static CATALOG: OnceCell<Result<&'static Catalog, String>> = const { OnceCell::new() };
+}
+
+fn catalog() -> Result<&'static Catalog, String> {
+    CATALOG.with(|catalog| catalog.get_or_init(load_catalog).clone())
+}
+
+/// last_updated of the dataset being served
+fn last_updated() -> &'static str {
+    catalog().map(|catalog| catalog.last_updated).unwrap_or(LAST_UPDATED)
+}
+
+/// The KV Store's routing table when it is newer than the embedded one,
+/// else the embedded one. Only the table is read up front; shards are read
+/// as queries route to them, from the binary when it embeds them and the
+/// table is its own, otherwise from the KV Store under the table's version.
+fn load_catalog() -> Result<&'static Catalog, String> {
+    let store = KVStore::open(INDEX_STORE).unwrap_or_else(|e| {
+        eprintln!("KV Store {} unavailable: {}", INDEX_STORE, e);
+        None
+    });
+    let published = match store.as_ref().map(published_routes) {
+        Some(Ok(published)) => published,
+        Some(Err(e)) => {
+            eprintln!("Serving the embedded routing table, KV Store one unavailable: {}", e);
+            None
+        }
+        None => None,
+    };
+    let (routes, last_updated, embedded) = match published {
+        Some((routes, version)) => (routes, version, false),
+        None => (
+            ShardRoutes::access(&ROUTES_DATA.0).map_err(|e| e.to_string())?,
+            LAST_UPDATED,
+            !EMBEDDED_SHARDS.is_empty(),
+        ),
+    };
+    Ok(Box::leak(Box::new(Catalog { routes, last_updated, embedded, store, cache: RefCell::default() })))
+}
+
+fn published_routes(store: &KVStore) -> Result<Option<(&'static ArchivedShardRoutes, &'static str)>, String> {
+    let version = match store.lookup("version") {
+        Ok(mut found) => String::from_utf8(found.take_body_bytes()).map_err(|e| e.to_string())?,
+        Err(KVStoreError::ItemNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    // Timestamps are ISO 8601, so they order as strings
+    let version = version.trim();
+    if version <= LAST_UPDATED {
+        return Ok(None);
+    }
+
+    let body = store
+        .lookup(&format!("routes/{}", version))
+        .map_err(|e| format!("routes/{}: {}", version, e))?
+        .take_body();
+    let routes = RoutesBuf::read(body).map_err(|e| e.to_string())?;
+    // A table published before all its shards would break the pages
+    // reading the missing ones, so keep serving the embedded catalog
+    let uploaded = published_shards(store, version)?;
+    if let Some(missing) = routes.routes().services.iter().find(|route| !uploaded.contains(route.service.as_str())) {
+        return Err(format!("shards/{}/{} missing", version, missing.service));
+    }
+    // Lives as long as the instance, like the embedded table
+    let buf: &'static RoutesBuf = Box::leak(Box::new(routes));
+    Ok(Some((buf.routes(), Box::leak(version.to_string().into_boxed_str()))))
 }
 
+/// Services with a shard stored under `shards/{version}/`
+fn published_shards(store: &KVStore, version: &str) -> Result<HashSet<String>, String> {
+    let prefix = format!("shards/{}/", version);
+    let mut services = HashSet::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut list = store.build_list().prefix(&prefix).limit(1000);
+        if let Some(cursor) = &cursor {
+            list = list.cursor(cursor);
+        }
+        let page = list.execute().map_err(|e| format!("{}: {}", prefix, e))?;
+        cursor = page.next_cursor().filter(|next| !next.is_empty());
+        services.extend(page.into_keys().into_iter().filter_map(|key| key.strip_prefix(&prefix).map(str::to_string)));
+        if cursor.is_none() {
+            return Ok(services);
+        }
+    }
+}
+
+// Include generated timestamp constant
+include!(concat!(env!("OUT_DIR"), "/timestamp.rs"));
+
+// Include sitemaps generated from the dataset (SITEMAP_XML, SITEMAP_PARTS)
+include!(concat!(env!("OUT_DIR"), "/sitemaps.rs"));
+
+/// Canonical origin for og:url and the sitemap
+const BASE_URL: &str = "https://gcpiam.com";
+
+/// Lets browsers add gcpiam.com as a search engine (linked from every page)
+static OPENSEARCH_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>GCP IAM</ShortName>
+  <Description>Search Google Cloud IAM roles and permissions</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <Url type="text/html" method="get" template="https://gcpiam.com/?q={searchTerms}"/>
+  <Url type="application/opensearchdescription+xml" rel="self" template="https://gcpiam.com/opensearch.xml"/>
+</OpenSearchDescription>
+"#;
+
+static ROBOTS_TXT: &str = "User-agent: *\nAllow: /\nDisallow: /api/\n\nSitemap: https://gcpiam.com/sitemap.xml\n";
+
+/// Frontend file embedded at compile time, with the brotli variant build.rs
+/// compressed it to
+struct Asset {
+    plain: &'static str,
+    brotli: &'static [u8],
+}
+
+static INDEX_HTML: Asset = Asset {
+    plain: include_str!("../../frontend/public/index.html"),
+    brotli: include_bytes!(concat!(env!("OUT_DIR"), "/index.html.br")),
+};
+static STYLES_CSS: Asset = Asset {
+    plain: include_str!("../../frontend/public/styles.css"),
+    brotli: include_bytes!(concat!(env!("OUT_DIR"), "/styles.css.br")),
+};
+static APP_JS: Asset = Asset {
+    plain: include_str!("../../frontend/public/app.js"),
+    brotli: include_bytes!(concat!(env!("OUT_DIR"), "/app.js.br")),
+};
+
+// API response types
 #[derive(Serialize)]
 struct SearchResponse {
     success: bool,
@@ -88,6 +251,29 @@ struct SearchData {
     roles: Vec<RoleSearchResult>,
     query: String,
     mode: String,
+    /// Matches before limit/offset, for "show more"
+    total_permissions: usize,
+    total_roles: usize,
+    limit: usize,
+    offset: usize,
+}
+
+/// Results per list when ?limit= is absent, and the most a request may ask for
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+/// Deepest ?offset= accepted
+const MAX_OFFSET: usize = 10_000;
+
+#[derive(Serialize)]
+struct CompareResponse {
+    success: bool,
+    data: RoleComparison,
+}
+
+#[derive(Serialize)]
+struct NotFoundResponse {
+    error: String,
+    suggestions: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -102,6 +288,8 @@ struct StatsData {
     total_roles: usize,
     indexed: bool,
     version: String,
+    /// When the dataset was fetched from the IAM API
+    last_updated: String,
 }
 
 #[derive(Serialize)]
@@ -123,29 +311,89 @@ struct MetadataResponse {
 }
 
 fn main() -> Result<(), Error> {
+    let started = Instant::now();
     let req = Request::from_client();
-    let resp = handle_request(req)?;
+    let mut log = RequestLog::new(&req);
+    let resp = handle_request(req, &mut log)?;
+    log.status = resp.get_status().as_u16();
+    log.latency_ms = started.elapsed().as_secs_f64() * 1000.0;
     resp.send_to_client();
+    log.write();
     Ok(())
 }
 
-// Allowed domains for access control
-const ALLOWED_HOSTS: &[&str] = &["gcpiam.com", "www.gcpiam.com", "localhost", "127.0.0.1"];
+/// Logging endpoint that receives one JSON line per request, for search
+/// analytics and error rates
+const LOG_ENDPOINT: &str = "gcpiam_requests";
+
+#[derive(Serialize, Default)]
+struct RequestLog {
+    path: String,
+    /// Search query and mode, once validated
+    query: Option<String>,
+    mode: Option<String>,
+    /// Search matches before pagination
+    permissions: Option<usize>,
+    roles: Option<usize>,
+    status: u16,
+    latency_ms: f64,
+    country: Option<String>,
+    city: Option<String>,
+}
+
+impl RequestLog {
+    fn new(req: &Request) -> Self {
+        let geo = req.get_client_ip_addr().and_then(geo_lookup);
+        RequestLog {
+            path: req.get_path().to_string(),
+            country: geo.as_ref().map(|g| g.country_code().to_string()),
+            city: geo.as_ref().map(|g| g.city().to_string()),
+            ..RequestLog::default()
+        }
+    }
+
+    /// Dropped when the service has no endpoint by that name (local runs)
+    fn write(&self) {
+        if let (Ok(mut endpoint), Ok(line)) = (Endpoint::try_from_name(LOG_ENDPOINT), serde_json::to_string(self)) {
+            let _ = writeln!(endpoint, "{}", line);
+        }
+    }
+}
+
+// Allowed domains for access control, unless the config store lists others
+const DEFAULT_ALLOWED_HOSTS: &[&str] = &["gcpiam.com", "www.gcpiam.com", "localhost", "127.0.0.1"];
+
+/// Config store whose comma-separated "allowed_hosts" key replaces
+/// DEFAULT_ALLOWED_HOSTS, so staging domains and aliases need no redeploy
+const CONFIG_STORE: &str = "gcpiam_config";
+
+static ALLOWED_HOSTS: OnceLock<Vec<String>> = OnceLock::new();
+
+fn allowed_hosts() -> &'static [String] {
+    ALLOWED_HOSTS.get_or_init(|| {
+        let configured = ConfigStore::try_open(CONFIG_STORE)
+            .ok()
+            .and_then(|store| store.try_get("allowed_hosts").ok().flatten());
+        match configured {
+            Some(list) => list.split(',').map(|h| h.trim().to_string()).filter(|h| !h.is_empty()).collect(),
+            None => DEFAULT_ALLOWED_HOSTS.iter().map(|h| h.to_string()).collect(),
+        }
+    })
+}
 
 fn is_allowed_host(req: &Request) -> bool {
-    // Check the Host header
-    if let Some(host) = req.get_header_str("host") {
-        let host_without_port = host.split(':').next().unwrap_or(host);
-        if ALLOWED_HOSTS.iter().any(|&h| h == host_without_port) {
-            return true;
+    match req.get_header_str("host") {
+        Some(host) => {
+            let host_without_port = host.split(':').next().unwrap_or(host);
+            allowed_hosts().iter().any(|h| h.eq_ignore_ascii_case(host_without_port))
         }
+        // If no Host header, allow (Fastly routing is already protecting us)
+        // This handles cases where Fastly doesn't forward the Host header in WASM
+        None => true,
     }
-    // If no Host header, allow (Fastly routing is already protecting us)
-    // This handles cases where Fastly doesn't forward the Host header in WASM
-    true
 }
 
-fn handle_request(req: Request) -> Result<Response, Error> {
+fn handle_request(req: Request, log: &mut RequestLog) -> Result<Response, Error> {
     let path = req.get_path();
     let method = req.get_method();
 
@@ -166,86 +414,234 @@ fn handle_request(req: Request) -> Result<Response, Error> {
         return Ok(resp);
     }
 
+    // Send trailing-slash, case and percent-encoded variants of detail pages
+    // to the one URL they're listed under
+    if let Some(mut location) = canonical_location(path) {
+        if let Some(query) = req.get_query_str() {
+            location = format!("{}?{}", location, query);
+        }
+        let mut resp = Response::from_status(StatusCode::MOVED_PERMANENTLY);
+        resp.set_header("Location", location);
+        resp.set_header("Cache-Control", "public, max-age=86400");
+        return Ok(resp);
+    }
+
+    // Dataset-backed responses only change when the embedded data does
+    let validated = serves_dataset(path);
+    if validated && etag_matches(&req) {
+        let mut resp = Response::from_status(StatusCode::NOT_MODIFIED);
+        resp.set_header("ETag", etag());
+        if let Some(modified) = http_date(last_updated()) {
+            resp.set_header("Last-Modified", modified);
+        }
+        if path.starts_with("/api/") {
+            resp.set_header("Access-Control-Allow-Origin", "https://gcpiam.com");
+        }
+        return Ok(resp);
+    }
+
     // Route requests
-    match path {
-        "/" | "/index.html" => serve_html(INDEX_HTML),
-        "/styles.css" => serve_css(STYLES_CSS),
-        "/app.js" => serve_js(APP_JS),
-        "/sitemap.xml" => serve_sitemap(),
+    let mut resp = match path {
+        "/" | "/index.html" => serve_html(&req, &INDEX_HTML),
+        "/styles.css" => serve_css(&req, &STYLES_CSS),
+        "/app.js" => serve_js(&req, &APP_JS),
+        "/sitemap.xml" => serve_sitemap(SITEMAP_XML),
+        "/robots.txt" => serve_robots(),
+        "/opensearch.xml" => serve_opensearch(),
         "/api/v1/health" => serve_json(handle_health()),
         "/api/v1/stats" => serve_json(handle_stats()),
         "/api/v1/info" => serve_json(handle_info()),
-        p if p.starts_with("/api/v1/search") => serve_json(handle_search(&req)),
-        p if p.starts_with("/permissions/") => serve_permission_page(p),
-        p if p.starts_with("/roles/") => serve_role_page(p),
+        "/api/v1/compare" => serve_json(handle_compare(&req)),
+        "/compare" => serve_compare_page(&req),
+        p if p.starts_with("/api/v1/search") => serve_json(handle_search(&req, log)),
+        p if p.starts_with("/roles/browse") => serve_roles_browse(p),
+        p if p.starts_with("/permissions/browse") => serve_permissions_browse(p),
+        p if p.starts_with("/permissions/") => serve_permission_page(&req, p),
+        p if sitemap_part(p).is_some() => serve_sitemap(sitemap_part(p).unwrap_or_default()),
+        p if p.starts_with("/roles/") => serve_role_page(&req, p),
+        p if p.starts_with("/services/") => serve_service_page(p),
         _ => serve_not_found(),
+    }?;
+    if validated && resp.get_status() == StatusCode::OK {
+        resp.set_header("ETag", etag());
+        if let Some(modified) = http_date(last_updated()) {
+            resp.set_header("Last-Modified", modified);
+        }
+        resp.set_header("Surrogate-Key", surrogate_keys(path));
+        if let Some(cache_control) = resp.get_header_str("Cache-Control").map(str::to_string) {
+            resp.set_header("Cache-Control", format!("{}, {}", cache_control, STALE_DIRECTIVES));
+        }
+    }
+    Ok(resp)
+}
+
+/// Dataset responses stay servable while being refetched after they expire,
+/// and while the service errors
+const STALE_DIRECTIVES: &str = "stale-while-revalidate=3600, stale-if-error=86400";
+
+/// Space-separated keys a purge can target: every dataset response
+/// ("dataset"), one dataset version's, and a page's own role, permission or
+/// service ("role-roles/storage.admin"), so publishing a dataset can purge
+/// just the pages it changed
+fn surrogate_keys(path: &str) -> String {
+    let mut keys = vec!["dataset".to_string(), format!("dataset-{}", last_updated())];
+    for (prefix, kind) in [("/roles/", "role"), ("/permissions/", "permission"), ("/services/", "service")] {
+        if let Some(name) = path.strip_prefix(prefix).filter(|name| !name.starts_with("browse")) {
+            keys.push(format!("{}-{}", kind, name));
+        }
     }
+    keys.join(" ")
 }
 
-fn serve_html(content: &str) -> Result<Response, Error> {
+/// Canonical path of a role, permission or service page requested under a
+/// variant of it, or None when the path is already canonical (or names
+/// nothing in the index)
+fn canonical_location(path: &str) -> Option<String> {
+    let (prefix, raw) = ["/roles/", "/permissions/", "/services/"]
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix).map(|rest| (*prefix, rest)))?;
+    let name = urlencoding::decode(raw).ok()?;
+    let name = name.trim_end_matches('/');
+    let catalog = catalog().ok()?;
+
+    let canonical = match prefix {
+        "/roles/" => catalog.routes.role_ignore_case(name)?.name.to_string(),
+        "/permissions/" => catalog.view().permission_shard_ignore_case(name).ok()??.permission_ignore_case(name)?.name.to_string(),
+        _ => Some(name.to_lowercase()).filter(|service| catalog.routes.service(service).is_some())?,
+    };
+    // Names a client would percent-encode again (spaces, non-ASCII) would
+    // redirect forever; those pages decode their path instead
+    let url_safe = canonical.bytes().all(|b| b.is_ascii_alphanumeric() || b"._-/:@".contains(&b));
+    (url_safe && canonical != raw).then(|| format!("{}{}", prefix, canonical))
+}
+
+/// Routes whose responses are derived from the served index
+fn serves_dataset(path: &str) -> bool {
+    ["/api/v1/search", "/api/v1/stats", "/api/v1/info", "/api/v1/compare", "/compare"].contains(&path)
+        || ["/permissions/", "/roles/", "/services/"].iter().any(|prefix| path.starts_with(prefix))
+}
+
+/// Changes with the embedded dataset's timestamp and with each release
+fn etag() -> String {
+    format!("\"{}-{}\"", env!("CARGO_PKG_VERSION"), last_updated())
+}
+
+/// An ISO 8601 UTC timestamp ("2026-01-31T04:04:23.920243Z") as an HTTP
+/// date ("Sat, 31 Jan 2026 04:04:23 GMT")
+fn http_date(iso: &str) -> Option<String> {
+    let field = |range: std::ops::Range<usize>| iso.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+
+    // Days since 1970-01-01 (a Thursday), per Howard Hinnant's days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let days = era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + doy - 719_468;
+
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    Some(format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        hour,
+        minute,
+        second
+    ))
+}
+
+/// Whether If-None-Match lists the current ETag (weak or strong) or `*`
+fn etag_matches(req: &Request) -> bool {
+    let Some(header) = req.get_header_str("If-None-Match") else {
+        return false;
+    };
+    let current = etag();
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == current)
+}
+
+fn serve_html(req: &Request, asset: &Asset) -> Result<Response, Error> {
     let mut resp = Response::from_status(StatusCode::OK);
     resp.set_header("Content-Type", "text/html; charset=utf-8");
     resp.set_header("Cache-Control", "public, max-age=3600");
-    resp.set_body(content);
+    set_asset_body(&mut resp, req, asset);
     Ok(resp)
 }
 
-fn serve_css(content: &str) -> Result<Response, Error> {
+fn serve_css(req: &Request, asset: &Asset) -> Result<Response, Error> {
     let mut resp = Response::from_status(StatusCode::OK);
     resp.set_header("Content-Type", "text/css; charset=utf-8");
     resp.set_header("Cache-Control", "public, max-age=86400");
-    resp.set_body(content);
+    set_asset_body(&mut resp, req, asset);
     Ok(resp)
 }
 
-fn serve_js(content: &str) -> Result<Response, Error> {
+fn serve_js(req: &Request, asset: &Asset) -> Result<Response, Error> {
     let mut resp = Response::from_status(StatusCode::OK);
     resp.set_header("Content-Type", "application/javascript; charset=utf-8");
     resp.set_header("Cache-Control", "public, max-age=86400");
-    resp.set_body(content);
+    set_asset_body(&mut resp, req, asset);
     Ok(resp)
 }
 
-fn serve_sitemap() -> Result<Response, Error> {
-    // Load index to get permissions and roles
-    let index_data: PrebuiltIndex = match bincode::deserialize(INDEX_DATA) {
-        Ok(data) => data,
-        Err(_) => {
-            let mut resp = Response::from_status(StatusCode::INTERNAL_SERVER_ERROR);
-            resp.set_body("Failed to load index");
-            return Ok(resp);
-        }
-    };
-
-    let mut sitemap = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+fn set_asset_body(resp: &mut Response, req: &Request, asset: &Asset) {
+    resp.set_header("Vary", "Accept-Encoding");
+    if accepts_brotli(req) {
+        resp.set_header("Content-Encoding", "br");
+        resp.set_body(asset.brotli);
+    } else {
+        resp.set_body(asset.plain);
+    }
+}
 
-    // Add homepage
-    sitemap.push_str("  <url>\n    <loc>https://gcpiam.com/</loc>\n    <priority>1.0</priority>\n  </url>\n");
+/// Whether Accept-Encoding lists `br` without refusing it (`br;q=0`)
+fn accepts_brotli(req: &Request) -> bool {
+    let Some(header) = req.get_header_str("Accept-Encoding") else {
+        return false;
+    };
+    header.split(',').any(|coding| {
+        let mut parts = coding.split(';').map(str::trim);
+        parts.next() == Some("br") && !parts.any(|param| param.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0))
+    })
+}
 
-    // Add permission pages
-    for perm in &index_data.permissions {
-        let encoded = urlencoding::encode(&perm.name);
-        sitemap.push_str(&format!(
-            "  <url>\n    <loc>https://gcpiam.com/permissions/{}</loc>\n    <priority>0.8</priority>\n  </url>\n",
-            encoded
-        ));
-    }
+fn serve_sitemap(xml: &'static str) -> Result<Response, Error> {
+    let mut resp = Response::from_status(StatusCode::OK);
+    resp.set_header("Content-Type", "application/xml; charset=utf-8");
+    resp.set_header("Cache-Control", "public, max-age=86400");
+    resp.set_header("x-compress-hint", "on");
+    resp.set_body(xml);
+    Ok(resp)
+}
 
-    // Add role pages
-    for role in &index_data.roles {
-        let encoded = urlencoding::encode(&role.name);
-        sitemap.push_str(&format!(
-            "  <url>\n    <loc>https://gcpiam.com/roles/{}</loc>\n    <priority>0.8</priority>\n  </url>\n",
-            encoded
-        ));
-    }
+/// Part N of a sitemap split behind a sitemap index ("/sitemap-3.xml")
+fn sitemap_part(path: &str) -> Option<&'static str> {
+    let n: usize = path.strip_prefix("/sitemap-")?.strip_suffix(".xml")?.parse().ok()?;
+    SITEMAP_PARTS.get(n.checked_sub(1)?).copied()
+}
 
-    sitemap.push_str("</urlset>");
+fn serve_opensearch() -> Result<Response, Error> {
+    let mut resp = Response::from_status(StatusCode::OK);
+    resp.set_header("Content-Type", "application/opensearchdescription+xml; charset=utf-8");
+    resp.set_header("Cache-Control", "public, max-age=86400");
+    resp.set_body(OPENSEARCH_XML);
+    Ok(resp)
+}
 
+fn serve_robots() -> Result<Response, Error> {
     let mut resp = Response::from_status(StatusCode::OK);
-    resp.set_header("Content-Type", "application/xml; charset=utf-8");
+    resp.set_header("Content-Type", "text/plain; charset=utf-8");
     resp.set_header("Cache-Control", "public, max-age=86400");
-    resp.set_body(sitemap);
+    resp.set_body(ROBOTS_TXT);
     Ok(resp)
 }
 
@@ -280,28 +676,102 @@ fn serve_not_found() -> Result<Response, Error> {
     Ok(resp)
 }
 
-fn serve_permission_page(path: &str) -> Result<Response, Error> {
-    let perm_name = path.strip_prefix("/permissions/").unwrap_or("");
+/// Kind of detail page whose name wasn't in the index
+#[derive(Clone, Copy)]
+enum Missing {
+    Role,
+    Permission,
+}
+
+/// Most suggestions listed on a detail page's 404
+const MAX_SUGGESTIONS: usize = 5;
+
+/// 404 for a role or permission name that isn't in the index, listing the
+/// closest names (fuzzy search) as links, or as JSON when the client asks.
+/// Permissions are only suggested from the shards of services named like
+/// the first segment of `name`, rather than from every shard.
+fn serve_missing(req: &Request, catalog: &Catalog, kind: Missing, name: &str) -> Result<Response, Error> {
+    let options = SearchOptions {
+        limit: MAX_SUGGESTIONS,
+        ..SearchOptions::default()
+    };
+    let (label, prefix, suggestions): (&str, &str, Vec<String>) = match kind {
+        Missing::Role => (
+            "Role",
+            "/roles/",
+            catalog.routes.search_roles(name, "fuzzy", &options).into_iter().map(|r| r.name).collect(),
+        ),
+        Missing::Permission => (
+            "Permission",
+            "/permissions/",
+            {
+                let service = name.split(['.', ':', '/']).next().unwrap_or_default();
+                let shards = catalog.routes.shards_for_prefix(service);
+                catalog
+                    .view()
+                    .search_permissions_in(&shards, name, "fuzzy", &options)
+                    .map(|page| page.items.into_iter().map(|p| p.name).collect())
+                    .unwrap_or_default()
+            },
+        ),
+    };
+    let error = format!("{} '{}' not found", label, name);
+
+    let mut resp = Response::from_status(StatusCode::NOT_FOUND);
+    let wants_json = req.get_header_str("Accept").is_some_and(|accept| accept.contains("application/json"));
+    if wants_json {
+        resp.set_header("Content-Type", "application/json");
+        resp.set_body(serde_json::to_string(&NotFoundResponse { error, suggestions }).unwrap());
+        return Ok(resp);
+    }
+
+    let list = if suggestions.is_empty() {
+        String::new()
+    } else {
+        let items: String = suggestions
+            .iter()
+            .map(|s| format!(r#"<li><a href="{}{}">{}</a></li>"#, prefix, html_escape(s), html_escape(s)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("<p>Did you mean:</p>\n<ul>\n{}\n</ul>", items)
+    };
+    resp.set_header("Content-Type", "text/html; charset=utf-8");
+    resp.set_body(format!(r#"<!DOCTYPE html>
+<html><head><title>{} Not Found</title></head>
+<body style="font-family: system-ui; max-width: 600px; margin: 50px auto; padding: 20px;">
+<h1>{} Not Found</h1>
+<p><code>{}</code> is not in the index.</p>
+{}
+<p><a href="/">Back to Search</a></p>
+</body></html>"#, label, label, html_escape(name), list));
+    Ok(resp)
+}
+
+fn serve_permission_page(req: &Request, path: &str) -> Result<Response, Error> {
+    let decoded = urlencoding::decode(path.strip_prefix("/permissions/").unwrap_or("")).unwrap_or_default();
+    let perm_name = decoded.as_ref();
     if perm_name.is_empty() {
         return serve_not_found();
     }
 
-    let index: PrebuiltIndex = match bincode::deserialize(INDEX_DATA) {
-        Ok(idx) => idx,
-        Err(_) => return serve_not_found(),
+    let Ok(catalog) = catalog() else {
+        return serve_not_found();
     };
 
-    // Find the permission
-    let perm_idx = index.permission_names.iter().position(|n| n == perm_name);
-    let perm = match perm_idx {
-        Some(idx) => &index.permissions[idx],
-        None => return serve_not_found(),
+    // Find the permission's shard, then the permission
+    let shard = match catalog.view().permission_shard(perm_name) {
+        Ok(Some(shard)) => shard,
+        Ok(None) => return serve_missing(req, catalog, Missing::Permission, perm_name),
+        Err(_) => return serve_not_found(),
+    };
+    let Some(perm) = shard.permission(perm_name) else {
+        return serve_not_found();
     };
 
-    // Get roles that grant this permission
+    // Get roles that grant this permission; the shard holds all of them
     let roles_html: String = perm.granted_by_roles
         .iter()
-        .filter_map(|&idx| index.roles.get(idx as usize))
+        .filter_map(|idx| shard.roles.get(idx.to_native() as usize))
         .map(|role| {
             let stage_color = match role.stage.as_str() {
                 "GA" => "#4CAF50",
@@ -332,6 +802,7 @@ fn serve_permission_page(path: &str) -> Result<Response, Error> {
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>{} - GCP IAM Permission</title>
     <meta name="description" content="GCP IAM permission {} - granted by {} roles">
+{}
     <style>
         :root {{ --accent: #1f73e7; }}
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
@@ -342,8 +813,9 @@ fn serve_permission_page(path: &str) -> Result<Response, Error> {
         .breadcrumb a {{ color: white; text-decoration: none; }}
         .breadcrumb a:hover {{ text-decoration: underline; }}
         h1 {{ font-size: 1.5rem; word-break: break-all; }}
+        .perm-desc {{ margin-top: 10px; opacity: 0.9; font-size: 0.95rem; }}
         .meta {{ display: flex; gap: 10px; margin-top: 15px; flex-wrap: wrap; }}
-        .badge {{ padding: 4px 12px; border-radius: 4px; font-size: 0.85rem; background: rgba(255,255,255,0.2); }}
+        .badge {{ padding: 4px 12px; border-radius: 4px; font-size: 0.85rem; background: rgba(255,255,255,0.2); color: white; text-decoration: none; }}
         .section {{ background: white; border-radius: 8px; padding: 20px; margin-bottom: 20px; box-shadow: 0 1px 3px rgba(0,0,0,0.1); }}
         .section-title {{ font-size: 1.1rem; margin-bottom: 15px; color: #555; }}
         .role-card {{ padding: 12px; border: 1px solid #e0e0e0; border-radius: 6px; margin-bottom: 10px; }}
@@ -366,8 +838,9 @@ fn serve_permission_page(path: &str) -> Result<Response, Error> {
         <div class="header">
             <div class="breadcrumb"><a href="/">Search</a> / Permission</div>
             <h1>{}</h1>
+            {}
             <div class="meta">
-                <span class="badge">Service: {}</span>
+                <a class="badge" href="/services/{}">Service: {}</a>
                 <span class="badge">Resource: {}</span>
                 <span class="badge">Action: {}</span>
             </div>
@@ -376,43 +849,250 @@ fn serve_permission_page(path: &str) -> Result<Response, Error> {
             <div class="section-title">Granted by {} role(s)</div>
             {}
         </div>
+        {}
     </div>
 </body>
 </html>"#,
         html_escape(perm_name),
         html_escape(perm_name),
         perm.granted_by_roles.len(),
+        page_meta(
+            &format!("{} - GCP IAM Permission", perm_name),
+            &format!("GCP IAM permission {} - granted by {} roles", perm_name, perm.granted_by_roles.len()),
+            &format!("/permissions/{}", perm_name),
+        ),
         html_escape(perm_name),
+        perm.description.as_ref().map_or(String::new(), |d| format!(r#"<div class="perm-desc">{}</div>"#, html_escape(d))),
+        html_escape(&perm.service),
         html_escape(&perm.service),
         html_escape(&perm.resource),
         html_escape(&perm.action),
         perm.granted_by_roles.len(),
-        if roles_html.is_empty() { "<p class=\"empty\">No roles grant this permission directly.</p>".to_string() } else { roles_html }
+        if roles_html.is_empty() { "<p class=\"empty\">No roles grant this permission directly.</p>".to_string() } else { roles_html },
+        page_footer()
     );
 
     let mut resp = Response::from_status(StatusCode::OK);
     resp.set_header("Content-Type", "text/html; charset=utf-8");
     resp.set_header("Cache-Control", "public, max-age=3600");
+    // Large pages; Fastly compresses them to what the client accepts
+    resp.set_header("x-compress-hint", "on");
     resp.set_body(html);
     Ok(resp)
 }
 
-fn serve_role_page(path: &str) -> Result<Response, Error> {
-    let role_name = path.strip_prefix("/roles/").unwrap_or("");
+/// Predefined GCP roles, in name order
+fn gcp_roles(routes: &ArchivedShardRoutes) -> Vec<&ArchivedRoleRoute> {
+    let mut roles: Vec<&ArchivedRoleRoute> = routes
+        .roles
+        .iter()
+        .filter(|r| !r.custom && r.provider == DEFAULT_PROVIDER)
+        .collect();
+    roles.sort_by(|a, b| a.name.cmp(&b.name));
+    roles
+}
+
+/// Browse letter of a role: the first character after "roles/"
+fn role_letter(name: &str) -> Option<char> {
+    name.strip_prefix("roles/")?.chars().next().map(|c| c.to_ascii_lowercase())
+}
+
+/// Services with GCP permissions, in name order
+fn gcp_services(routes: &ArchivedShardRoutes) -> Vec<&ArchivedServiceRoute> {
+    routes.services.iter().filter(|s| s.provider == DEFAULT_PROVIDER).collect()
+}
+
+/// /roles/browse lists the letters roles start with, /roles/browse/{letter}
+/// the roles under one
+fn serve_roles_browse(path: &str) -> Result<Response, Error> {
+    let Ok(catalog) = catalog() else {
+        return serve_not_found();
+    };
+    let roles = gcp_roles(catalog.routes);
+    let mut letters: Vec<char> = roles.iter().filter_map(|r| role_letter(&r.name)).collect();
+    letters.dedup();
+    let letter_links = letters
+        .iter()
+        .map(|l| format!(r#"<a class="letter" href="/roles/browse/{0}">{1}</a>"#, l, l.to_ascii_uppercase()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let letter = path.strip_prefix("/roles/browse").unwrap_or("").trim_matches('/');
+    if letter.is_empty() {
+        return serve_browse_page(
+            "Roles A-Z",
+            &format!("Browse {} predefined GCP IAM roles by name", roles.len()),
+            "/roles/browse",
+            &format!(r#"<div class="letters">{}</div>"#, letter_links),
+        );
+    }
+
+    let mut chars = letter.chars();
+    let (Some(wanted), None) = (chars.next(), chars.next()) else {
+        return serve_not_found();
+    };
+    let wanted = wanted.to_ascii_lowercase();
+    let listed: Vec<&&ArchivedRoleRoute> = roles.iter().filter(|r| role_letter(&r.name) == Some(wanted)).collect();
+    if listed.is_empty() {
+        return serve_not_found();
+    }
+    let items = listed
+        .iter()
+        .map(|role| {
+            format!(
+                r#"<li><a href="/roles/{0}">{0}</a> <span class="muted">{1}</span></li>"#,
+                html_escape(&role.name),
+                html_escape(&role.title)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    serve_browse_page(
+        &format!("Roles: {}", wanted.to_ascii_uppercase()),
+        &format!("{} predefined GCP IAM roles starting with {}", listed.len(), wanted.to_ascii_uppercase()),
+        &format!("/roles/browse/{}", wanted),
+        &format!(r#"<div class="letters">{}</div><ul>{}</ul>"#, letter_links, items),
+    )
+}
+
+/// /permissions/browse lists services, /permissions/browse/{service} the
+/// service's permissions
+fn serve_permissions_browse(path: &str) -> Result<Response, Error> {
+    let Ok(catalog) = catalog() else {
+        return serve_not_found();
+    };
+    let service = path.strip_prefix("/permissions/browse").unwrap_or("").trim_matches('/');
+    if service.is_empty() {
+        let services = gcp_services(catalog.routes);
+        let items = services
+            .iter()
+            .map(|s| {
+                format!(
+                    r#"<li><a href="/permissions/browse/{0}">{0}</a> <span class="muted">{1} permissions</span></li>"#,
+                    html_escape(&s.service),
+                    s.permissions
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        return serve_browse_page(
+            "Permissions by Service",
+            &format!("Browse GCP IAM permissions of {} services", services.len()),
+            "/permissions/browse",
+            &format!(r#"<ul class="columns">{}</ul>"#, items),
+        );
+    }
+
+    let Some(shard) = catalog.routes.service(service).and_then(|position| catalog.shard(position).ok()) else {
+        return serve_not_found();
+    };
+    let permissions = shard.service_permissions(service);
+    if permissions.is_empty() {
+        return serve_not_found();
+    }
+    let items = permissions
+        .iter()
+        .map(|perm| format!(r#"<li><a href="/permissions/{0}">{0}</a></li>"#, html_escape(&perm.name)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    serve_browse_page(
+        &format!("Permissions: {}", service),
+        &format!("All {} GCP IAM permissions of the {} service", permissions.len(), service),
+        &format!("/permissions/browse/{}", service),
+        &format!(
+            r#"<p><a href="/services/{0}">{0} service overview</a></p><ul>{1}</ul>"#,
+            html_escape(service),
+            items
+        ),
+    )
+}
+
+/// Shared layout of the browse pages; `body` is trusted HTML
+fn serve_browse_page(title: &str, description: &str, path: &str, body: &str) -> Result<Response, Error> {
+    let html = format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{} - GCP IAM</title>
+    <meta name="description" content="{}">
+{}
+    <style>
+        :root {{ --accent: #1f73e7; }}
+        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
+        body {{ font-family: system-ui, sans-serif; background: #f5f5f5; color: #333; line-height: 1.6; }}
+        .container {{ max-width: 900px; margin: 0 auto; padding: 20px; }}
+        .header {{ background: linear-gradient(135deg, var(--accent), #1557b0); color: white; padding: 30px 20px; margin: -20px -20px 20px; }}
+        .breadcrumb {{ margin-bottom: 10px; opacity: 0.9; }}
+        .breadcrumb a {{ color: white; text-decoration: none; }}
+        .breadcrumb a:hover {{ text-decoration: underline; }}
+        h1 {{ font-size: 1.5rem; word-break: break-all; }}
+        .section {{ background: white; border-radius: 8px; padding: 20px; margin-bottom: 20px; box-shadow: 0 1px 3px rgba(0,0,0,0.1); }}
+        .section a {{ color: var(--accent); text-decoration: none; }}
+        .section a:hover {{ text-decoration: underline; }}
+        .letters {{ display: flex; flex-wrap: wrap; gap: 8px; margin-bottom: 15px; }}
+        .letter {{ padding: 4px 10px; border: 1px solid #e0e0e0; border-radius: 4px; font-weight: 600; }}
+        ul {{ list-style: none; }}
+        li {{ padding: 6px 0; border-bottom: 1px solid #eee; font-family: monospace; font-size: 0.9rem; word-break: break-all; }}
+        li:last-child {{ border-bottom: none; }}
+        .columns {{ columns: 3 200px; }}
+        .muted {{ color: #666; font-family: system-ui, sans-serif; }}
+        @media (prefers-color-scheme: dark) {{
+            body {{ background: #1a1a1a; color: #e0e0e0; }}
+            .section {{ background: #2d2d2d; }}
+            li, .letter {{ border-color: #444; }}
+            .muted {{ color: #aaa; }}
+        }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <div class="breadcrumb"><a href="/">Search</a> / <a href="/roles/browse">Roles</a> / <a href="/permissions/browse">Permissions</a></div>
+            <h1>{}</h1>
+        </div>
+        <div class="section">
+            {}
+        </div>
+        {}
+    </div>
+</body>
+</html>"#,
+        html_escape(title),
+        html_escape(description),
+        page_meta(&format!("{} - GCP IAM", title), description, path),
+        html_escape(title),
+        body,
+        page_footer()
+    );
+
+    let mut resp = Response::from_status(StatusCode::OK);
+    resp.set_header("Content-Type", "text/html; charset=utf-8");
+    resp.set_header("Cache-Control", "public, max-age=3600");
+    // Large pages; Fastly compresses them to what the client accepts
+    resp.set_header("x-compress-hint", "on");
+    resp.set_body(html);
+    Ok(resp)
+}
+
+/// Rows of a role page's permissions list before it continues on ?page=2
+const PERMISSIONS_PER_PAGE: usize = 500;
+
+fn serve_role_page(req: &Request, path: &str) -> Result<Response, Error> {
+    let decoded = urlencoding::decode(path.strip_prefix("/roles/").unwrap_or("")).unwrap_or_default();
+    let role_name = decoded.as_ref();
     if role_name.is_empty() {
         return serve_not_found();
     }
 
-    let index: PrebuiltIndex = match bincode::deserialize(INDEX_DATA) {
-        Ok(idx) => idx,
-        Err(_) => return serve_not_found(),
+    let Ok(catalog) = catalog() else {
+        return serve_not_found();
     };
 
     // Find the role
-    let role_idx = index.role_names.iter().position(|n| n == role_name);
-    let role = match role_idx {
-        Some(idx) => &index.roles[idx],
-        None => return serve_not_found(),
+    let role = match catalog.routes.role(role_name) {
+        Some(role) => role,
+        None => return serve_missing(req, catalog, Missing::Role, role_name),
     };
 
     let stage_color = match role.stage.as_str() {
@@ -422,8 +1102,35 @@ fn serve_role_page(path: &str) -> Result<Response, Error> {
         _ => "#9E9E9E",
     };
 
-    // Generate permissions list
-    let perms_html: String = role.included_permissions
+    // One page (?page=N) of the permissions list; roles/owner has thousands
+    let total = role.permission_count.to_native() as usize;
+    let pages = total.div_ceil(PERMISSIONS_PER_PAGE).max(1);
+    let page = match query_params(req).get("page") {
+        None => 1,
+        Some(raw) => match raw.parse::<usize>() {
+            Ok(page) if (1..=pages).contains(&page) => page,
+            _ => return serve_not_found(),
+        },
+    };
+    let start = (page - 1) * PERMISSIONS_PER_PAGE;
+    let end = (start + PERMISSIONS_PER_PAGE).min(total);
+    let page_path = if page == 1 { format!("/roles/{}", role.name) } else { format!("/roles/{}?page={}", role.name, page) };
+    let pager = if pages == 1 {
+        String::new()
+    } else {
+        let prev = if page > 1 { format!(r#"<a href="?page={}">&larr; Previous</a>"#, page - 1) } else { String::new() };
+        let next = if page < pages { format!(r#"<a href="?page={}">Next &rarr;</a>"#, page + 1) } else { String::new() };
+        format!(
+            r#"<div class="pager">{}<span>Page {} of {} ({}&ndash;{} of {})</span>{}</div>"#,
+            prev, page, pages, start + 1, end, total, next
+        )
+    };
+
+    // Generate permissions list, reading only the shards the page spans
+    let Ok(permissions) = catalog.view().role_permissions(role, start..end) else {
+        return serve_not_found();
+    };
+    let perms_html: String = permissions
         .iter()
         .map(|perm| {
             format!(
@@ -442,6 +1149,7 @@ fn serve_role_page(path: &str) -> Result<Response, Error> {
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>{} - GCP IAM Role</title>
     <meta name="description" content="{} - {}">
+{}
     <style>
         :root {{ --accent: #1f73e7; }}
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
@@ -462,11 +1170,13 @@ fn serve_role_page(path: &str) -> Result<Response, Error> {
         .perm-item:last-child {{ border-bottom: none; }}
         .perm-name {{ color: var(--accent); text-decoration: none; font-family: monospace; font-size: 0.9rem; }}
         .perm-name:hover {{ text-decoration: underline; }}
+        .pager {{ display: flex; gap: 15px; justify-content: space-between; padding: 10px 12px; color: #666; font-size: 0.9rem; }}
+        .pager a {{ color: var(--accent); text-decoration: none; }}
         @media (prefers-color-scheme: dark) {{
             body {{ background: #1a1a1a; color: #e0e0e0; }}
             .section {{ background: #2d2d2d; }}
             .perm-item {{ border-color: #444; }}
-            .section-title {{ color: #aaa; }}
+            .section-title, .pager {{ color: #aaa; }}
         }}
     </style>
 </head>
@@ -485,25 +1195,359 @@ fn serve_role_page(path: &str) -> Result<Response, Error> {
         <div class="section">
             <div class="section-title">Included Permissions</div>
             {}
+            {}
+            {}
         </div>
+        {}
     </div>
 </body>
 </html>"#,
         html_escape(&role.name),
         html_escape(&role.title),
         html_escape(&role.description),
+        page_meta(
+            &format!("{} - GCP IAM Role", role.name),
+            &format!("{} - {}", role.title, role.description),
+            &page_path,
+        ),
         html_escape(&role.name),
         html_escape(&role.title),
         html_escape(&role.description),
         stage_color,
         html_escape(&role.stage),
-        role.included_permissions.len(),
-        perms_html
+        total,
+        pager,
+        perms_html,
+        pager,
+        page_footer()
     );
 
     let mut resp = Response::from_status(StatusCode::OK);
     resp.set_header("Content-Type", "text/html; charset=utf-8");
     resp.set_header("Cache-Control", "public, max-age=3600");
+    // Large pages; Fastly compresses them to what the client accepts
+    resp.set_header("x-compress-hint", "on");
+    resp.set_body(html);
+    Ok(resp)
+}
+
+/// When the served dataset was fetched from the IAM API
+fn page_footer() -> String {
+    match last_updated().get(..10) {
+        Some(date) => format!(
+            r#"<div style="text-align:center; color:#999; font-size:0.85rem; margin-top:20px;">Data updated {}</div>"#,
+            html_escape(date)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Canonical link, so search engines index one URL per page, and OpenGraph
+/// and Twitter card tags so shared links unfurl with a title and summary (in
+/// Slack, social media, ...)
+fn page_meta(title: &str, description: &str, path: &str) -> String {
+    format!(
+        r#"    <link rel="canonical" href="{url}">
+    <link rel="search" type="application/opensearchdescription+xml" title="GCP IAM Search" href="/opensearch.xml">
+    <meta property="og:type" content="website">
+    <meta property="og:site_name" content="GCP IAM Search">
+    <meta property="og:title" content="{title}">
+    <meta property="og:description" content="{description}">
+    <meta property="og:url" content="{url}">
+    <meta name="twitter:card" content="summary">
+    <meta name="twitter:title" content="{title}">
+    <meta name="twitter:description" content="{description}">"#,
+        title = html_escape(title),
+        description = html_escape(description),
+        url = html_escape(&format!("{}{}", BASE_URL, path)),
+    )
+}
+
+fn serve_service_page(path: &str) -> Result<Response, Error> {
+    let decoded = urlencoding::decode(path.strip_prefix("/services/").unwrap_or("")).unwrap_or_default();
+    let service = decoded.as_ref();
+    if service.is_empty() {
+        return serve_not_found();
+    }
+
+    let Ok(catalog) = catalog() else {
+        return serve_not_found();
+    };
+
+    let Some(shard) = catalog.routes.service(service).and_then(|position| catalog.shard(position).ok()) else {
+        return serve_not_found();
+    };
+    let permissions = shard.service_permissions(service);
+    if permissions.is_empty() {
+        return serve_not_found();
+    }
+    let roles = catalog.routes.service_roles(service);
+
+    // Group permissions by resource, keeping name order within each group
+    let mut by_resource: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for perm in &permissions {
+        by_resource.entry(perm.resource.as_str()).or_default().push(perm.name.as_str());
+    }
+    let perms_html: String = by_resource
+        .iter()
+        .map(|(resource, names)| {
+            let items: String = names
+                .iter()
+                .map(|name| {
+                    format!(
+                        r#"<div class="perm-item"><a href="/permissions/{}" class="perm-name">{}</a></div>"#,
+                        html_escape(name),
+                        html_escape(name)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                r#"<div class="resource"><div class="resource-name">{} ({})</div>{}</div>"#,
+                html_escape(resource),
+                names.len(),
+                items
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let roles_html: String = roles
+        .iter()
+        .map(|role| {
+            format!(
+                r#"<div class="role-item"><a href="/roles/{}" class="role-name">{}</a> <span class="role-title">{}</span></div>"#,
+                html_escape(&role.name),
+                html_escape(&role.name),
+                html_escape(&role.title)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let description = format!(
+        "GCP IAM permissions and roles of the {} service - {} permissions, {} roles",
+        service,
+        permissions.len(),
+        roles.len()
+    );
+    let html = format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{} - GCP IAM Service</title>
+    <meta name="description" content="{}">
+{}
+    <style>
+        :root {{ --accent: #1f73e7; }}
+        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
+        body {{ font-family: system-ui, sans-serif; background: #f5f5f5; color: #333; line-height: 1.6; }}
+        .container {{ max-width: 900px; margin: 0 auto; padding: 20px; }}
+        .header {{ background: linear-gradient(135deg, var(--accent), #1557b0); color: white; padding: 30px 20px; margin: -20px -20px 20px; }}
+        .breadcrumb {{ margin-bottom: 10px; opacity: 0.9; }}
+        .breadcrumb a {{ color: white; text-decoration: none; }}
+        .breadcrumb a:hover {{ text-decoration: underline; }}
+        h1 {{ font-size: 1.5rem; word-break: break-all; }}
+        .meta {{ display: flex; gap: 10px; margin-top: 15px; flex-wrap: wrap; }}
+        .badge {{ padding: 4px 12px; border-radius: 4px; font-size: 0.85rem; background: rgba(255,255,255,0.2); }}
+        .section {{ background: white; border-radius: 8px; padding: 20px; margin-bottom: 20px; box-shadow: 0 1px 3px rgba(0,0,0,0.1); }}
+        .section-title {{ font-size: 1.1rem; margin-bottom: 15px; color: #555; }}
+        .resource {{ margin-bottom: 15px; }}
+        .resource-name {{ font-weight: 600; padding: 4px 12px; }}
+        .perm-item, .role-item {{ padding: 8px 12px; border-bottom: 1px solid #eee; }}
+        .perm-item:last-child, .role-item:last-child {{ border-bottom: none; }}
+        .perm-name, .role-name {{ color: var(--accent); text-decoration: none; font-family: monospace; font-size: 0.9rem; }}
+        .perm-name:hover, .role-name:hover {{ text-decoration: underline; }}
+        .role-title {{ color: #666; font-size: 0.9rem; }}
+        .empty {{ color: #999; font-style: italic; }}
+        @media (prefers-color-scheme: dark) {{
+            body {{ background: #1a1a1a; color: #e0e0e0; }}
+            .section {{ background: #2d2d2d; }}
+            .perm-item, .role-item {{ border-color: #444; }}
+            .section-title, .role-title {{ color: #aaa; }}
+        }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <div class="breadcrumb"><a href="/">Search</a> / Service</div>
+            <h1>{}</h1>
+            <div class="meta">
+                <span class="badge">{} permissions</span>
+                <span class="badge">{} resources</span>
+                <span class="badge">{} roles</span>
+            </div>
+        </div>
+        <div class="section">
+            <div class="section-title">Roles</div>
+            {}
+        </div>
+        <div class="section">
+            <div class="section-title">Permissions by resource</div>
+            {}
+        </div>
+        {}
+    </div>
+</body>
+</html>"#,
+        html_escape(service),
+        html_escape(&description),
+        page_meta(&format!("{} - GCP IAM Service", service), &description, &format!("/services/{}", service)),
+        html_escape(service),
+        permissions.len(),
+        by_resource.len(),
+        roles.len(),
+        if roles_html.is_empty() { "<p class=\"empty\">No predefined roles are scoped to this service.</p>".to_string() } else { roles_html },
+        perms_html,
+        page_footer()
+    );
+
+    let mut resp = Response::from_status(StatusCode::OK);
+    resp.set_header("Content-Type", "text/html; charset=utf-8");
+    resp.set_header("Cache-Control", "public, max-age=3600");
+    // Large pages; Fastly compresses them to what the client accepts
+    resp.set_header("x-compress-hint", "on");
+    resp.set_body(html);
+    Ok(resp)
+}
+
+fn serve_compare_page(req: &Request) -> Result<Response, Error> {
+    let comparison = match compare_roles(req) {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            let mut resp = Response::from_status(StatusCode::BAD_REQUEST);
+            resp.set_header("Content-Type", "text/html; charset=utf-8");
+            resp.set_body(format!(r#"<!DOCTYPE html>
+<html><head><title>Cannot Compare Roles</title></head>
+<body style="font-family: system-ui; max-width: 600px; margin: 50px auto; padding: 20px;">
+<h1>Cannot Compare Roles</h1>
+<p>{}</p>
+<p><a href="/">Back to Search</a></p>
+</body></html>"#, html_escape(&e)));
+            return Ok(resp);
+        }
+    };
+
+    let column = |title: &str, permissions: &[String]| {
+        let items: String = permissions
+            .iter()
+            .map(|perm| {
+                format!(
+                    r#"<div class="perm-item"><a href="/permissions/{}" class="perm-name">{}</a></div>"#,
+                    html_escape(perm),
+                    html_escape(perm)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            r#"<div class="section"><div class="section-title">{} ({})</div>{}</div>"#,
+            title,
+            permissions.len(),
+            if items.is_empty() { "<p class=\"empty\">None</p>".to_string() } else { items }
+        )
+    };
+    let first_link = format!(r#"<a href="/roles/{0}">{0}</a>"#, html_escape(&comparison.first));
+    let second_link = format!(r#"<a href="/roles/{0}">{0}</a>"#, html_escape(&comparison.second));
+    let columns = [
+        column(&format!("Only in {}", first_link), &comparison.only_in_first),
+        column("Shared", &comparison.shared),
+        column(&format!("Only in {}", second_link), &comparison.only_in_second),
+    ]
+    .join("\n");
+
+    let title = format!("{} vs {}", comparison.first, comparison.second);
+    let description = format!(
+        "Permissions of GCP IAM roles {} and {} compared - {} shared, {} only in {}, {} only in {}",
+        comparison.first,
+        comparison.second,
+        comparison.shared.len(),
+        comparison.only_in_first.len(),
+        comparison.first,
+        comparison.only_in_second.len(),
+        comparison.second
+    );
+    let html = format!(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{} - GCP IAM Role Comparison</title>
+    <meta name="description" content="{}">
+{}
+    <style>
+        :root {{ --accent: #1f73e7; }}
+        * {{ margin: 0; padding: 0; box-sizing: border-box; }}
+        body {{ font-family: system-ui, sans-serif; background: #f5f5f5; color: #333; line-height: 1.6; }}
+        .container {{ max-width: 1200px; margin: 0 auto; padding: 20px; }}
+        .header {{ background: linear-gradient(135deg, var(--accent), #1557b0); color: white; padding: 30px 20px; margin: -20px -20px 20px; }}
+        .breadcrumb {{ margin-bottom: 10px; opacity: 0.9; }}
+        .breadcrumb a {{ color: white; text-decoration: none; }}
+        .breadcrumb a:hover {{ text-decoration: underline; }}
+        h1 {{ font-size: 1.5rem; word-break: break-all; }}
+        .meta {{ display: flex; gap: 10px; margin-top: 15px; flex-wrap: wrap; }}
+        .badge {{ padding: 4px 12px; border-radius: 4px; font-size: 0.85rem; background: rgba(255,255,255,0.2); }}
+        .columns {{ display: grid; grid-template-columns: repeat(3, 1fr); gap: 20px; align-items: start; }}
+        .section {{ background: white; border-radius: 8px; padding: 20px; box-shadow: 0 1px 3px rgba(0,0,0,0.1); min-width: 0; }}
+        .section-title {{ font-size: 1.1rem; margin-bottom: 15px; color: #555; word-break: break-all; }}
+        .section-title a {{ color: var(--accent); text-decoration: none; }}
+        .perm-item {{ padding: 8px 12px; border-bottom: 1px solid #eee; word-break: break-all; }}
+        .perm-item:last-child {{ border-bottom: none; }}
+        .perm-name {{ color: var(--accent); text-decoration: none; font-family: monospace; font-size: 0.9rem; }}
+        .perm-name:hover {{ text-decoration: underline; }}
+        .empty {{ color: #999; font-style: italic; }}
+        @media (max-width: 800px) {{ .columns {{ grid-template-columns: 1fr; }} }}
+        @media (prefers-color-scheme: dark) {{
+            body {{ background: #1a1a1a; color: #e0e0e0; }}
+            .section {{ background: #2d2d2d; }}
+            .perm-item {{ border-color: #444; }}
+            .section-title {{ color: #aaa; }}
+        }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <div class="breadcrumb"><a href="/">Search</a> / Compare</div>
+            <h1>{}</h1>
+            <div class="meta">
+                <span class="badge">Similarity: {:.2}</span>
+                <span class="badge">{} shared</span>{}
+            </div>
+        </div>
+        <div class="columns">
+            {}
+        </div>
+        {}
+    </div>
+</body>
+</html>"#,
+        html_escape(&title),
+        html_escape(&description),
+        page_meta(
+            &format!("{} - GCP IAM Role Comparison", title),
+            &description,
+            &format!("/compare?roles={},{}", comparison.first, comparison.second),
+        ),
+        html_escape(&title),
+        comparison.similarity,
+        comparison.shared.len(),
+        comparison
+            .inclusion()
+            .map(|(outer, inner)| format!(r#"
+                <span class="badge">{} includes {}</span>"#, html_escape(outer), html_escape(inner)))
+            .unwrap_or_default(),
+        columns,
+        page_footer()
+    );
+
+    let mut resp = Response::from_status(StatusCode::OK);
+    resp.set_header("Content-Type", "text/html; charset=utf-8");
+    resp.set_header("Cache-Control", "public, max-age=3600");
+    // Large pages; Fastly compresses them to what the client accepts
+    resp.set_header("x-compress-hint", "on");
     resp.set_body(html);
     Ok(resp)
 }
@@ -525,36 +1569,34 @@ fn handle_health() -> Result<String, String> {
 }
 
 fn handle_stats() -> Result<String, String> {
-    let index: PrebuiltIndex = bincode::deserialize(INDEX_DATA).map_err(|e| e.to_string())?;
+    let routes = catalog()?.routes;
 
     serde_json::to_string(&StatsResponse {
         success: true,
         data: StatsData {
-            total_permissions: index.permissions.len(),
-            total_roles: index.roles.len(),
+            total_permissions: routes.permission_count(),
+            total_roles: routes.roles.len(),
             indexed: true,
             version: "0.1.0-edge".to_string(),
+            last_updated: last_updated().to_string(),
         },
     })
     .map_err(|e| e.to_string())
 }
 
 fn handle_info() -> Result<String, String> {
-    let index: PrebuiltIndex = bincode::deserialize(INDEX_DATA).map_err(|e| e.to_string())?;
+    let routes = catalog()?.routes;
 
     serde_json::to_string(&MetadataResponse {
-        last_updated: LAST_UPDATED.to_string(),
-        total_permissions: index.permissions.len() as u32,
-        total_roles: index.roles.len() as u32,
+        last_updated: last_updated().to_string(),
+        total_permissions: routes.permission_count() as u32,
+        total_roles: routes.roles.len() as u32,
     })
     .map_err(|e| e.to_string())
 }
 
-fn handle_search(req: &Request) -> Result<String, String> {
-    let query_string = req.get_query_str().unwrap_or("");
-    let params: HashMap<String, String> = url::form_urlencoded::parse(query_string.as_bytes())
-        .into_owned()
-        .collect();
+fn handle_search(req: &Request, log: &mut RequestLog) -> Result<String, String> {
+    let params = query_params(req);
 
     let query = params.get("q").map(|s: &String| s.as_str()).unwrap_or("").trim();
     if query.is_empty() {
@@ -565,118 +1607,91 @@ fn handle_search(req: &Request) -> Result<String, String> {
     }
 
     let mode = params.get("mode").map(|s: &String| s.as_str()).unwrap_or("prefix");
+    let limit = bounded_param(&params, "limit", DEFAULT_LIMIT, 1..=MAX_LIMIT)?;
+    let offset = bounded_param(&params, "offset", 0, 0..=MAX_OFFSET)?;
 
-    let index: PrebuiltIndex = bincode::deserialize(INDEX_DATA).map_err(|e| e.to_string())?;
+    let catalog = catalog()?;
 
-    let permissions = search_permissions(&index, query, mode);
-    let roles = search_roles(&index, query, mode);
+    let options = SearchOptions {
+        limit,
+        offset,
+        ..SearchOptions::default()
+    };
+    let permissions = catalog.view().search_permissions_page(query, mode, &options)?;
+    let roles = catalog.routes.search_roles_page(query, mode, &options);
+    log.query = Some(query.to_string());
+    log.mode = Some(mode.to_string());
+    log.permissions = Some(permissions.total);
+    log.roles = Some(roles.total);
 
     serde_json::to_string(&SearchResponse {
         success: true,
         data: SearchData {
-            permissions,
-            roles,
+            permissions: permissions.items,
+            roles: roles.items,
             query: query.to_string(),
             mode: mode.to_string(),
+            total_permissions: permissions.total,
+            total_roles: roles.total,
+            limit,
+            offset,
         },
     })
     .map_err(|e| e.to_string())
 }
 
-fn search_permissions(index: &PrebuiltIndex, query: &str, mode: &str) -> Vec<PermissionSearchResult> {
-    let query_lower = query.to_lowercase();
-    let mut results: Vec<(usize, f64)> = Vec::new();
-
-    match mode {
-        "exact" => {
-            if let Ok(idx) = index.permission_names.binary_search(&query.to_string()) {
-                results.push((idx, 1.0));
-            }
-        }
-        "prefix" => {
-            for (idx, name_lower) in index.permission_names_lower.iter().enumerate() {
-                if name_lower.starts_with(&query_lower) {
-                    results.push((idx, 0.9));
-                }
-            }
-        }
-        _ => {
-            for (idx, name_lower) in index.permission_names_lower.iter().enumerate() {
-                if name_lower.contains(&query_lower) {
-                    results.push((idx, 0.85));
-                }
-            }
-        }
-    }
-
-    results
-        .into_iter()
-        .take(20)
-        .map(|(idx, score)| {
-            let perm = &index.permissions[idx];
-            let granted_by_roles: Vec<RoleSummary> = perm
-                .granted_by_roles
-                .iter()
-                .take(5)
-                .filter_map(|&role_idx| index.role_summaries.get(role_idx as usize).cloned())
-                .collect();
-
-            PermissionSearchResult {
-                name: perm.name.clone(),
-                service: perm.service.clone(),
-                resource: perm.resource.clone(),
-                action: perm.action.clone(),
-                score,
-                granted_by_roles,
-            }
-        })
-        .collect()
+fn handle_compare(req: &Request) -> Result<String, String> {
+    serde_json::to_string(&CompareResponse {
+        success: true,
+        data: compare_roles(req)?,
+    })
+    .map_err(|e| e.to_string())
 }
 
-fn search_roles(index: &PrebuiltIndex, query: &str, mode: &str) -> Vec<RoleSearchResult> {
-    let query_lower = query.to_lowercase();
-    let mut results: Vec<(usize, f64)> = Vec::new();
+/// Comparison of the two roles named by `?roles=a,b`
+fn compare_roles(req: &Request) -> Result<RoleComparison, String> {
+    let params = query_params(req);
 
-    match mode {
-        "exact" => {
-            if let Ok(idx) = index.role_names.binary_search(&query.to_string()) {
-                results.push((idx, 1.0));
-            }
-        }
-        "prefix" => {
-            for (idx, name_lower) in index.role_names_lower.iter().enumerate() {
-                if name_lower.starts_with(&query_lower)
-                    || index.role_titles_lower[idx].starts_with(&query_lower)
-                {
-                    results.push((idx, 0.9));
-                }
-            }
-        }
-        _ => {
-            for (idx, name_lower) in index.role_names_lower.iter().enumerate() {
-                if name_lower.contains(&query_lower)
-                    || index.role_titles_lower[idx].contains(&query_lower)
-                {
-                    results.push((idx, 0.85));
-                }
-            }
-        }
-    }
+    let names: Vec<&str> = params
+        .get("roles")
+        .map(|s| s.split(',').map(str::trim).filter(|n| !n.is_empty()).collect())
+        .unwrap_or_default();
+    let [first, second] = names[..] else {
+        return Err("Query parameter 'roles' must name two roles (roles=a,b)".to_string());
+    };
 
-    results
-        .into_iter()
-        .take(20)
-        .map(|(idx, score)| {
-            let role = &index.roles[idx];
-            RoleSearchResult {
-                name: role.name.clone(),
-                title: role.title.clone(),
-                description: role.description.clone(),
-                stage: role.stage.clone(),
-                score,
-                permission_count: role.included_permissions.len(),
-                sample_permissions: role.included_permissions.iter().take(5).cloned().collect(),
-            }
-        })
+    let catalog = catalog()?;
+    let role = |name: &str| catalog.routes.role(name).ok_or_else(|| format!("Role '{}' not found", name));
+    let (a, b) = (role(first)?, role(second)?);
+    let view = catalog.view();
+    let (a_permissions, b_permissions) = (view.role_permissions(a, 0..usize::MAX)?, view.role_permissions(b, 0..usize::MAX)?);
+    Ok(RoleComparison::new(
+        &a.name,
+        a_permissions.iter().map(|p| p.as_str()),
+        &b.name,
+        b_permissions.iter().map(|p| p.as_str()),
+    ))
+}
+
+fn query_params(req: &Request) -> HashMap<String, String> {
+    let query_string = req.get_query_str().unwrap_or("");
+    url::form_urlencoded::parse(query_string.as_bytes())
+        .into_owned()
         .collect()
 }
+
+/// An integer query parameter within `range`, or `default` when absent
+fn bounded_param(
+    params: &HashMap<String, String>,
+    name: &str,
+    default: usize,
+    range: std::ops::RangeInclusive<usize>,
+) -> Result<usize, String> {
+    let Some(raw) = params.get(name) else {
+        return Ok(default);
+    };
+    raw.parse::<usize>()
+        .ok()
+        .filter(|value| range.contains(value))
+        .ok_or_else(|| format!("{} must be an integer between {} and {}", name, range.start(), range.end()))
+}
@@ -10,13 +10,21 @@ export interface RoleSummary {
     stage: string;
 }
 
+export interface ResultWarning {
+    code: string;
+    message: string;
+    replacement?: string;
+}
+
 export interface Permission {
     name: string;
     service: string;
     resource: string;
     action: string;
+    description?: string;
     score: number;
     granted_by_roles: RoleSummary[];
+    warnings?: ResultWarning[];
 }
 
 export interface Role {
@@ -27,6 +35,8 @@ export interface Role {
     score: number;
     permission_count: number;
     sample_permissions: string[];
+    deprecated?: boolean;
+    warnings?: ResultWarning[];
 }
 
 export interface SearchResults {
@@ -91,6 +91,52 @@
     }
   };
 
+  // public/offline.ts
+  var OfflineSearchClient = class {
+    constructor(pkgUrl = "/pkg") {
+      __publicField(this, "pkgUrl");
+      __publicField(this, "index", null);
+      __publicField(this, "loading", null);
+      this.pkgUrl = pkgUrl.replace(/\/$/, "");
+    }
+    /**
+     * Load the WASM module and index (once)
+     */
+    async load() {
+      if (this.index) {
+        return this.index;
+      }
+      if (!this.loading) {
+        this.loading = (async () => {
+          const moduleUrl = `${this.pkgUrl}/gcpiam_wasm.js`;
+          const wasm = await import(
+            /* webpackIgnore: true */
+            moduleUrl
+          );
+          await wasm.default();
+          const response = await fetch(`${this.pkgUrl}/index.bin`);
+          if (!response.ok) {
+            throw new Error(`HTTP ${response.status}: ${response.statusText}`);
+          }
+          const bytes = new Uint8Array(await response.arrayBuffer());
+          this.index = new wasm.WasmIndex(bytes);
+          return this.index;
+        })();
+      }
+      return this.loading;
+    }
+    /**
+     * Search for permissions and roles without any network round-trip
+     */
+    async search(query, mode = "fuzzy", limit = 20) {
+      const index = await this.load();
+      return {
+        permissions: JSON.parse(index.searchPermissions(query, mode, limit)),
+        roles: JSON.parse(index.searchRoles(query, mode, limit))
+      };
+    }
+  };
+
   // public/ui.ts
   var SearchUI = class {
     constructor() {
@@ -225,6 +271,8 @@
                 <span class="result-badge action">${this.escapeHtml(perm.action)}</span>
                 <span class="result-score">Match: ${(perm.score * 100).toFixed(0)}%</span>
             </div>
+            ${perm.description ? `<div class="permission-description">${this.escapeHtml(perm.description)}</div>` : ''}
+            ${this.warningsHtml(perm.warnings)}
             ${rolesHtml}
         `;
       div.addEventListener("click", (e) => {
@@ -262,6 +310,7 @@
                 </span>
                 <span class="result-score">Match: ${(role.score * 100).toFixed(0)}%</span>
             </div>
+            ${this.warningsHtml(role.warnings)}
             ${permissionsHtml}
         `;
       div.addEventListener("click", (e) => {
@@ -323,6 +372,14 @@
         }
       });
     }
+    /**
+     * Deprecation notices, linking the suggested replacement role
+     */
+    warningsHtml(warnings) {
+      return (warnings || []).map(
+        (w) => `<div class="result-warning">\u26A0 ${this.escapeHtml(w.message)}${w.replacement ? ` <a href="/roles/${this.escapeHtml(w.replacement)}" class="result-link">View replacement</a>` : ""}</div>`
+      ).join("");
+    }
     /**
      * Escape HTML special characters
      */
@@ -430,6 +487,7 @@
   // public/app.ts
   var CONFIG = {
     API_BASE_URL: "https://gcpiam.com/api/v1",
+    WASM_PKG_URL: "/pkg",
     SEARCH_DEBOUNCE_MS: 150,
     RESULT_LIMIT: 20,
     FUZZY_THRESHOLD: 0.5
@@ -437,6 +495,7 @@
   async function initializeApp() {
     try {
       const apiClient = new SearchClient(CONFIG.API_BASE_URL);
+      const offlineClient = new OfflineSearchClient(CONFIG.WASM_PKG_URL);
       const ui = new SearchUI();
       const searchManager = new SearchManager({
         debounceMs: CONFIG.SEARCH_DEBOUNCE_MS,
@@ -473,7 +532,10 @@
         ui.showLoading();
         try {
           const mode = searchManager.getCurrentMode();
-          const results = await apiClient.search(query, mode);
+          const results = await apiClient.search(query, mode).catch((error) => {
+            console.warn("API search failed, searching offline:", error);
+            return offlineClient.search(query, mode, CONFIG.RESULT_LIMIT);
+          });
           if (results.permissions.length === 0 && results.roles.length === 0) {
             ui.showEmptyState();
           } else {
@@ -525,6 +587,11 @@
         }
       });
       searchInput.focus();
+      const initialQuery = new URLSearchParams(window.location.search).get("q");
+      if (initialQuery) {
+        searchInput.value = initialQuery;
+        searchInput.dispatchEvent(new Event("input"));
+      }
       console.log("\u2713 GCP IAM Search initialized");
     } catch (error) {
       console.error("Failed to initialize app:", error);
@@ -3,12 +3,14 @@
 // ============================================
 
 import { SearchClient } from './api';
+import { OfflineSearchClient } from './offline';
 import { SearchUI } from './ui';
 import { SearchManager } from './search';
 
 // Configuration
 const CONFIG = {
     API_BASE_URL: 'https://gcpiam.com/api/v1',
+    WASM_PKG_URL: '/pkg',
     SEARCH_DEBOUNCE_MS: 150,
     RESULT_LIMIT: 20,
     FUZZY_THRESHOLD: 0.5,
@@ -19,6 +21,7 @@ async function initializeApp() {
     try {
         // Create components
         const apiClient = new SearchClient(CONFIG.API_BASE_URL);
+        const offlineClient = new OfflineSearchClient(CONFIG.WASM_PKG_URL);
         const ui = new SearchUI();
         const searchManager = new SearchManager({
             debounceMs: CONFIG.SEARCH_DEBOUNCE_MS,
@@ -64,7 +67,11 @@ async function initializeApp() {
 
             try {
                 const mode = searchManager.getCurrentMode();
-                const results = await apiClient.search(query, mode);
+                // Search the prebuilt index in the browser when the API is unreachable
+                const results = await apiClient.search(query, mode).catch((error) => {
+                    console.warn('API search failed, searching offline:', error);
+                    return offlineClient.search(query, mode, CONFIG.RESULT_LIMIT);
+                });
 
                 if (results.permissions.length === 0 && results.roles.length === 0) {
                     ui.showEmptyState();
@@ -136,6 +143,13 @@ async function initializeApp() {
         // Set initial focus
         searchInput.focus();
 
+        // Run a search passed in the URL (/?q=..., the OpenSearch template)
+        const initialQuery = new URLSearchParams(window.location.search).get('q');
+        if (initialQuery) {
+            searchInput.value = initialQuery;
+            searchInput.dispatchEvent(new Event('input'));
+        }
+
         console.log('✓ GCP IAM Search initialized');
     } catch (error) {
         console.error('Failed to initialize app:', error);
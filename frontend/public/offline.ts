@@ -0,0 +1,69 @@
+// ============================================
+// Offline Search - WASM search core in the browser
+// ============================================
+
+import type { Permission, Role, SearchMode, SearchResults } from './api';
+
+// Bindings generated by wasm-bindgen (see scripts/build_wasm.sh)
+interface WasmIndex {
+    searchPermissions(query: string, mode: string, limit: number): string;
+    searchRoles(query: string, mode: string, limit: number): string;
+    readonly permissionCount: number;
+    readonly roleCount: number;
+}
+
+interface WasmModule {
+    default(input?: string | URL): Promise<unknown>;
+    WasmIndex: new (bytes: Uint8Array) => WasmIndex;
+}
+
+/**
+ * Searches a prebuilt index entirely client-side, with the same
+ * interface as the API-backed SearchClient
+ */
+export class OfflineSearchClient {
+    private pkgUrl: string;
+    private index: WasmIndex | null = null;
+    private loading: Promise<WasmIndex> | null = null;
+
+    constructor(pkgUrl: string = '/pkg') {
+        this.pkgUrl = pkgUrl.replace(/\/$/, ''); // Remove trailing slash
+    }
+
+    /**
+     * Load the WASM module and index (once)
+     */
+    async load(): Promise<WasmIndex> {
+        if (this.index) {
+            return this.index;
+        }
+        if (!this.loading) {
+            this.loading = (async () => {
+                const moduleUrl = `${this.pkgUrl}/gcpiam_wasm.js`;
+                const wasm: WasmModule = await import(/* webpackIgnore: true */ moduleUrl);
+                await wasm.default();
+
+                const response = await fetch(`${this.pkgUrl}/index.bin`);
+                if (!response.ok) {
+                    throw new Error(`HTTP ${response.status}: ${response.statusText}`);
+                }
+                const bytes = new Uint8Array(await response.arrayBuffer());
+
+                this.index = new wasm.WasmIndex(bytes);
+                return this.index;
+            })();
+        }
+        return this.loading;
+    }
+
+    /**
+     * Search for permissions and roles without any network round-trip
+     */
+    async search(query: string, mode: SearchMode = 'fuzzy', limit: number = 20): Promise<SearchResults> {
+        const index = await this.load();
+        return {
+            permissions: JSON.parse(index.searchPermissions(query, mode, limit)) as Permission[],
+            roles: JSON.parse(index.searchRoles(query, mode, limit)) as Role[],
+        };
+    }
+}
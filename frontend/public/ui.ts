@@ -2,7 +2,7 @@
 // UI Management - DOM Manipulation and Rendering
 // ============================================
 
-import { SearchResults, Permission, Role } from './api';
+import { SearchResults, Permission, Role, ResultWarning } from './api';
 
 export class SearchUI {
     private selectedIndex: number = -1;
@@ -168,6 +168,8 @@ export class SearchUI {
                 <span class="result-badge action">${this.escapeHtml(perm.action)}</span>
                 <span class="result-score">Match: ${(perm.score * 100).toFixed(0)}%</span>
             </div>
+            ${perm.description ? `<div class="permission-description">${this.escapeHtml(perm.description)}</div>` : ''}
+            ${this.warningsHtml(perm.warnings)}
             ${rolesHtml}
         `;
 
@@ -221,6 +223,7 @@ export class SearchUI {
                 </span>
                 <span class="result-score">Match: ${(role.score * 100).toFixed(0)}%</span>
             </div>
+            ${this.warningsHtml(role.warnings)}
             ${permissionsHtml}
         `;
 
@@ -296,6 +299,17 @@ export class SearchUI {
         });
     }
 
+    /**
+     * Deprecation notices, linking the suggested replacement role
+     */
+    private warningsHtml(warnings?: ResultWarning[]): string {
+        return (warnings || []).map(w =>
+            `<div class="result-warning">⚠ ${this.escapeHtml(w.message)}${w.replacement
+                ? ` <a href="/roles/${this.escapeHtml(w.replacement)}" class="result-link">View replacement</a>`
+                : ''}</div>`
+        ).join('');
+    }
+
     /**
      * Escape HTML special characters
      */
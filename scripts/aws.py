@@ -0,0 +1,188 @@
+"""
+AWS managed policies as roles, for cross-cloud search from one dataset.
+
+AWS managed policies play the part of predefined roles and their actions
+("s3:GetObject") the part of permissions. Policies are fetched with the IAM
+GetAccountAuthorizationDetails call, which returns every managed policy
+with its policy documents in a few pages, and converted to the same API
+shape the GCP client returns, tagged with provider "aws".
+
+Only Allow statements contribute actions. Wildcards ("s3:Get*") are kept
+as written: expanding them needs the full AWS action catalog. NotAction
+statements grant "everything except" and can't be listed, so they are
+skipped. Credentials come from AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY
+(and AWS_SESSION_TOKEN), as for S3 uploads; the caller needs
+iam:GetAccountAuthorizationDetails.
+"""
+
+import json
+import os
+import sys
+import time
+import urllib.error
+import urllib.request
+import xml.etree.ElementTree as ET
+from datetime import datetime, timezone
+from pathlib import Path
+from urllib.parse import quote, unquote, urlencode, urlparse
+
+from upload import sigv4_authorization
+
+PROVIDER = "aws"
+IAM_ENDPOINT = "https://iam.amazonaws.com/"
+IAM_REGION = "us-east-1"  # IAM is global; requests are signed for us-east-1
+API_VERSION = "2010-05-08"
+MAX_ATTEMPTS = 4
+
+# Offline fixture: `aws iam get-account-authorization-details --filter AWSManagedPolicy` output
+FIXTURE_NAME = "aws/policies.json"
+
+
+class AwsError(Exception):
+    """AWS credentials missing, an API failure, or an unreadable policy."""
+
+
+def _as_list(value):
+    return value if isinstance(value, list) else [value]
+
+
+def policy_actions(document):
+    """Actions granted by a policy document's Allow statements, sorted."""
+    if isinstance(document, str):
+        document = json.loads(unquote(document))
+    actions = set()
+    for statement in _as_list(document.get('Statement', [])):
+        if statement.get('Effect') == 'Allow' and 'Action' in statement:
+            actions.update(_as_list(statement['Action']))
+    return sorted(actions)
+
+
+def policy_to_role(policy):
+    """A managed policy (GetAccountAuthorizationDetails shape) as a role in API shape."""
+    versions = policy.get('PolicyVersionList', [])
+    default = next((v for v in versions if v.get('IsDefaultVersion') in (True, 'true')), None)
+    if default is None:
+        raise AwsError(f"{policy.get('Arn', '?')}: no default policy version")
+    try:
+        actions = policy_actions(default.get('Document', {}))
+    except (ValueError, AttributeError) as e:
+        raise AwsError(f"{policy.get('Arn', '?')}: unreadable policy document: {e}")
+    return {
+        'name': policy['Arn'],
+        'title': policy.get('PolicyName', ''),
+        'description': policy.get('Description', ''),
+        'stage': 'GA',
+        'includedPermissions': actions,
+        'etag': policy.get('DefaultVersionId', ''),
+        'provider': PROVIDER,
+    }
+
+
+def _strip_namespace(tree):
+    for element in tree.iter():
+        element.tag = element.tag.rsplit('}', 1)[-1]
+    return tree
+
+
+def _policy_from_xml(member):
+    return {
+        'PolicyName': member.findtext('PolicyName', ''),
+        'Arn': member.findtext('Arn', ''),
+        'Description': member.findtext('Description', ''),
+        'DefaultVersionId': member.findtext('DefaultVersionId', ''),
+        'PolicyVersionList': [
+            {
+                'VersionId': version.findtext('VersionId', ''),
+                'IsDefaultVersion': version.findtext('IsDefaultVersion') == 'true',
+                'Document': version.findtext('Document', '{}'),
+            }
+            for version in member.findall('PolicyVersionList/member')
+        ],
+    }
+
+
+def parse_authorization_details(xml_text):
+    """(policies, marker or None) from one GetAccountAuthorizationDetails response page."""
+    try:
+        result = _strip_namespace(ET.fromstring(xml_text)).find('GetAccountAuthorizationDetailsResult')
+    except ET.ParseError as e:
+        raise AwsError(f"Unreadable GetAccountAuthorizationDetails response: {e}")
+    if result is None:
+        raise AwsError("Unexpected GetAccountAuthorizationDetails response")
+    policies = [_policy_from_xml(member) for member in result.findall('Policies/member')]
+    marker = result.findtext('Marker') if result.findtext('IsTruncated') == 'true' else None
+    return policies, marker
+
+
+class AwsClient:
+    """Signed IAM Query API calls with AWS credentials from the environment."""
+
+    def __init__(self):
+        self.access_key = os.environ.get('AWS_ACCESS_KEY_ID')
+        self.secret_key = os.environ.get('AWS_SECRET_ACCESS_KEY')
+        self.session_token = os.environ.get('AWS_SESSION_TOKEN')
+        if not self.access_key or not self.secret_key:
+            raise AwsError("AWS collection needs AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY")
+
+    def call(self, params):
+        """The XML body of an IAM Query API call, retrying throttling and 5xx responses."""
+        url = f"{IAM_ENDPOINT}?{urlencode(sorted({**params, 'Version': API_VERSION}.items()), quote_via=quote)}"
+        payload_hash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"  # sha256(b"")
+
+        for attempt in range(1, MAX_ATTEMPTS + 1):
+            now = datetime.now(timezone.utc)
+            headers = {
+                'Host': urlparse(url).netloc,
+                'x-amz-content-sha256': payload_hash,
+                'x-amz-date': now.strftime('%Y%m%dT%H%M%SZ'),
+            }
+            if self.session_token:
+                headers['x-amz-security-token'] = self.session_token
+            headers['Authorization'] = sigv4_authorization(
+                'GET', url, headers, payload_hash, self.access_key, self.secret_key, IAM_REGION, now, service='iam',
+            )
+            try:
+                with urllib.request.urlopen(urllib.request.Request(url, headers=headers), timeout=60) as response:
+                    return response.read().decode()
+            except urllib.error.HTTPError as e:
+                detail = e.read().decode(errors='replace')
+                throttled = e.code == 400 and 'Throttling' in detail
+                if not (throttled or e.code >= 500) or attempt == MAX_ATTEMPTS:
+                    raise AwsError(f"IAM {params['Action']} failed (HTTP {e.code}): {detail}")
+            except urllib.error.URLError as e:
+                if attempt == MAX_ATTEMPTS:
+                    raise AwsError(f"IAM {params['Action']} failed: {e.reason}")
+            time.sleep(2 ** attempt)
+
+    def managed_policies(self):
+        """Every AWS managed policy with its policy versions."""
+        policies = []
+        marker = None
+        while True:
+            params = {'Action': 'GetAccountAuthorizationDetails', 'Filter.member.1': 'AWSManagedPolicy', 'MaxItems': '1000'}
+            if marker:
+                params['Marker'] = marker
+            page, marker = parse_authorization_details(self.call(params))
+            policies.extend(page)
+            print(f"   Listed {len(policies)} AWS managed policies", file=sys.stderr)
+            if not marker:
+                return policies
+
+
+class AwsFixtureClient:
+    """Managed policies from aws/policies.json in a fixtures directory."""
+
+    def __init__(self, fixtures_dir):
+        self.path = Path(fixtures_dir) / FIXTURE_NAME
+
+    def managed_policies(self):
+        try:
+            data = json.loads(self.path.read_text())
+        except (OSError, ValueError) as e:
+            raise AwsError(f"No AWS fixture: {e}")
+        return data.get('Policies', []) if isinstance(data, dict) else data
+
+
+def fetch_managed_policy_roles(client):
+    """AWS managed policies as roles in API shape."""
+    return [policy_to_role(policy) for policy in client.managed_policies()]
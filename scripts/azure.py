@@ -0,0 +1,149 @@
+"""
+Azure built-in role definitions as roles, for cross-cloud search.
+
+Built-in role definitions are listed from Azure Resource Manager and
+converted to the GCP client's API shape, tagged with provider "azure".
+Control- and data-plane operations (actions, dataActions) become the
+role's permissions; notActions/notDataActions, which Azure subtracts from
+wildcard grants such as "*" or "Microsoft.Storage/*", are kept as
+excludedPermissions.
+
+Credentials: AZURE_ACCESS_TOKEN (e.g. `az account get-access-token
+--query accessToken -o tsv`), or a service principal via AZURE_TENANT_ID,
+AZURE_CLIENT_ID and AZURE_CLIENT_SECRET. Any identity can read role
+definitions.
+"""
+
+import json
+import os
+import sys
+import time
+import urllib.error
+import urllib.request
+from pathlib import Path
+from urllib.parse import quote, urlencode
+
+PROVIDER = "azure"
+ARM_ENDPOINT = "https://management.azure.com"
+ROLE_DEFINITIONS_URL = f"{ARM_ENDPOINT}/providers/Microsoft.Authorization/roleDefinitions?" + urlencode(
+    {'api-version': '2022-04-01', '$filter': "type eq 'BuiltInRole'"}, quote_via=quote,
+)
+TOKEN_URL = "https://login.microsoftonline.com/{tenant}/oauth2/v2.0/token"
+MAX_ATTEMPTS = 4
+
+# Offline fixture: the roleDefinitions list response, or `az role definition list` output
+FIXTURE_NAME = "azure/roleDefinitions.json"
+
+
+class AzureError(Exception):
+    """Azure credentials missing, an API failure, or an unreadable role definition."""
+
+
+def is_built_in(definition):
+    if 'properties' in definition:
+        return definition['properties'].get('type') == 'BuiltInRole'
+    return definition.get('roleType') == 'BuiltInRole'
+
+
+def role_name(definition_id):
+    """Scope-independent role name: "/providers/Microsoft.Authorization/roleDefinitions/{guid}"."""
+    _, found, rest = definition_id.partition('/providers/')
+    return f"/providers/{rest}" if found else definition_id
+
+
+def definition_to_role(definition):
+    """A role definition (ARM or az CLI shape) as a role in API shape."""
+    properties = definition.get('properties', definition)
+    try:
+        included, excluded = set(), set()
+        for permission in properties.get('permissions', []):
+            included.update(permission.get('actions', []) + permission.get('dataActions', []))
+            excluded.update(permission.get('notActions', []) + permission.get('notDataActions', []))
+        return {
+            'name': role_name(definition['id']),
+            'title': properties.get('roleName', ''),
+            'description': properties.get('description') or '',
+            'stage': 'GA',
+            'includedPermissions': sorted(included),
+            'excludedPermissions': sorted(excluded),
+            'etag': properties.get('updatedOn') or '',
+            'provider': PROVIDER,
+        }
+    except (KeyError, AttributeError, TypeError) as e:
+        raise AzureError(f"Unreadable role definition {definition.get('id', '?')}: {e}")
+
+
+def _read_json(req):
+    """JSON response of a request, retrying throttling and 5xx responses."""
+    for attempt in range(1, MAX_ATTEMPTS + 1):
+        try:
+            with urllib.request.urlopen(req, timeout=60) as response:
+                return json.loads(response.read())
+        except urllib.error.HTTPError as e:
+            if (e.code != 429 and e.code < 500) or attempt == MAX_ATTEMPTS:
+                detail = e.read().decode(errors='replace')
+                raise AzureError(f"{req.full_url.split('?')[0]} failed (HTTP {e.code}): {detail}")
+        except urllib.error.URLError as e:
+            if attempt == MAX_ATTEMPTS:
+                raise AzureError(f"{req.full_url.split('?')[0]} failed: {e.reason}")
+        except ValueError as e:
+            raise AzureError(f"Unreadable response from {req.full_url.split('?')[0]}: {e}")
+        time.sleep(2 ** attempt)
+
+
+class AzureClient:
+    """Azure Resource Manager reads with a bearer token."""
+
+    def __init__(self):
+        self.token = os.environ.get('AZURE_ACCESS_TOKEN') or self._client_credentials_token()
+
+    @staticmethod
+    def _client_credentials_token():
+        tenant = os.environ.get('AZURE_TENANT_ID')
+        client_id = os.environ.get('AZURE_CLIENT_ID')
+        secret = os.environ.get('AZURE_CLIENT_SECRET')
+        if not (tenant and client_id and secret):
+            raise AzureError(
+                "Azure collection needs AZURE_ACCESS_TOKEN, or AZURE_TENANT_ID, AZURE_CLIENT_ID and AZURE_CLIENT_SECRET"
+            )
+        body = urlencode({
+            'grant_type': 'client_credentials',
+            'client_id': client_id,
+            'client_secret': secret,
+            'scope': f"{ARM_ENDPOINT}/.default",
+        }).encode()
+        req = urllib.request.Request(TOKEN_URL.format(tenant=quote(tenant)), data=body, method='POST')
+        req.add_header('Content-Type', 'application/x-www-form-urlencoded')
+        return _read_json(req)['access_token']
+
+    def role_definitions(self):
+        """Every built-in role definition, following nextLink pages."""
+        definitions = []
+        url = ROLE_DEFINITIONS_URL
+        while url:
+            req = urllib.request.Request(url, headers={'Authorization': f"Bearer {self.token}"})
+            page = _read_json(req)
+            definitions.extend(page.get('value', []))
+            print(f"   Listed {len(definitions)} Azure role definitions", file=sys.stderr)
+            url = page.get('nextLink')
+        return definitions
+
+
+class AzureFixtureClient:
+    """Role definitions from azure/roleDefinitions.json in a fixtures directory."""
+
+    def __init__(self, fixtures_dir):
+        self.path = Path(fixtures_dir) / FIXTURE_NAME
+
+    def role_definitions(self):
+        try:
+            data = json.loads(self.path.read_text())
+        except (OSError, ValueError) as e:
+            raise AzureError(f"No Azure fixture: {e}")
+        return data.get('value', []) if isinstance(data, dict) else data
+
+
+def fetch_built_in_roles(client):
+    """Azure built-in role definitions as roles in API shape."""
+    # `az role definition list` includes custom roles; keep the built-in ones
+    return [definition_to_role(d) for d in client.role_definitions() if is_built_in(d)]
@@ -0,0 +1,27 @@
+#!/usr/bin/env bash
+# Build the client-side search module and its index for the frontend.
+#
+# Requires the wasm32-unknown-unknown target and wasm-bindgen-cli:
+#   rustup target add wasm32-unknown-unknown
+#   cargo install wasm-bindgen-cli
+#
+# Output: frontend/public/pkg/{gcpiam_wasm.js,gcpiam_wasm_bg.wasm,index.bin}
+#
+# GCPIAM_SERVICES=compute,storage indexes only those services' shards from
+# data/services/ (written by the scraper's --split-services).
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+OUT_DIR=frontend/public/pkg
+
+cargo build -p gcpiam-wasm --release --target wasm32-unknown-unknown
+wasm-bindgen --target web --out-dir "$OUT_DIR" \
+    target/wasm32-unknown-unknown/release/gcpiam_wasm.wasm
+
+if [[ -n "${GCPIAM_SERVICES:-}" ]]; then
+    cargo run -q -p gcpiam-core --release --example build_index -- \
+        data/services/index.json "$OUT_DIR/index.bin" "$GCPIAM_SERVICES"
+else
+    cargo run -q -p gcpiam-core --release --example build_index -- \
+        data/iam-data.json "$OUT_DIR/index.bin"
+fi
@@ -0,0 +1,136 @@
+"""
+Change detection between two IAM datasets.
+
+Used to record what changed since the previous scrape and by tooling that
+compares dataset files.
+"""
+
+# Role fields compared besides the permission list
+TRACKED_FIELDS = ('title', 'description', 'stage')
+
+
+def compute_changes(previous, current):
+    """
+    Differences from `previous` to `current` (both iam-data.json dicts).
+
+    Modified roles carry the permissions added to and removed from them plus
+    any other fields that changed.
+    """
+    old_roles = {r['name']: r for r in previous.get('roles', [])}
+    new_roles = {r['name']: r for r in current.get('roles', [])}
+    old_permissions = {p['name'] for p in previous.get('permissions', [])}
+    new_permissions = {p['name'] for p in current.get('permissions', [])}
+
+    roles_modified = []
+    for name in sorted(old_roles.keys() & new_roles.keys()):
+        old, new = old_roles[name], new_roles[name]
+        old_perms = set(old.get('included_permissions', []))
+        new_perms = set(new.get('included_permissions', []))
+        fields_changed = [f for f in TRACKED_FIELDS if old.get(f) != new.get(f)]
+        if old_perms != new_perms or fields_changed:
+            roles_modified.append({
+                'name': name,
+                'permissions_added': sorted(new_perms - old_perms),
+                'permissions_removed': sorted(old_perms - new_perms),
+                'fields_changed': fields_changed,
+            })
+
+    return {
+        'roles_added': sorted(new_roles.keys() - old_roles.keys()),
+        'roles_removed': sorted(old_roles.keys() - new_roles.keys()),
+        'roles_modified': roles_modified,
+        'permissions_added': sorted(new_permissions - old_permissions),
+        'permissions_removed': sorted(old_permissions - new_permissions),
+    }
+
+
+def has_changes(changes):
+    return any(changes[key] for key in changes)
+
+
+def summarize(changes):
+    """One-line, human-readable change summary."""
+    if not has_changes(changes):
+        return "No changes"
+    return (
+        f"Roles: +{len(changes['roles_added'])} -{len(changes['roles_removed'])} "
+        f"~{len(changes['roles_modified'])}; "
+        f"Permissions: +{len(changes['permissions_added'])} -{len(changes['permissions_removed'])}"
+    )
+
+
+def format_report(changes):
+    """Multi-line report listing every change, for review before publishing."""
+    lines = [summarize(changes)]
+
+    def section(title, names, marker):
+        if names:
+            lines.append("")
+            lines.append(f"{title} ({len(names)})")
+            lines.extend(f"  {marker} {name}" for name in names)
+
+    section("Roles added", changes['roles_added'], '+')
+    section("Roles removed", changes['roles_removed'], '-')
+    if changes['roles_modified']:
+        lines.append("")
+        lines.append(f"Roles modified ({len(changes['roles_modified'])})")
+        for role in changes['roles_modified']:
+            lines.append(f"  ~ {role['name']}")
+            if role['fields_changed']:
+                lines.append(f"      changed: {', '.join(role['fields_changed'])}")
+            lines.extend(f"      + {p}" for p in role['permissions_added'])
+            lines.extend(f"      - {p}" for p in role['permissions_removed'])
+    section("Permissions added", changes['permissions_added'], '+')
+    section("Permissions removed", changes['permissions_removed'], '-')
+    return "\n".join(lines)
+
+
+CHANGELOG_TITLE = "# GCP IAM changelog"
+
+
+def format_markdown(changes, current, date):
+    """
+    CHANGELOG-style markdown entry for one run, headed by `date`. `current`
+    is the newer dataset, used for role titles and permission counts.
+    """
+    roles = {r['name']: r for r in current.get('roles', [])}
+    lines = [f"## {date}", "", summarize(changes)]
+
+    def section(title, items):
+        if items:
+            lines.extend(["", f"### {title}", ""])
+            lines.extend(items)
+
+    def added(name):
+        role = roles.get(name, {})
+        count = len(role.get('included_permissions', []))
+        title = f"{role['title']}, " if role.get('title') else ""
+        return f"- Added `{name}` ({title}{count} permission{'s' if count != 1 else ''})"
+
+    section("Roles added", [added(name) for name in changes['roles_added']])
+    deleted = {r['name'] for r in current.get('deleted_roles', []) if r.get('reason') == 'deleted'}
+    section("Roles removed", [
+        f"- Removed `{name}`{' (marked deleted)' if name in deleted else ''}" for name in changes['roles_removed']
+    ])
+    modified = []
+    for role in changes['roles_modified']:
+        parts = []
+        if role['permissions_added'] or role['permissions_removed']:
+            parts.append(f"+{len(role['permissions_added'])} -{len(role['permissions_removed'])} permissions")
+        if role['fields_changed']:
+            parts.append(f"{', '.join(role['fields_changed'])} changed")
+        modified.append(f"- `{role['name']}`: {'; '.join(parts)}")
+        modified.extend(f"  - Added `{p}`" for p in role['permissions_added'])
+        modified.extend(f"  - Removed `{p}`" for p in role['permissions_removed'])
+    section("Roles modified", modified)
+    section("Permissions added", [f"- `{name}`" for name in changes['permissions_added']])
+    section("Permissions removed", [f"- `{name}`" for name in changes['permissions_removed']])
+    return "\n".join(lines) + "\n"
+
+
+def prepend_entry(changelog, entry):
+    """`changelog` (existing file contents, possibly empty) with `entry` as its newest entry."""
+    body = changelog.strip()
+    if body.startswith(CHANGELOG_TITLE):
+        body = body[len(CHANGELOG_TITLE):].strip()
+    return f"{CHANGELOG_TITLE}\n\n{entry}" + (f"\n{body}\n" if body else "")
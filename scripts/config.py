@@ -0,0 +1,94 @@
+"""
+scraper.toml configuration.
+
+Nightly jobs tend to accumulate flags; the same settings can live in a TOML
+file instead. Values map one-to-one onto command-line options and any flag
+given on the command line wins, including repeatable ones (--upload on the
+command line replaces the file's targets rather than adding to them).
+See scraper.example.toml for every key.
+"""
+
+import tomllib
+from pathlib import Path
+
+DEFAULT_CONFIG_NAME = "scraper.toml"
+
+# (table, key) -> (argparse dest, type). Path values are resolved relative
+# to the config file so a job can run from any working directory.
+SETTINGS = {
+    ('output', 'dir'): ('output_dir', Path),
+    ('output', 'formats'): ('format', list),
+    ('output', 'compress'): ('compress', str),
+    ('output', 'changelog'): ('changelog', bool),
+    ('output', 'signing_key'): ('signing_key', Path),
+    ('output', 'public_key'): ('public_key', Path),
+    ('output', 'split_services'): ('split_services', bool),
+    ('output', 'snapshots'): ('snapshots', bool),
+    ('output', 'retain'): ('retain', int),
+    ('upload', 'targets'): ('upload', list),
+    ('upload', 'cache_control'): ('cache_control', str),
+    ('upload', 's3_endpoint'): ('s3_endpoint', str),
+    ('upload', 's3_region'): ('s3_region', str),
+    ('api', 'concurrency'): ('concurrency', int),
+    ('api', 'max_rps'): ('max_rps', float),
+    ('api', 'max_retries'): ('max_retries', int),
+    ('api', 'proxy'): ('proxy', str),
+    ('api', 'impersonate_service_account'): ('impersonate_service_account', str),
+    ('api', 'offline'): ('offline', Path),
+    ('collect', 'projects'): ('project', list),
+    ('collect', 'organizations'): ('organization', list),
+    ('collect', 'parents_file'): ('parents_file', Path),
+    ('collect', 'aws'): ('aws', bool),
+    ('collect', 'azure'): ('azure', bool),
+    ('collect', 'services'): ('services', list),
+    ('collect', 'incremental'): ('incremental', bool),
+    ('collect', 'enrich_descriptions'): ('enrich_descriptions', bool),
+    ('logging', 'format'): ('log_format', str),
+    ('notify', 'webhook'): ('webhook', str),
+    ('notify', 'format'): ('webhook_format', str),
+}
+
+
+class ConfigError(Exception):
+    """Unreadable config file or a setting of the wrong shape."""
+
+
+def _convert(value, expected, where, base_dir):
+    if expected is Path:
+        if not isinstance(value, str):
+            raise ConfigError(f"{where} should be a path string")
+        return base_dir / Path(value).expanduser()
+    if expected is float and isinstance(value, int) and not isinstance(value, bool):
+        return float(value)
+    if expected is int and isinstance(value, bool):
+        raise ConfigError(f"{where} should be an integer")
+    if not isinstance(value, expected):
+        raise ConfigError(f"{where} should be {'an array' if expected is list else expected.__name__}")
+    if expected is list:
+        if not all(isinstance(item, (str, int)) and not isinstance(item, bool) for item in value):
+            raise ConfigError(f"{where} should be an array of strings")
+        return [str(item) for item in value]
+    return value
+
+
+def load_config(path):
+    """argparse dest -> value for every setting in the file."""
+    path = Path(path)
+    try:
+        with open(path, 'rb') as f:
+            data = tomllib.load(f)
+    except OSError as e:
+        raise ConfigError(f"Cannot read {path}: {e}")
+    except tomllib.TOMLDecodeError as e:
+        raise ConfigError(f"{path}: {e}")
+
+    settings = {}
+    for table, values in data.items():
+        if not isinstance(values, dict):
+            raise ConfigError(f"{path}: '{table}' should be a table, e.g. [{table}]")
+        for key, value in values.items():
+            if (table, key) not in SETTINGS:
+                raise ConfigError(f"{path}: unknown setting {table}.{key}")
+            dest, expected = SETTINGS[(table, key)]
+            settings[dest] = _convert(value, expected, f"{path}: {table}.{key}", path.parent)
+    return settings
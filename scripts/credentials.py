@@ -0,0 +1,211 @@
+"""
+Google Application Default Credentials for the IAM scraper.
+
+Resolves an access token the same way Google client libraries do, using only
+the standard library:
+
+1. GOOGLE_APPLICATION_CREDENTIALS (service account key, authorized user, or
+   an external account file written by workload identity federation)
+2. gcloud's application default credentials
+   (~/.config/gcloud/application_default_credentials.json)
+3. The GCE / Cloud Run metadata server
+4. `gcloud auth print-access-token` as a last resort
+"""
+
+import base64
+import json
+import os
+import subprocess
+import sys
+import tempfile
+import time
+import urllib.error
+import urllib.parse
+import urllib.request
+from pathlib import Path
+
+SCOPE = "https://www.googleapis.com/auth/cloud-platform"
+TOKEN_URI = "https://oauth2.googleapis.com/token"
+IMPERSONATE_URL = (
+    "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/{account}:generateAccessToken"
+)
+METADATA_TOKEN_URL = (
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token"
+)
+
+
+class CredentialsError(Exception):
+    """No usable credentials were found."""
+
+
+def well_known_file():
+    """Path of gcloud's application default credentials file."""
+    if os.name == 'nt':
+        base = Path(os.environ.get('APPDATA', '')) / 'gcloud'
+    else:
+        base = Path(os.environ.get('CLOUDSDK_CONFIG', Path.home() / '.config' / 'gcloud'))
+    return base / 'application_default_credentials.json'
+
+
+def _post_form(url, fields):
+    data = urllib.parse.urlencode(fields).encode()
+    req = urllib.request.Request(url, data=data, method='POST')
+    req.add_header('Content-Type', 'application/x-www-form-urlencoded')
+    with urllib.request.urlopen(req, timeout=30) as response:
+        return json.loads(response.read().decode())
+
+
+def _b64url(data):
+    return base64.urlsafe_b64encode(data).rstrip(b'=').decode()
+
+
+def _sign_rs256(private_key_pem, message):
+    """Sign with the service account key via openssl (no third-party crypto)."""
+    # mkstemp creates the file readable by the current user only
+    fd, key_file = tempfile.mkstemp(suffix='.pem')
+    key_path = Path(key_file)
+    try:
+        with os.fdopen(fd, 'w') as f:
+            f.write(private_key_pem)
+        result = subprocess.run(
+            ['openssl', 'dgst', '-sha256', '-sign', str(key_path)],
+            input=message, capture_output=True,
+        )
+    except FileNotFoundError:
+        raise CredentialsError("openssl is required to sign service account tokens")
+    finally:
+        key_path.unlink(missing_ok=True)
+
+    if result.returncode != 0:
+        raise CredentialsError(f"openssl failed to sign token: {result.stderr.decode().strip()}")
+    return result.stdout
+
+
+def _service_account_token(info):
+    now = int(time.time())
+    header = _b64url(json.dumps({'alg': 'RS256', 'typ': 'JWT', 'kid': info.get('private_key_id', '')}).encode())
+    claims = _b64url(json.dumps({
+        'iss': info['client_email'],
+        'scope': SCOPE,
+        'aud': info.get('token_uri', TOKEN_URI),
+        'iat': now,
+        'exp': now + 3600,
+    }).encode())
+    unsigned = f"{header}.{claims}".encode()
+    assertion = f"{header}.{claims}.{_b64url(_sign_rs256(info['private_key'], unsigned))}"
+
+    response = _post_form(info.get('token_uri', TOKEN_URI), {
+        'grant_type': 'urn:ietf:params:oauth:grant-type:jwt-bearer',
+        'assertion': assertion,
+    })
+    return response['access_token']
+
+
+def _authorized_user_token(info):
+    response = _post_form(info.get('token_uri', TOKEN_URI), {
+        'grant_type': 'refresh_token',
+        'client_id': info['client_id'],
+        'client_secret': info['client_secret'],
+        'refresh_token': info['refresh_token'],
+    })
+    return response['access_token']
+
+
+def _token_from_file(path):
+    """Access token from a credentials JSON file, dispatching on its type."""
+    with open(path) as f:
+        info = json.load(f)
+
+    cred_type = info.get('type')
+    if cred_type == 'service_account':
+        return _service_account_token(info)
+    if cred_type == 'authorized_user':
+        return _authorized_user_token(info)
+    if cred_type == 'external_account':
+        # Workload identity federation needs the STS exchange that gcloud
+        # implements; google-github-actions/auth configures gcloud with it.
+        return _gcloud_token()
+    raise CredentialsError(f"Unsupported credentials type '{cred_type}' in {path}")
+
+
+def _metadata_token():
+    """Token for the attached service account on GCE, Cloud Run, GKE, etc."""
+    host = os.environ.get('GCE_METADATA_HOST')
+    url = f"http://{host}/computeMetadata/v1/instance/service-accounts/default/token" if host else METADATA_TOKEN_URL
+    req = urllib.request.Request(url)
+    req.add_header('Metadata-Flavor', 'Google')
+    # The metadata server is link-local; never send it through an egress proxy
+    direct = urllib.request.build_opener(urllib.request.ProxyHandler({}))
+    try:
+        with direct.open(req, timeout=2) as response:
+            return json.loads(response.read().decode())['access_token']
+    except (urllib.error.URLError, OSError, KeyError, ValueError):
+        return None
+
+
+def _gcloud_token():
+    try:
+        result = subprocess.run(['gcloud', 'auth', 'print-access-token'], capture_output=True, text=True)
+    except FileNotFoundError:
+        raise CredentialsError("gcloud is not installed")
+    if result.returncode != 0:
+        raise CredentialsError(f"gcloud auth print-access-token failed: {result.stderr.strip()}")
+    return result.stdout.strip()
+
+
+def default_token():
+    """
+    Access token from the first source in the ADC chain that is configured.
+
+    Returns (token, source) where source describes where it came from.
+    """
+    env_path = os.environ.get('GOOGLE_APPLICATION_CREDENTIALS')
+    if env_path:
+        if not Path(env_path).is_file():
+            raise CredentialsError(f"GOOGLE_APPLICATION_CREDENTIALS points to a missing file: {env_path}")
+        return _token_from_file(env_path), f"GOOGLE_APPLICATION_CREDENTIALS ({env_path})"
+
+    adc_path = well_known_file()
+    if adc_path.is_file():
+        return _token_from_file(adc_path), f"gcloud application default credentials ({adc_path})"
+
+    token = _metadata_token()
+    if token:
+        return token, "metadata server"
+
+    try:
+        return _gcloud_token(), "gcloud auth print-access-token"
+    except CredentialsError as e:
+        raise CredentialsError(
+            "No credentials found. Set GOOGLE_APPLICATION_CREDENTIALS, run "
+            "'gcloud auth application-default login', or run on GCP with an attached "
+            f"service account ({e})"
+        )
+
+
+def impersonate(source_token, service_account, lifetime_seconds=3600):
+    """
+    Exchange a token for one belonging to `service_account`.
+
+    The caller needs roles/iam.serviceAccountTokenCreator on that account.
+    """
+    url = IMPERSONATE_URL.format(account=urllib.parse.quote(service_account, safe='@'))
+    body = json.dumps({'scope': [SCOPE], 'lifetime': f"{lifetime_seconds}s"}).encode()
+    req = urllib.request.Request(url, data=body, method='POST')
+    req.add_header('Authorization', f'Bearer {source_token}')
+    req.add_header('Content-Type', 'application/json')
+    try:
+        with urllib.request.urlopen(req, timeout=30) as response:
+            return json.loads(response.read().decode())['accessToken']
+    except urllib.error.HTTPError as e:
+        detail = e.read().decode(errors='replace')
+        raise CredentialsError(f"Could not impersonate {service_account} (HTTP {e.code}): {detail}")
+
+
+if __name__ == '__main__':
+    try:
+        _, source = default_token()
+    except CredentialsError as e:
+        print(f"Error: {e}", file=sys.stderr)
+        sys.exit(1)
+    print(f"Found credentials via {source}")
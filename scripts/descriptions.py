@@ -0,0 +1,123 @@
+"""
+Permission descriptions from the public IAM documentation.
+
+The IAM API returns no description for permissions, but the published
+permissions reference explains many of them. This module downloads that
+page (through a local cache, so repeated runs don't re-download it),
+extracts permission -> description pairs and merges them into the dataset.
+"""
+
+import re
+import sys
+import time
+import urllib.error
+import urllib.request
+from html.parser import HTMLParser
+from pathlib import Path
+
+PERMISSIONS_REFERENCE_URL = "https://cloud.google.com/iam/docs/permissions-reference"
+CACHE_MAX_AGE_SECONDS = 7 * 24 * 3600
+
+PERMISSION_NAME = re.compile(r'^[a-z][a-zA-Z0-9-]*(\.[a-zA-Z0-9-]+){2,}$')
+
+
+class ReferenceParser(HTMLParser):
+    """
+    Collects descriptions from the reference page.
+
+    Understands the two layouts the docs use: table rows whose first cell
+    is the permission name, and <dt>name</dt><dd>description</dd> lists.
+    """
+
+    def __init__(self):
+        super().__init__()
+        self.descriptions = {}
+        self._cells = None
+        self._cell = None
+        self._term = None
+        self._last_term = ''
+        self._definition = None
+
+    def handle_starttag(self, tag, attrs):
+        if tag == 'tr':
+            self._cells = []
+        elif tag in ('td', 'th') and self._cells is not None:
+            self._cell = []
+        elif tag == 'dt':
+            self._term = []
+        elif tag == 'dd':
+            self._definition = []
+
+    def handle_endtag(self, tag):
+        if tag in ('td', 'th') and self._cell is not None:
+            self._cells.append(_clean(self._cell))
+            self._cell = None
+        elif tag == 'tr' and self._cells is not None:
+            if len(self._cells) >= 2:
+                self._add(self._cells[0], self._cells[1])
+            self._cells = None
+        elif tag == 'dt' and self._term is not None:
+            self._last_term = _clean(self._term)
+            self._term = None
+        elif tag == 'dd' and self._definition is not None:
+            self._add(self._last_term, _clean(self._definition))
+            self._definition = None
+
+    def handle_data(self, data):
+        for buffer in (self._cell, self._term, self._definition):
+            if buffer is not None:
+                buffer.append(data)
+
+    def _add(self, name, description):
+        # Skip cells that only list the roles granting the permission
+        if PERMISSION_NAME.match(name) and description and 'roles/' not in description:
+            self.descriptions.setdefault(name, description)
+
+
+def _clean(parts):
+    return ' '.join(''.join(parts).split())
+
+
+def parse_reference(html):
+    """Permission name -> description found in the reference page HTML."""
+    parser = ReferenceParser()
+    parser.feed(html)
+    parser.close()
+    return parser.descriptions
+
+
+def fetch_reference(cache_path, url=PERMISSIONS_REFERENCE_URL, max_age=CACHE_MAX_AGE_SECONDS):
+    """
+    The reference page HTML, from `cache_path` when it is fresher than
+    `max_age` seconds. A stale cache is still used if the download fails;
+    returns None when there is neither.
+    """
+    cache_path = Path(cache_path)
+    if cache_path.is_file() and time.time() - cache_path.stat().st_mtime < max_age:
+        return cache_path.read_text()
+
+    try:
+        req = urllib.request.Request(url, headers={'User-Agent': 'gcpiam-scraper'})
+        with urllib.request.urlopen(req, timeout=60) as response:
+            html = response.read().decode('utf-8', errors='replace')
+    except (urllib.error.URLError, OSError) as e:
+        if cache_path.is_file():
+            print(f"   Warning: Could not refresh {url} ({e}); using cached copy", file=sys.stderr)
+            return cache_path.read_text()
+        print(f"   Warning: Could not download {url}: {e}", file=sys.stderr)
+        return None
+
+    cache_path.parent.mkdir(parents=True, exist_ok=True)
+    cache_path.write_text(html)
+    return html
+
+
+def enrich_permissions(permissions, descriptions):
+    """Set `description` on permissions the docs describe; returns how many were matched."""
+    matched = 0
+    for permission in permissions:
+        description = descriptions.get(permission['name'])
+        if description:
+            permission['description'] = description
+            matched += 1
+    return matched
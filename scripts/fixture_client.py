@@ -0,0 +1,69 @@
+"""
+Offline IAM API backed by canned responses.
+
+A fixtures directory mirrors the API's role collections:
+
+    roles.json                          predefined roles
+    organizations/123456789/roles.json  custom roles of an organization
+    projects/my-project/roles.json      custom roles of a project
+    aws/policies.json                   AWS managed policies for --aws (see aws.py)
+    azure/roleDefinitions.json          Azure built-in role definitions for --azure (see azure.py)
+
+Each file holds a roles.list response ({"roles": [...]}) or a bare list of
+roles in API shape (name, title, description, stage, includedPermissions,
+etag, and "deleted": true for deleted roles). scripts/fixtures has a small sample set used by --offline.
+"""
+
+import json
+from pathlib import Path
+
+from gcp_client import IamApi
+
+
+class FixtureError(Exception):
+    """Missing or malformed fixture."""
+
+
+class FixtureClient(IamApi):
+    def __init__(self, fixtures_dir):
+        self.fixtures_dir = Path(fixtures_dir)
+        self.lookups = 0
+        self._collections = {}
+
+    def _roles(self, parent):
+        if parent not in self._collections:
+            path = self.fixtures_dir / (parent or '') / "roles.json"
+            try:
+                data = json.loads(path.read_text())
+            except OSError as e:
+                raise FixtureError(f"No fixture for {parent or 'predefined roles'}: {e}")
+            except ValueError as e:
+                raise FixtureError(f"{path}: {e}")
+            roles = data.get('roles', []) if isinstance(data, dict) else data
+            if not isinstance(roles, list):
+                raise FixtureError(f"{path}: expected a list of roles")
+            self._collections[parent] = roles
+        return self._collections[parent]
+
+    def list_roles(self, parent=None, view='FULL', progress=True, show_deleted=False):
+        self.lookups += 1
+        roles = [dict(role) for role in self._roles(parent) if show_deleted or not role.get('deleted')]
+        if view == 'BASIC':
+            for role in roles:
+                role.pop('includedPermissions', None)
+        return roles
+
+    def get_role(self, name):
+        self.lookups += 1
+        # "projects/p/roles/x" lives in the projects/p collection
+        parent = name.rsplit('/roles/', 1)[0] if '/roles/' in name else None
+        for role in self._roles(parent):
+            if role.get('name') == name:
+                return dict(role)
+        raise FixtureError(f"No fixture for role {name}")
+
+    def usage(self):
+        return {'offline': True, 'fixtures_dir': str(self.fixtures_dir), 'lookups': self.lookups}
+
+    def report(self):
+        return [f"Offline: {self.lookups} fixture lookups from {self.fixtures_dir}"]
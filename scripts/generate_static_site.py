@@ -8,68 +8,119 @@ Fetches all GCP IAM roles and permissions, generates:
 - sitemap.xml for SEO
 """
 
+import argparse
 import json
-import subprocess
 import sys
 import os
-from datetime import datetime
+import time
+from collections import defaultdict
+from concurrent.futures import ThreadPoolExecutor
+from datetime import date, datetime, timedelta
 from pathlib import Path
 from html import escape
-from urllib.parse import quote
+from urllib.parse import quote, urlsplit
+
+from aws import PROVIDER as AWS, AwsClient, AwsError, AwsFixtureClient, fetch_managed_policy_roles
+from azure import PROVIDER as AZURE, AzureClient, AzureError, AzureFixtureClient, fetch_built_in_roles
+from changes import compute_changes, format_markdown, format_report, has_changes, prepend_entry, summarize
+from config import DEFAULT_CONFIG_NAME, SETTINGS, ConfigError, load_config
+from credentials import CredentialsError, default_token, impersonate
+from descriptions import enrich_permissions, fetch_reference, parse_reference
+from fixture_client import FixtureClient, FixtureError
+from gcp_client import DEFAULT_CONCURRENCY, MAX_RETRIES, GcpClient, configure_proxy
+from manifest import IntegrityError, load_public_key, load_signing_key, verify_file
+from notify import WEBHOOK_FORMATS, RunSummary, post_webhook
+from products import product_name, role_service
+from quality import QUALITY_REPORT_NAME, format_findings, quality_report
+from progress import Progress
+from sitemap import build_sitemaps
+from stats import compute_stats, format_stats
+from storage import (
+    COMPRESSION_SUFFIXES, DATA_FILE_NAME, DEFAULT_RETAIN, FORMATS, SERVICES_DIR, SHARD_INDEX, StorageManager,
+    find_variant, load_dataset,
+)
+from upload import DEFAULT_CACHE_CONTROL, make_sink
+from validate import validate_dataset
 
 # Configuration
 BASE_URL = "https://gcpiam.com"
 OUTPUT_DIR = Path(__file__).parent.parent / "data"
-STATIC_DIR = OUTPUT_DIR / "static"
-ROLES_DIR = STATIC_DIR / "roles"
-PERMISSIONS_DIR = STATIC_DIR / "permissions"
+FIXTURES_DIR = Path(__file__).parent / "fixtures"
+CACHE_DIR = Path(os.environ.get('XDG_CACHE_HOME') or Path.home() / ".cache") / "gcpiam"
+CHANGELOG_NAME = "CHANGELOG.md"
 
+# Predefined roles all report this etag, so it cannot signal a change
+UNVERSIONED_ETAG = "AA=="
+
+# Provider of roles and permissions without a provider field
+GCP = "gcp"
+
+# How long deleted and vanished roles stay in the dataset's deleted_roles
+DELETED_ROLE_RETENTION_DAYS = 90
+
+
+def get_token(impersonate_service_account=None):
+    """Get GCP access token from Application Default Credentials."""
+    try:
+        token, source = default_token()
+        print(f"   Using {source}", file=sys.stderr)
+        if impersonate_service_account:
+            token = impersonate(token, impersonate_service_account)
+            print(f"   Impersonating {impersonate_service_account}", file=sys.stderr)
+    except CredentialsError as e:
+        sys.exit(f"Error: {e}")
+    return token
 
-def get_token():
-    """Get GCP access token from gcloud."""
-    result = subprocess.run(['gcloud', 'auth', 'print-access-token'], capture_output=True, text=True)
-    if result.returncode != 0:
-        print("Error: Could not get access token. Run 'gcloud auth login' first.", file=sys.stderr)
-        sys.exit(1)
-    return result.stdout.strip()
 
+def redact_credentials(url):
+    """`url` with any user:password@ removed, safe to log."""
+    parts = urlsplit(url)
+    return parts._replace(netloc=parts.netloc.rsplit('@', 1)[-1]).geturl()
 
-def fetch_url(url, token, method='GET', data=None):
-    """Fetch URL with authentication."""
-    import urllib.request
 
-    req = urllib.request.Request(url, method=method)
-    req.add_header('Authorization', f'Bearer {token}')
-    if data:
-        req.add_header('Content-Type', 'application/json')
-        req.data = json.dumps(data).encode()
+def has_usable_etag(role):
+    etag = role.get('etag')
+    return bool(etag) and etag != UNVERSIONED_ETAG
 
-    with urllib.request.urlopen(req) as response:
-        return json.loads(response.read().decode())
 
+def dataset_role_to_api(role):
+    """Convert a role from a saved dataset back into the IAM API shape."""
+    return {
+        'name': role['name'],
+        'title': role.get('title', ''),
+        'description': role.get('description', ''),
+        'stage': role.get('stage', 'GA'),
+        'includedPermissions': role.get('included_permissions', []),
+        'etag': role.get('etag', ''),
+    }
 
-def fetch_all_roles(token):
-    """Fetch all predefined roles from GCP IAM API."""
-    all_roles = []
-    page_token = None
-    page_num = 0
 
-    while True:
-        page_num += 1
-        url = "https://iam.googleapis.com/v1/roles?pageSize=1000&view=FULL"
-        if page_token:
-            url += f"&pageToken={page_token}"
+def fetch_roles_incremental(client, previous_roles, parent=None, progress=True):
+    """
+    Fetch roles, reusing previous data for roles whose etag is unchanged.
 
-        data = fetch_url(url, token)
-        roles = data.get('roles', [])
-        all_roles.extend(roles)
-        print(f"  Fetched page {page_num}: {len(roles)} roles (total: {len(all_roles)})", file=sys.stderr)
+    Lists roles with the BASIC view and only calls roles.get for new roles and
+    roles whose etag differs from `previous_roles` (name -> dataset role),
+    running those gets concurrently. When no listed role has a usable etag
+    (as with predefined roles) one FULL listing is cheaper than a get per
+    role. Returns (roles, refetched_count).
+    """
+    listed = client.list_roles(parent, view='BASIC', progress=progress, show_deleted=True)
+    if not any(has_usable_etag(r) for r in listed):
+        roles = client.list_roles(parent, progress=progress, show_deleted=True)
+        return roles, len(roles)
 
-        page_token = data.get('nextPageToken')
-        if not page_token:
-            break
+    def unchanged(role):
+        previous = previous_roles.get(role['name'])
+        return previous and has_usable_etag(role) and previous.get('etag') == role['etag']
 
-    return all_roles
+    stale = [r['name'] for r in listed if not unchanged(r)]
+    fetched = dict(zip(stale, client.get_roles(stale, progress=progress)))
+    roles = [
+        fetched[r['name']] if r['name'] in fetched else dataset_role_to_api(previous_roles[r['name']])
+        for r in listed
+    ]
+    return roles, len(stale)
 
 
 def fetch_permission_metadata(token, permissions_batch):
@@ -79,17 +130,152 @@ def fetch_permission_metadata(token, permissions_batch):
     return {}
 
 
+def is_custom_role(role):
+    """Project- and organization-level roles live outside the roles/ namespace."""
+    return role.get('provider', GCP) == GCP and not role.get('name', '').startswith('roles/')
+
+
+def permission_parts(name, provider=None):
+    """
+    (provider, service, resource, action) of a permission name: GCP's
+    "storage.buckets.get", an AWS action "s3:GetObject" (no resource), or an
+    Azure operation "Microsoft.Compute/virtualMachines/start/action". Names
+    like "*" exist in several clouds, so pass the granting role's provider
+    when it is known; otherwise it is guessed from the name.
+    """
+    if provider is None:
+        provider = AWS if ':' in name else AZURE if '/' in name else GCP
+    if provider == AWS:
+        service, _, action = name.partition(':')
+        return AWS, service, '', action
+    if provider == AZURE:
+        service, *resource, action = name.split('/') if '/' in name else [name, '']
+        return AZURE, service, '/'.join(resource), action
+    parts = name.split('.')
+    return GCP, parts[0], parts[1] if len(parts) > 1 else '', parts[2] if len(parts) > 2 else ''
+
+
+def is_site_role(role):
+    """Roles published as pages: predefined GCP roles (custom roles are organization-internal)."""
+    return role.get('provider', GCP) == GCP and not role.get('custom', is_custom_role(role))
+
+
+def role_collection(record):
+    """What collected a role this run: its parent, "" for predefined GCP roles, or its provider."""
+    provider = record.get('provider', GCP)
+    return provider if provider != GCP else record.get('origin', role_origin(record['name']))
+
+
+def role_origin(name):
+    """Parent a custom role was collected from ("organizations/123"); "" for predefined roles."""
+    return name.rsplit('/roles/', 1)[0] if '/roles/' in name else ''
+
+
+def read_parents(path):
+    """
+    Role parents listed in a file, one per line.
+
+    Accepts "projects/ID" and "organizations/ID", or bare IDs: numeric ones
+    are organizations, anything else a project. Blank lines and # comments
+    are ignored. Raises ValueError on unsupported parent types.
+    """
+    parents = []
+    for number, line in enumerate(Path(path).read_text().splitlines(), 1):
+        entry = line.split('#', 1)[0].strip()
+        if not entry:
+            continue
+        if '/' in entry:
+            kind, _, ident = entry.partition('/')
+            if kind not in ('projects', 'organizations') or not ident or '/' in ident:
+                raise ValueError(f"{path}:{number}: expected projects/ID or organizations/ID, got '{entry}'")
+            parents.append(entry)
+        else:
+            parents.append(f"organizations/{entry}" if entry.isdigit() else f"projects/{entry}")
+    return list(dict.fromkeys(parents))
+
+
+def filter_services(roles, services):
+    """
+    Narrow API roles to the given services, for small focused datasets.
+
+    Keeps permissions whose service prefix is listed, predefined roles of
+    those services (roles/compute.admin for "compute"), and custom roles
+    and other providers' roles that still grant something. Retained roles list only retained
+    permissions, so the dataset stays referentially consistent.
+    """
+    services = set(services)
+    kept = []
+    for role in roles:
+        provider = role.get('provider', GCP)
+        perms = [p for p in role.get('includedPermissions', []) if permission_parts(p, provider)[1] in services]
+        scoped = provider == GCP and not is_custom_role(role)
+        if not scoped and not perms:
+            continue
+        if scoped and role_service(role.get('name', '')) not in services:
+            continue
+        kept.append({**role, 'includedPermissions': perms})
+    return kept
+
+
+def transform_role(role):
+    """A role in API shape converted to its dataset record."""
+    return {
+        'name': role.get('name', ''),
+        'title': role.get('title', ''),
+        'description': role.get('description', ''),
+        'stage': role.get('stage', 'GA'),
+        'included_permissions': role.get('includedPermissions', []),
+        'excluded_permissions': role.get('excludedPermissions', []),
+        'etag': role.get('etag', ''),
+        'custom': is_custom_role(role),
+        'origin': role_origin(role.get('name', '')),
+        'product': product_name(role_service(role.get('name', ''))),
+        'provider': role.get('provider', GCP),
+    }
+
+
+def track_deleted_roles(previous, current_roles, deleted, collected, today):
+    """
+    The dataset's deleted_roles section: roles the API reports as deleted
+    (custom roles can be undeleted for a while before they are purged) and
+    roles that vanished since `previous`, each flagged `deleted` with the
+    date first seen gone. Entries are kept for DELETED_ROLE_RETENTION_DAYS
+    and dropped if the role comes back. Only roles whose collection (see
+    role_collection) is in `collected` count as vanished, so a parent that
+    failed to list this run doesn't look deleted.
+    """
+    live = {role['name'] for role in current_roles}
+    cutoff = (date.fromisoformat(today) - timedelta(days=DELETED_ROLE_RETENTION_DAYS)).isoformat()
+    entries = {}
+    for entry in (previous or {}).get('deleted_roles', []):
+        if entry['name'] not in live and entry.get('deleted_on', today) >= cutoff:
+            entries[entry['name']] = entry
+
+    def flagged(record, reason):
+        since = entries.get(record['name'], {}).get('deleted_on', today)
+        return {**record, 'deleted': True, 'deleted_on': since, 'reason': reason}
+
+    for record in (previous or {}).get('roles', []):
+        if record['name'] not in live and role_collection(record) in collected:
+            entries[record['name']] = flagged(record, 'removed')
+    for role in deleted:
+        entries[role['name']] = flagged(transform_role(role), 'deleted')
+    return sorted(entries.values(), key=lambda entry: (entry['deleted_on'], entry['name']), reverse=True)
+
+
 def build_dataset(roles):
     """Build the complete dataset with bidirectional references."""
     # Build permission -> roles mapping
     permission_to_roles = {}
     all_permissions = set()
+    permission_provider = {}
 
     for role in roles:
         role_name = role.get('name', '')
         perms = role.get('includedPermissions', [])
         for perm in perms:
             all_permissions.add(perm)
+            permission_provider.setdefault(perm, role.get('provider', GCP))
             if perm not in permission_to_roles:
                 permission_to_roles[perm] = []
             permission_to_roles[perm].append({
@@ -99,26 +285,19 @@ def build_dataset(roles):
             })
 
     # Build roles data
-    roles_data = []
-    for role in roles:
-        roles_data.append({
-            'name': role.get('name', ''),
-            'title': role.get('title', ''),
-            'description': role.get('description', ''),
-            'stage': role.get('stage', 'GA'),
-            'included_permissions': role.get('includedPermissions', []),
-            'etag': role.get('etag', ''),
-        })
+    roles_data = [transform_role(role) for role in roles]
 
     # Build permissions data with roles that grant them
     permissions_data = []
     for perm in sorted(all_permissions):
-        parts = perm.split('.')
+        provider, service, resource, action = permission_parts(perm, permission_provider[perm])
         permissions_data.append({
             'name': perm,
-            'service': parts[0] if parts else '',
-            'resource': parts[1] if len(parts) > 1 else '',
-            'action': parts[2] if len(parts) > 2 else '',
+            'service': service,
+            'product': product_name(service) if provider == GCP else service,
+            'resource': resource,
+            'action': action,
+            'provider': provider,
             'granted_by_roles': permission_to_roles.get(perm, []),
         })
 
@@ -127,9 +306,11 @@ def build_dataset(roles):
         'permissions': permissions_data,
         'metadata': {
             'total_roles': len(roles_data),
+            'custom_roles': sum(1 for r in roles_data if r['custom']),
             'total_permissions': len(permissions_data),
             'last_updated': datetime.utcnow().isoformat() + 'Z',
             'source': 'Google Cloud IAM API',
+            'providers': sorted({r['provider'] for r in roles_data} | {GCP}),
         }
     }
 
@@ -232,7 +413,7 @@ def generate_permission_page(perm_data):
     service = perm_data['service']
     resource = perm_data['resource']
     action = perm_data['action']
-    roles = perm_data.get('granted_by_roles', [])
+    roles = [r for r in perm_data.get('granted_by_roles', []) if not is_custom_role(r)]
 
     title = name
     description = f"GCP IAM permission {name} - granted by {len(roles)} roles. Service: {service}, Resource: {resource}, Action: {action}."
@@ -244,6 +425,8 @@ def generate_permission_page(perm_data):
     <p class="subtitle">GCP IAM Permission</p>
 
     <div class="description">
+        {f"<p>{escape(perm_data['description'])}</p>" if perm_data.get('description') else ''}
+        <p><strong>Product:</strong> {escape(perm_data.get('product') or product_name(service))}</p>
         <p><strong>Service:</strong> {escape(service)}</p>
         <p><strong>Resource:</strong> {escape(resource)}</p>
         <p><strong>Action:</strong> {escape(action)}</p>
@@ -285,6 +468,7 @@ def generate_role_page(role_data, permission_to_roles):
     html += f'''
     <h1>{escape(name)}</h1>
     <p class="subtitle">{escape(title)} {get_stage_badge(stage)}</p>
+    {f'<p class="subtitle">{escape(role_data["product"])}</p>' if role_data.get('product') else ''}
 
     <div class="description">
         <p>{escape(description)}</p>
@@ -293,11 +477,19 @@ def generate_role_page(role_data, permission_to_roles):
     <h2>Included Permissions <span class="count">({len(permissions)})</span></h2>
 '''
 
+    # Roles spanning several products list their permissions per product
+    by_product = defaultdict(list)
+    for perm in permissions:
+        by_product[product_name(perm.split('.', 1)[0])].append(perm)
+
     if permissions:
-        html += '<ul class="list">\n'
-        for perm in sorted(permissions):
-            html += f'    <li><a href="/permissions/{quote(perm)}">{escape(perm)}</a></li>\n'
-        html += '</ul>\n'
+        for product, perms in sorted(by_product.items()):
+            if len(by_product) > 1:
+                html += f'<h3>{escape(product)} <span class="count">({len(perms)})</span></h3>\n'
+            html += '<ul class="list">\n'
+            for perm in sorted(perms):
+                html += f'    <li><a href="/permissions/{quote(perm)}">{escape(perm)}</a></li>\n'
+            html += '</ul>\n'
     else:
         html += '<p style="color: var(--text-secondary);">This role has no permissions.</p>\n'
 
@@ -305,43 +497,14 @@ def generate_role_page(role_data, permission_to_roles):
     return html
 
 
-def generate_sitemap(roles, permissions):
-    """Generate sitemap.xml."""
-    now = datetime.utcnow().strftime('%Y-%m-%d')
-
-    xml = '''<?xml version="1.0" encoding="UTF-8"?>
-<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
-    <url>
-        <loc>{base}/</loc>
-        <lastmod>{date}</lastmod>
-        <changefreq>daily</changefreq>
-        <priority>1.0</priority>
-    </url>
-'''.format(base=BASE_URL, date=now)
-
-    # Add role pages
-    for role in roles:
-        role_path = role['name'].replace('roles/', '')
-        xml += f'''    <url>
-        <loc>{BASE_URL}/roles/{quote(role_path)}</loc>
-        <lastmod>{now}</lastmod>
-        <changefreq>weekly</changefreq>
-        <priority>0.8</priority>
-    </url>
-'''
-
-    # Add permission pages
-    for perm in permissions:
-        xml += f'''    <url>
-        <loc>{BASE_URL}/permissions/{quote(perm['name'])}</loc>
-        <lastmod>{now}</lastmod>
-        <changefreq>weekly</changefreq>
-        <priority>0.7</priority>
-    </url>
-'''
-
-    xml += '</urlset>\n'
-    return xml
+def write_sitemaps(storage, roles, permissions, base_url, lastmod):
+    """Write sitemap.xml (split behind a sitemap index when large); returns the paths."""
+    files = build_sitemaps(roles, permissions, base_url, lastmod)
+    # Drop numbered parts left over from a previous, larger sitemap
+    for stale in sorted(storage.static_dir.glob('sitemap-*.xml')):
+        if stale.name not in files:
+            storage.delete(stale)
+    return [storage.write_text(storage.static_dir / name, xml) for name, xml in files.items()]
 
 
 def generate_index_page(metadata):
@@ -377,96 +540,616 @@ def generate_index_page(metadata):
     return html
 
 
+def parse_args(argv=None):
+    parser = argparse.ArgumentParser(
+        description="Fetch GCP IAM roles and generate the dataset and static pages",
+        epilog="Run without a subcommand to scrape.",
+    )
+    subcommands = parser.add_subparsers(dest='command', metavar='COMMAND')
+
+    diff = subcommands.add_parser('diff', help="Compare two dataset files")
+    diff.add_argument('old', type=Path, help="Earlier iam-data.json")
+    diff.add_argument('new', type=Path, help="Later iam-data.json")
+    diff.add_argument('--json', type=Path, metavar='PATH', help="Also write the changes as JSON to PATH")
+    diff.add_argument('--markdown', type=Path, metavar='PATH', help="Also write a changelog entry (markdown) to PATH")
+    diff.add_argument('--exit-code', action='store_true', help="Exit with status 1 when the datasets differ")
+
+    validate = subcommands.add_parser('validate', help="Check a dataset for schema and integrity problems")
+    validate.add_argument('path', type=Path, help=f"Dataset file, or a directory containing {DATA_FILE_NAME}")
+    validate.add_argument(
+        '--public-key', type=Path, metavar='PEM',
+        help="Also require manifest.json to be signed by this ed25519 public key",
+    )
+
+    sitemap = subcommands.add_parser('generate-sitemap', help="Write sitemap.xml for an existing dataset")
+    sitemap.add_argument(
+        'path', type=Path, nargs='?', default=OUTPUT_DIR,
+        help=f"Dataset file, or a directory containing {DATA_FILE_NAME} (default: {OUTPUT_DIR})",
+    )
+    sitemap.add_argument('--base-url', default=BASE_URL, help=f"Site URL the sitemap points at (default: {BASE_URL})")
+    sitemap.add_argument('--output-dir', type=Path, default=OUTPUT_DIR, help=f"Data directory to write static/ under (default: {OUTPUT_DIR})")
+    sitemap.add_argument('--dry-run', action='store_true', help="Report the files that would be written")
+
+    stats = subcommands.add_parser('stats', help="Summarize a dataset: totals, services, stages, largest roles")
+    stats.add_argument('path', type=Path, help=f"Dataset file, or a directory containing {DATA_FILE_NAME}")
+    stats.add_argument('--top', type=int, default=10, metavar='N', help="Entries per list section (default: 10)")
+    stats.add_argument('--json', action='store_true', help="Print the statistics as JSON")
+
+    parser.add_argument(
+        '--impersonate-service-account', metavar='EMAIL',
+        help="Fetch as this service account, exchanging the caller's credentials via the IAM Credentials API",
+    )
+    parser.add_argument(
+        '--incremental', action='store_true',
+        help="Only refetch roles whose etag changed since the previous dataset",
+    )
+    parser.add_argument(
+        '--format', action='append', choices=FORMATS,
+        help="Dataset format to write; repeat for several (default: json)",
+    )
+    parser.add_argument(
+        '--compress', choices=sorted(COMPRESSION_SUFFIXES),
+        help="Also write compressed copies of the dataset files (e.g. iam-data.json.zst)",
+    )
+    parser.add_argument(
+        '--changelog', action='store_true',
+        help=f"Prepend this run's changes to {CHANGELOG_NAME} in the output directory",
+    )
+    parser.add_argument(
+        '--signing-key', type=Path, metavar='PEM',
+        help="Sign manifest.json with this ed25519 private key (writes manifest.json.sig; needs cryptography)",
+    )
+    parser.add_argument(
+        '--public-key', type=Path, metavar='PEM',
+        help="Only reuse a previous dataset whose manifest is signed by this ed25519 public key",
+    )
+    parser.add_argument(
+        '--split-services', action='store_true',
+        help=f"Also write one dataset per service to {SERVICES_DIR}/ with an {SERVICES_DIR}/{SHARD_INDEX} listing them",
+    )
+    parser.add_argument(
+        '--snapshots', action='store_true',
+        help="Also keep a dated copy (data/YYYY-MM-DD/iam-data.json) and point data/latest at it",
+    )
+    parser.add_argument(
+        '--retain', type=int, default=DEFAULT_RETAIN, metavar='N',
+        help=f"Dated snapshots to keep with --snapshots; older ones are deleted (default: {DEFAULT_RETAIN})",
+    )
+    parser.add_argument(
+        '--upload', action='append', default=[], metavar='URL',
+        help="Publish the dataset files to gs://bucket/path or s3://bucket/path after saving (repeatable)",
+    )
+    parser.add_argument(
+        '--s3-endpoint', metavar='URL',
+        help="S3-compatible endpoint for s3:// uploads, e.g. https://<account>.r2.cloudflarestorage.com",
+    )
+    parser.add_argument(
+        '--s3-region', metavar='REGION',
+        help="Signing region for s3:// uploads (default: AWS_REGION or us-east-1; use 'auto' for R2)",
+    )
+    parser.add_argument(
+        '--cache-control', default=DEFAULT_CACHE_CONTROL,
+        help=f"Cache-Control for uploaded files (default: '{DEFAULT_CACHE_CONTROL}')",
+    )
+    parser.add_argument(
+        '--proxy', metavar='URL',
+        help="HTTP(S) proxy for all requests, e.g. http://proxy.corp:3128 (default: HTTPS_PROXY; NO_PROXY is honored)",
+    )
+    parser.add_argument(
+        '--offline', type=Path, nargs='?', const=FIXTURES_DIR, metavar='DIR',
+        help=f"Replay canned API responses from DIR instead of calling GCP (default: {FIXTURES_DIR})",
+    )
+    parser.add_argument(
+        '--output-dir', type=Path, default=OUTPUT_DIR, metavar='DIR',
+        help=f"Directory for the dataset and static pages (default: {OUTPUT_DIR})",
+    )
+    parser.add_argument(
+        '--dry-run', action='store_true',
+        help="Fetch and transform but write nothing; report the files that would be produced",
+    )
+    parser.add_argument(
+        '--services', type=lambda value: sorted({s.strip() for s in value.split(',') if s.strip()}),
+        metavar='LIST',
+        help="Only keep permissions and roles of these services, e.g. compute,storage,iam",
+    )
+    parser.add_argument(
+        '--enrich-descriptions', action='store_true',
+        help="Add permission descriptions from the public IAM permissions reference (cached for a week)",
+    )
+    parser.add_argument(
+        '--webhook', metavar='URL', default=os.environ.get('SCRAPER_WEBHOOK_URL'),
+        help="Post a run summary here when the scrape ends or fails (default: SCRAPER_WEBHOOK_URL)",
+    )
+    parser.add_argument(
+        '--webhook-format', choices=WEBHOOK_FORMATS, default='auto',
+        help="Slack message or JSON summary (default: auto, Slack for hooks.slack.com)",
+    )
+    parser.add_argument(
+        '--log-format', choices=('text', 'json'), default='text',
+        help="Progress output on stderr: a progress bar (text) or one JSON object per line (json)",
+    )
+    parser.add_argument(
+        '--concurrency', type=int, default=DEFAULT_CONCURRENCY, metavar='N',
+        help=f"Maximum concurrent role detail requests (default: {DEFAULT_CONCURRENCY})",
+    )
+    parser.add_argument(
+        '--max-rps', type=float, metavar='N',
+        help="Never exceed N IAM API requests per second across all workers (default: adapt to 429s only)",
+    )
+    parser.add_argument(
+        '--max-retries', type=int, default=MAX_RETRIES, metavar='N',
+        help=f"Retries per request on 429/5xx and network errors (default: {MAX_RETRIES})",
+    )
+    parser.add_argument(
+        '--project', action='append', default=[], metavar='ID',
+        help="Also collect custom roles defined in this project (repeatable)",
+    )
+    parser.add_argument(
+        '--organization', action='append', default=[], metavar='ID',
+        help="Also collect custom roles defined in this organization (repeatable)",
+    )
+    parser.add_argument(
+        '--aws', action='store_true',
+        help="Also collect AWS managed policies (provider \"aws\"); needs AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY",
+    )
+    parser.add_argument(
+        '--azure', action='store_true',
+        help="Also collect Azure built-in role definitions (provider \"azure\"); needs AZURE_ACCESS_TOKEN or a service principal",
+    )
+    parser.add_argument(
+        '--parents-file', type=Path, metavar='PATH',
+        help="Also collect custom roles from every project/organization listed in PATH, concurrently",
+    )
+    parser.add_argument(
+        '--config', type=Path, metavar='PATH',
+        help=f"Settings file read before the command line (default: ./{DEFAULT_CONFIG_NAME} if present)",
+    )
+
+    pre_parser = argparse.ArgumentParser(add_help=False)
+    pre_parser.add_argument('--config', type=Path)
+    config_path = pre_parser.parse_known_args(argv)[0].config
+    if config_path is None and Path(DEFAULT_CONFIG_NAME).is_file():
+        config_path = Path(DEFAULT_CONFIG_NAME)
+    if config_path is None:
+        return parser.parse_args(argv)
+
+    try:
+        settings = load_config(config_path)
+    except ConfigError as e:
+        parser.error(str(e))
+    for action in parser._actions:
+        value = settings.get(action.dest)
+        values = value if isinstance(value, list) else [value]
+        if action.choices and value is not None and any(v not in action.choices for v in values):
+            key = next(f"{table}.{name}" for (table, name), (dest, _) in SETTINGS.items() if dest == action.dest)
+            parser.error(f"{config_path}: {key} must be one of {', '.join(map(str, action.choices))}")
+
+    # Repeatable flags given on the command line replace the file's lists
+    repeatable = {a.dest for a in parser._actions if isinstance(a, argparse._AppendAction)}
+    parser.set_defaults(**{dest: value for dest, value in settings.items() if dest not in repeatable})
+    args = parser.parse_args(argv)
+    for dest in repeatable & settings.keys():
+        if not getattr(args, dest):
+            setattr(args, dest, settings[dest])
+    args.config = config_path
+    return args
+
+
+def run_diff(args):
+    """Print the changes between two dataset files; returns the exit status."""
+    try:
+        new = load_dataset(args.new)
+        changes = compute_changes(load_dataset(args.old), new)
+    except (OSError, ValueError) as e:
+        print(f"Error: {e}", file=sys.stderr)
+        return 2
+    print(format_report(changes))
+    if args.json:
+        with open(args.json, 'w') as f:
+            json.dump(changes, f, indent=2)
+        print(f"\nWrote {args.json}", file=sys.stderr)
+    if args.markdown:
+        args.markdown.write_text(format_markdown(changes, new, date.today().isoformat()))
+        print(f"\nWrote {args.markdown}", file=sys.stderr)
+    return 1 if args.exit_code and has_changes(changes) else 0
+
+
+def dataset_file(path):
+    """A dataset path argument: the file itself, or iam-data.json (or a compressed variant) in a directory."""
+    if path.is_dir():
+        return find_variant(path / DATA_FILE_NAME) or path / DATA_FILE_NAME
+    return path
+
+
+def run_validate(args):
+    """Report dataset problems; returns 1 if any were found."""
+    path = dataset_file(args.path)
+    try:
+        public_key = load_public_key(args.public_key) if args.public_key else None
+        verify_file(path, public_key)
+        dataset = load_dataset(path)
+    except IntegrityError as e:
+        print(f"Error: {e}", file=sys.stderr)
+        return 1
+    except (OSError, ValueError, RuntimeError) as e:
+        print(f"Error: {e}", file=sys.stderr)
+        return 2
+
+    problems = validate_dataset(dataset)
+    for problem in problems:
+        print(problem)
+    if problems:
+        print(f"\n{path}: {len(problems)} problem(s)", file=sys.stderr)
+        return 1
+    print(f"{path}: OK ({len(dataset['roles'])} roles, {len(dataset['permissions'])} permissions)", file=sys.stderr)
+    return 0
+
+
+def run_generate_sitemap(args):
+    """Regenerate the sitemap from a dataset file; returns the exit status."""
+    path = dataset_file(args.path)
+    try:
+        dataset = load_dataset(path)
+    except (OSError, ValueError) as e:
+        print(f"Error: {e}", file=sys.stderr)
+        return 2
+
+    storage = StorageManager(args.output_dir, dry_run=args.dry_run)
+    public_roles = [r for r in dataset['roles'] if is_site_role(r)]
+    site_permissions = [p for p in dataset['permissions'] if p.get('provider', GCP) == GCP]
+    lastmod = (dataset['metadata'].get('last_updated') or datetime.utcnow().isoformat())[:10]
+    saved = "Would save to" if args.dry_run else "Saved to"
+    for sitemap_path in write_sitemaps(storage, public_roles, site_permissions, args.base_url, lastmod):
+        print(f"{saved} {sitemap_path}", file=sys.stderr)
+    return 0
+
+
+def run_stats(args):
+    """Print dataset statistics; returns the exit status."""
+    path = dataset_file(args.path)
+    try:
+        dataset = load_dataset(path)
+        stats = compute_stats(dataset, top=max(1, args.top))
+    except (OSError, ValueError) as e:
+        print(f"Error: {e}", file=sys.stderr)
+        return 2
+    except (KeyError, TypeError, IndexError, AttributeError) as e:
+        print(f"Error: {path} is not a valid dataset ({e!r}); run validate for details", file=sys.stderr)
+        return 2
+    print(json.dumps(stats, indent=2) if args.json else format_stats(stats))
+    return 0
+
+
 def main():
+    args = parse_args()
+    if args.command == 'diff':
+        sys.exit(run_diff(args))
+    if args.command == 'validate':
+        sys.exit(run_validate(args))
+    if args.command == 'stats':
+        sys.exit(run_stats(args))
+    if args.command == 'generate-sitemap':
+        sys.exit(run_generate_sitemap(args))
+
+    summary = RunSummary(dry_run=args.dry_run)
+    started = time.monotonic()
+    try:
+        scrape(args, summary)
+    except BaseException as e:
+        if not isinstance(e, SystemExit):
+            summary.failed = True
+            summary.errors.append(f"{type(e).__name__}: {e}")
+        elif isinstance(e.code, str):
+            summary.failed = True
+            summary.errors.append(e.code.removeprefix("Error: "))
+        elif e.code and not summary.errors:
+            summary.failed = True
+        raise
+    finally:
+        summary.duration = time.monotonic() - started
+        if args.webhook:
+            post_webhook(args.webhook, summary, args.webhook_format)
+
+
+def scrape(args, summary):
+    """Fetch, build, save and render; progress and outcome are recorded in `summary`."""
     print("GCP IAM Static Site Generator", file=sys.stderr)
     print("=" * 40, file=sys.stderr)
-
-    # Create output directories
-    OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
-    STATIC_DIR.mkdir(parents=True, exist_ok=True)
-    ROLES_DIR.mkdir(parents=True, exist_ok=True)
-    PERMISSIONS_DIR.mkdir(parents=True, exist_ok=True)
+    if args.config:
+        print(f"Settings from {args.config}", file=sys.stderr)
+
+    try:
+        storage = StorageManager(
+            args.output_dir,
+            dry_run=args.dry_run,
+            formats=args.format or ['json'],
+            compression=args.compress,
+            signing_key=load_signing_key(args.signing_key) if args.signing_key else None,
+            public_key=load_public_key(args.public_key) if args.public_key else None,
+        )
+    except RuntimeError as e:
+        sys.exit(f"Error: {e}")
+    if args.snapshots and args.retain < 1:
+        sys.exit("Error: --retain must keep at least one snapshot")
+    saved = "Would save to" if args.dry_run else "Saved to"
 
     # Get token
     print("\n1. Authenticating with GCP...", file=sys.stderr)
-    token = get_token()
-    print("   OK", file=sys.stderr)
+    proxy = configure_proxy(args.proxy)
+    if proxy:
+        print(f"   Using proxy {redact_credentials(proxy)}", file=sys.stderr)
+    if args.offline:
+        client = FixtureClient(args.offline)
+        print(f"   Offline: replaying fixtures from {args.offline}", file=sys.stderr)
+    else:
+        token = get_token(args.impersonate_service_account)
+        client = GcpClient(
+            token,
+            concurrency=args.concurrency,
+            max_retries=args.max_retries,
+            log_format=args.log_format,
+            max_rps=args.max_rps,
+        )
+        print("   OK", file=sys.stderr)
+
+    try:
+        if args.offline and any(url.startswith('gs://') for url in args.upload):
+            raise ValueError("gs:// uploads need GCP credentials and can't be combined with --offline")
+        sinks = [
+            make_sink(client, url, args.cache_control, s3_endpoint=args.s3_endpoint, s3_region=args.s3_region)
+            for url in args.upload
+        ]
+    except ValueError as e:
+        sys.exit(f"Error: {e}")
+
+    args.services = sorted(set(args.services)) if args.services else None
+    previous = storage.load_previous()
+    if args.incremental and previous is None:
+        print("   No previous dataset found; fetching everything", file=sys.stderr)
+    previous_roles = {r['name']: r for r in previous['roles']} if previous and args.incremental else None
+    if previous_roles is not None and previous['metadata'].get('services') != args.services:
+        # Reused roles would carry the previous run's permission filter
+        print("   --services changed since the previous run; fetching everything", file=sys.stderr)
+        previous_roles = None
+
+    def fetch(parent=None, progress=True):
+        """(roles, roles refetched or None when not incremental)"""
+        if previous_roles is None:
+            return client.list_roles(parent, progress=progress, show_deleted=True), None
+        return fetch_roles_incremental(client, previous_roles, parent, progress=progress)
+
+    parents = [f"projects/{p}" for p in args.project] + [f"organizations/{o}" for o in args.organization]
+    if args.parents_file:
+        try:
+            parents = list(dict.fromkeys(parents + read_parents(args.parents_file)))
+        except (OSError, ValueError) as e:
+            sys.exit(f"Error: {e}")
+
+    def fetch_parent(parent):
+        try:
+            return parent, fetch(parent, progress=len(parents) == 1), None
+        except (OSError, ValueError, FixtureError) as e:
+            return parent, None, e
 
     # Fetch roles
     print("\n2. Fetching roles from GCP IAM API...", file=sys.stderr)
-    roles = fetch_all_roles(token)
+    errors = summary.errors
+    try:
+        roles, refetched = fetch()
+    except FixtureError as e:
+        sys.exit(f"Error: {e}")
     print(f"   Fetched {len(roles)} roles", file=sys.stderr)
+    if refetched is not None:
+        print(f"   {refetched} of {len(roles)} roles changed since the previous run", file=sys.stderr)
+
+    # Custom roles: each parent lists serially, so collect parents in parallel
+    collected = {''} | set(parents)
+    if parents:
+        with ThreadPoolExecutor(max_workers=min(args.concurrency, len(parents))) as pool:
+            with Progress("Custom role parents", len(parents), log_format=args.log_format, unit='parents',
+                          enabled=len(parents) > 1) as progress:
+                results = []
+                for result in pool.map(fetch_parent, parents):
+                    results.append(result)
+                    progress.update()
+        for parent, fetched, error in results:
+            if error:
+                errors.append(f"{parent}: {error}")
+                print(f"   Warning: Skipping custom roles from {parent}: {error}", file=sys.stderr)
+                collected.discard(parent)
+                continue
+            custom, refetched = fetched
+            changed = f" ({refetched} changed)" if refetched is not None else ""
+            print(f"   Fetched {len(custom)} custom roles from {parent}{changed}", file=sys.stderr)
+            roles.extend(custom)
+
+    if args.aws:
+        aws_client = AwsFixtureClient(args.offline) if args.offline else None
+        try:
+            aws_roles = fetch_managed_policy_roles(aws_client or AwsClient())
+        except AwsError as e:
+            errors.append(f"aws: {e}")
+            print(f"   Warning: Skipping AWS managed policies: {e}", file=sys.stderr)
+        else:
+            print(f"   Fetched {len(aws_roles)} AWS managed policies", file=sys.stderr)
+            roles.extend(aws_roles)
+            collected.add(AWS)
+
+    if args.azure:
+        azure_client = AzureFixtureClient(args.offline) if args.offline else None
+        try:
+            azure_roles = fetch_built_in_roles(azure_client or AzureClient())
+        except AzureError as e:
+            errors.append(f"azure: {e}")
+            print(f"   Warning: Skipping Azure built-in roles: {e}", file=sys.stderr)
+        else:
+            print(f"   Fetched {len(azure_roles)} Azure built-in roles", file=sys.stderr)
+            roles.extend(azure_roles)
+            collected.add(AZURE)
 
     # Build dataset
     print("\n3. Building dataset...", file=sys.stderr)
+    deleted = [role for role in roles if role.get('deleted')]
+    roles = [role for role in roles if not role.get('deleted')]
+    if args.services:
+        roles = filter_services(roles, args.services)
+        deleted = filter_services(deleted, args.services)
+        print(f"   Keeping services: {', '.join(args.services)}", file=sys.stderr)
     dataset = build_dataset(roles)
+    dataset['deleted_roles'] = track_deleted_roles(
+        previous, dataset['roles'], deleted, collected, dataset['metadata']['last_updated'][:10]
+    )
+    dataset['metadata']['total_deleted_roles'] = len(dataset['deleted_roles'])
+    summary.totals = {
+        'roles': dataset['metadata']['total_roles'],
+        'custom_roles': dataset['metadata']['custom_roles'],
+        'permissions': dataset['metadata']['total_permissions'],
+    }
+    if args.services:
+        dataset['metadata']['services'] = args.services
     print(f"   {dataset['metadata']['total_roles']} roles", file=sys.stderr)
     print(f"   {dataset['metadata']['total_permissions']} permissions", file=sys.stderr)
+    if dataset['deleted_roles']:
+        print(f"   {len(dataset['deleted_roles'])} deleted roles tracked", file=sys.stderr)
+    report = quality_report(roles, dataset, dataset['metadata']['last_updated'])
+    for finding in format_findings(report):
+        print(f"   Quality: {finding} (see {QUALITY_REPORT_NAME})", file=sys.stderr)
+
+    if args.enrich_descriptions:
+        reference = fetch_reference(CACHE_DIR / "permissions-reference.html")
+        if reference:
+            described = enrich_permissions(dataset['permissions'], parse_reference(reference))
+            dataset['metadata']['described_permissions'] = described
+            print(f"   {described} permissions described from the IAM docs", file=sys.stderr)
+
+    if previous is not None:
+        changes = compute_changes(previous, dataset)
+        dataset['metadata']['changes_since_last_run'] = changes
+        summary.changes = changes
+        print(f"   {summarize(changes)}", file=sys.stderr)
+        for name in changes['roles_added']:
+            print(f"     + {name}", file=sys.stderr)
+        for name in changes['roles_removed']:
+            print(f"     - {name}", file=sys.stderr)
+        for role in changes['roles_modified']:
+            print(f"     ~ {role['name']} (+{len(role['permissions_added'])} "
+                  f"-{len(role['permissions_removed'])} permissions)", file=sys.stderr)
 
     # Save JSON data
     print("\n4. Saving JSON data...", file=sys.stderr)
-    json_path = OUTPUT_DIR / "iam-data.json"
-    with open(json_path, 'w') as f:
-        json.dump(dataset, f, indent=2)
-    print(f"   Saved to {json_path}", file=sys.stderr)
+    dataset_paths = storage.save_dataset(dataset)
+    for path in dataset_paths:
+        print(f"   {saved} {path}", file=sys.stderr)
+    report_path = storage.write_text(storage.output_dir / QUALITY_REPORT_NAME, json.dumps(report, indent=2) + '\n')
+    print(f"   {saved} {report_path}", file=sys.stderr)
+    if sinks:
+        uploaded = "Would upload" if args.dry_run else "Uploaded"
+        for destination in storage.publish(sinks):
+            print(f"   {uploaded} {destination}", file=sys.stderr)
+
+    if args.split_services:
+        shard_paths = storage.save_service_shards(dataset)
+        print(f"   {saved} {len(shard_paths) - 1} service shards and {shard_paths[-1]}", file=sys.stderr)
+
+    if args.snapshots:
+        snapshot_id = dataset['metadata']['last_updated'][:10]
+        print(f"   {saved} {storage.save_snapshot(dataset, snapshot_id)} (latest)", file=sys.stderr)
+        removed = storage.prune_snapshots(args.retain, current=snapshot_id)
+        if removed:
+            pruned = "Would remove" if args.dry_run else "Removed"
+            print(f"   {pruned} {len(removed)} snapshot(s) beyond the newest {args.retain}: {', '.join(removed)}",
+                  file=sys.stderr)
+
+    if args.changelog and summary.changes and has_changes(summary.changes):
+        path = storage.output_dir / CHANGELOG_NAME
+        entry = format_markdown(summary.changes, dataset, dataset['metadata']['last_updated'][:10])
+        storage.write_text(path, prepend_entry(path.read_text() if path.is_file() else "", entry))
+        print(f"   {saved} {path}", file=sys.stderr)
 
     # Build permission->roles lookup for role pages
     perm_to_roles = {p['name']: p['granted_by_roles'] for p in dataset['permissions']}
 
+    # The site covers GCP; custom roles are organization-internal and never published
+    public_roles = [r for r in dataset['roles'] if is_site_role(r)]
+    site_permissions = [p for p in dataset['permissions'] if p['provider'] == GCP]
+
     # Generate static pages
     print("\n5. Generating static HTML pages...", file=sys.stderr)
 
     # Permission pages
-    print("   Generating permission pages...", file=sys.stderr)
-    for i, perm in enumerate(dataset['permissions']):
-        html = generate_permission_page(perm)
-        # Use URL-safe filename
-        filename = perm['name'].replace('/', '_') + '.html'
-        filepath = PERMISSIONS_DIR / filename
-        with open(filepath, 'w') as f:
-            f.write(html)
-        if (i + 1) % 1000 == 0:
-            print(f"      {i + 1}/{len(dataset['permissions'])} permissions", file=sys.stderr)
-    print(f"   Generated {len(dataset['permissions'])} permission pages", file=sys.stderr)
+    with Progress("Permission pages", len(site_permissions), log_format=args.log_format, unit='pages') as progress:
+        for perm in site_permissions:
+            html = generate_permission_page(perm)
+            # Use URL-safe filename
+            filename = perm['name'].replace('/', '_') + '.html'
+            storage.write_text(storage.static_dir / "permissions" / filename, html)
+            progress.update()
 
     # Role pages
-    print("   Generating role pages...", file=sys.stderr)
-    for i, role in enumerate(dataset['roles']):
-        html = generate_role_page(role, perm_to_roles)
-        # Use URL-safe filename (remove roles/ prefix)
-        role_name = role['name'].replace('roles/', '')
-        filename = role_name.replace('/', '_') + '.html'
-        filepath = ROLES_DIR / filename
-        with open(filepath, 'w') as f:
-            f.write(html)
-        if (i + 1) % 500 == 0:
-            print(f"      {i + 1}/{len(dataset['roles'])} roles", file=sys.stderr)
-    print(f"   Generated {len(dataset['roles'])} role pages", file=sys.stderr)
+    with Progress("Role pages", len(public_roles), log_format=args.log_format, unit='pages') as progress:
+        for role in public_roles:
+            html = generate_role_page(role, perm_to_roles)
+            # Use URL-safe filename (remove roles/ prefix)
+            role_name = role['name'].replace('roles/', '')
+            filename = role_name.replace('/', '_') + '.html'
+            storage.write_text(storage.static_dir / "roles" / filename, html)
+            progress.update()
 
     # Generate sitemap
     print("\n6. Generating sitemap.xml...", file=sys.stderr)
-    sitemap = generate_sitemap(dataset['roles'], dataset['permissions'])
-    sitemap_path = STATIC_DIR / "sitemap.xml"
-    with open(sitemap_path, 'w') as f:
-        f.write(sitemap)
-    print(f"   Saved to {sitemap_path}", file=sys.stderr)
+    sitemap_paths = write_sitemaps(
+        storage, public_roles, site_permissions, BASE_URL, dataset['metadata']['last_updated'][:10]
+    )
+    for sitemap_path in sitemap_paths:
+        print(f"   {saved} {sitemap_path}", file=sys.stderr)
 
     # Generate index
     print("\n7. Generating index.html...", file=sys.stderr)
-    index_html = generate_index_page(dataset['metadata'])
-    index_path = STATIC_DIR / "index.html"
-    with open(index_path, 'w') as f:
-        f.write(index_html)
-    print(f"   Saved to {index_path}", file=sys.stderr)
+    index_html = generate_index_page(
+        {**dataset['metadata'], 'total_roles': len(public_roles), 'total_permissions': len(site_permissions)}
+    )
+    index_path = storage.write_text(storage.static_dir / "index.html", index_html)
+    print(f"   {saved} {index_path}", file=sys.stderr)
+
+    # Checksums (and signature) for everything written above
+    print("\n8. Writing manifest...", file=sys.stderr)
+    manifest_files = storage.write_manifest()
+    for path, _ in manifest_files:
+        print(f"   {saved} {path}", file=sys.stderr)
+    if sinks:
+        for destination in storage.publish(sinks, manifest_files):
+            print(f"   {uploaded} {destination}", file=sys.stderr)
+
+    print("\nAPI usage:", file=sys.stderr)
+    for line in client.report():
+        print(f"   {line}", file=sys.stderr)
+    if args.log_format == 'json':
+        print(json.dumps({'event': 'api_report', **client.usage()}), file=sys.stderr)
 
     print("\n" + "=" * 40, file=sys.stderr)
-    print("Done!", file=sys.stderr)
-    print(f"\nGenerated files:", file=sys.stderr)
-    print(f"  - {json_path}", file=sys.stderr)
-    print(f"  - {len(dataset['permissions'])} permission pages in {PERMISSIONS_DIR}", file=sys.stderr)
-    print(f"  - {len(dataset['roles'])} role pages in {ROLES_DIR}", file=sys.stderr)
-    print(f"  - {sitemap_path}", file=sys.stderr)
-    print(f"  - {index_path}", file=sys.stderr)
+    if args.dry_run:
+        print("Dry run: nothing was written. A real run would produce:", file=sys.stderr)
+        for line in storage.report():
+            print(f"  {line}", file=sys.stderr)
+    else:
+        print("Done!", file=sys.stderr)
+        print(f"\nGenerated files:", file=sys.stderr)
+        for path in dataset_paths:
+            print(f"  - {path}", file=sys.stderr)
+        print(f"  - {len(site_permissions)} permission pages in {storage.static_dir / 'permissions'}", file=sys.stderr)
+        print(f"  - {len(public_roles)} role pages in {storage.static_dir / 'roles'}", file=sys.stderr)
+        for sitemap_path in sitemap_paths:
+            print(f"  - {sitemap_path}", file=sys.stderr)
+        print(f"  - {index_path}", file=sys.stderr)
+        for path, _ in manifest_files:
+            print(f"  - {path}", file=sys.stderr)
+
+    # Partial collections still publish, but the run should not look clean
+    if errors:
+        print(f"\nCompleted with {len(errors)} error(s):", file=sys.stderr)
+        for error in errors:
+            print(f"  - {error}", file=sys.stderr)
+        sys.exit(1)
 
 
 if __name__ == '__main__':
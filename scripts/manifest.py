@@ -0,0 +1,118 @@
+"""
+Dataset manifests: checksums and an optional signature for emitted files.
+
+manifest.json lists the SHA-256 and size of every file a run wrote, relative
+to the directory holding the manifest, so consumers can detect truncated or
+tampered datasets. With a signing key the run also writes manifest.json.sig,
+a raw ed25519 signature over the manifest's bytes that can be checked with
+`openssl pkeyutl -verify -pubin -inkey key.pub -rawin -in manifest.json
+-sigfile manifest.json.sig`. Checksums need only the standard library;
+signing and verifying signatures need the cryptography package.
+"""
+
+import hashlib
+import json
+from pathlib import Path
+
+MANIFEST_NAME = "manifest.json"
+SIGNATURE_NAME = MANIFEST_NAME + ".sig"
+MANIFEST_VERSION = 1
+
+
+class IntegrityError(Exception):
+    """A file that doesn't match its manifest, or a manifest with a bad signature."""
+
+
+def _serialization():
+    try:
+        from cryptography.hazmat.primitives import serialization
+    except ImportError:
+        raise RuntimeError("ed25519 signatures need the cryptography package (pip install cryptography)")
+    return serialization
+
+
+def load_signing_key(path):
+    """An ed25519 private key from a PEM file (`openssl genpkey -algorithm ed25519`)."""
+    serialization = _serialization()
+    from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+    try:
+        key = serialization.load_pem_private_key(Path(path).read_bytes(), password=None)
+    except (OSError, ValueError) as e:
+        raise RuntimeError(f"Cannot load signing key {path}: {e}")
+    if not isinstance(key, Ed25519PrivateKey):
+        raise RuntimeError(f"{path} is not an ed25519 private key")
+    return key
+
+
+def load_public_key(path):
+    """An ed25519 public key from a PEM file (`openssl pkey -in key.pem -pubout`)."""
+    serialization = _serialization()
+    from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PublicKey
+    try:
+        key = serialization.load_pem_public_key(Path(path).read_bytes())
+    except (OSError, ValueError) as e:
+        raise RuntimeError(f"Cannot load public key {path}: {e}")
+    if not isinstance(key, Ed25519PublicKey):
+        raise RuntimeError(f"{path} is not an ed25519 public key")
+    return key
+
+
+def checksum(data):
+    return hashlib.sha256(data).hexdigest()
+
+
+def build_manifest(files, generated):
+    """Manifest bytes for `files` (relative name -> (sha256, size))."""
+    manifest = {
+        'version': MANIFEST_VERSION,
+        'generated': generated,
+        'files': {name: {'sha256': sha, 'size': size} for name, (sha, size) in sorted(files.items())},
+    }
+    return (json.dumps(manifest, indent=2) + '\n').encode()
+
+
+def read_manifest(directory, public_key=None):
+    """
+    The manifest in `directory`, or None if there is none. With
+    `public_key` the manifest must carry a valid signature.
+    """
+    path = Path(directory) / MANIFEST_NAME
+    if not path.is_file():
+        if public_key is not None:
+            raise IntegrityError(f"No {MANIFEST_NAME} in {directory} to verify")
+        return None
+    data = path.read_bytes()
+    if public_key is not None:
+        from cryptography.exceptions import InvalidSignature
+        signature_path = Path(directory) / SIGNATURE_NAME
+        try:
+            public_key.verify(signature_path.read_bytes(), data)
+        except OSError as e:
+            raise IntegrityError(f"{path} is not signed: {e}")
+        except InvalidSignature:
+            raise IntegrityError(f"{path} has an invalid signature")
+    try:
+        return json.loads(data)
+    except ValueError as e:
+        raise IntegrityError(f"{path}: {e}")
+
+
+def verify_file(path, public_key=None):
+    """
+    Check `path` against the manifest beside it. Files without a manifest,
+    or not listed in it, pass unless a public key demands a signed entry.
+    Raises IntegrityError on a mismatch.
+    """
+    path = Path(path)
+    manifest = read_manifest(path.parent, public_key)
+    entry = (manifest or {}).get('files', {}).get(path.name)
+    if entry is None:
+        if public_key is not None:
+            raise IntegrityError(f"{path.name} is not listed in {path.parent / MANIFEST_NAME}")
+        return
+    data = path.read_bytes()
+    if len(data) != entry.get('size') or checksum(data) != entry.get('sha256'):
+        raise IntegrityError(
+            f"{path} does not match {MANIFEST_NAME} ({len(data)} bytes, expected {entry.get('size')}); "
+            "truncated or modified"
+        )
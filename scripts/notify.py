@@ -0,0 +1,99 @@
+"""
+End-of-run webhook notifications.
+
+Posts a run summary (status, duration, totals, change counts, errors) so the
+team hears about new roles or a broken scrape without reading CI logs.
+Slack incoming webhooks get a {"text": ...} message; any other endpoint gets
+the structured summary as JSON, with the same text under "text".
+"""
+
+import json
+import sys
+import urllib.error
+import urllib.request
+from urllib.parse import urlsplit
+
+WEBHOOK_FORMATS = ('auto', 'slack', 'json')
+
+# Names listed per change category before summarizing the rest
+MAX_LISTED = 10
+
+
+class RunSummary:
+    """What a scrape run did, filled in as it progresses."""
+
+    def __init__(self, dry_run=False):
+        self.dry_run = dry_run
+        self.totals = None
+        self.changes = None
+        self.errors = []
+        self.failed = False
+        self.duration = 0.0
+
+    @property
+    def status(self):
+        if self.failed:
+            return 'failed'
+        return 'completed_with_errors' if self.errors else 'succeeded'
+
+    def as_dict(self):
+        return {
+            'status': self.status,
+            'dry_run': self.dry_run,
+            'duration_seconds': round(self.duration, 1),
+            'totals': self.totals,
+            'changes': {key: len(value) for key, value in self.changes.items()} if self.changes else None,
+            'roles_added': self.changes['roles_added'] if self.changes else [],
+            'roles_removed': self.changes['roles_removed'] if self.changes else [],
+            'errors': self.errors,
+        }
+
+
+def _duration(seconds):
+    minutes, seconds = divmod(int(seconds), 60)
+    return f"{minutes}m{seconds:02d}s" if minutes else f"{seconds}s"
+
+
+def format_text(summary):
+    """Short multi-line message suitable for chat."""
+    headline = {
+        'succeeded': "IAM scrape succeeded",
+        'completed_with_errors': "IAM scrape completed with errors",
+        'failed': "IAM scrape FAILED",
+    }[summary.status]
+    lines = [f"{headline}{' (dry run)' if summary.dry_run else ''} in {_duration(summary.duration)}"]
+    if summary.totals:
+        lines.append(
+            f"{summary.totals['roles']} roles ({summary.totals['custom_roles']} custom), "
+            f"{summary.totals['permissions']} permissions"
+        )
+    if summary.changes:
+        c = summary.changes
+        lines.append(
+            f"Roles: +{len(c['roles_added'])} -{len(c['roles_removed'])} ~{len(c['roles_modified'])}; "
+            f"Permissions: +{len(c['permissions_added'])} -{len(c['permissions_removed'])}"
+        )
+        for marker, names in (('+', c['roles_added']), ('-', c['roles_removed'])):
+            lines.extend(f"  {marker} {name}" for name in names[:MAX_LISTED])
+            if len(names) > MAX_LISTED:
+                lines.append(f"  ... and {len(names) - MAX_LISTED} more")
+    lines.extend(f"Error: {error}" for error in summary.errors)
+    return "\n".join(lines)
+
+
+def post_webhook(url, summary, fmt='auto'):
+    """Send the summary; failures are reported on stderr but never raised."""
+    if fmt == 'auto':
+        fmt = 'slack' if urlsplit(url).hostname == 'hooks.slack.com' else 'json'
+    text = format_text(summary)
+    payload = {'text': text} if fmt == 'slack' else {**summary.as_dict(), 'text': text}
+
+    req = urllib.request.Request(url, data=json.dumps(payload).encode(), method='POST')
+    req.add_header('Content-Type', 'application/json')
+    try:
+        with urllib.request.urlopen(req, timeout=15):
+            pass
+    except (urllib.error.URLError, OSError) as e:
+        print(f"Warning: Webhook notification failed: {e}", file=sys.stderr)
+        return False
+    return True
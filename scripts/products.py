@@ -0,0 +1,145 @@
+"""
+Service to product-name mapping.
+
+Permission and role names start with the API service ("run", "aiplatform"),
+which is rarely what people call the product. PRODUCTS is a curated map to
+the marketed names so pages and search can show "Cloud Run" or "Vertex AI"
+and group by product. Services not listed fall back to their service id.
+"""
+
+PRODUCTS = {
+    'accessapproval': 'Access Approval',
+    'accesscontextmanager': 'Access Context Manager',
+    'aiplatform': 'Vertex AI',
+    'alloydb': 'AlloyDB',
+    'analyticshub': 'BigQuery Analytics Hub',
+    'apigateway': 'API Gateway',
+    'apigee': 'Apigee',
+    'apihub': 'Apigee API hub',
+    'apikeys': 'API Keys',
+    'appengine': 'App Engine',
+    'apphub': 'App Hub',
+    'artifactregistry': 'Artifact Registry',
+    'assuredworkloads': 'Assured Workloads',
+    'automl': 'AutoML',
+    'backupdr': 'Backup and DR',
+    'baremetalsolution': 'Bare Metal Solution',
+    'batch': 'Batch',
+    'beyondcorp': 'BeyondCorp Enterprise',
+    'bigquery': 'BigQuery',
+    'bigquerymigration': 'BigQuery Migration Service',
+    'biglake': 'BigLake',
+    'bigtable': 'Bigtable',
+    'billing': 'Cloud Billing',
+    'binaryauthorization': 'Binary Authorization',
+    'certificatemanager': 'Certificate Manager',
+    'chronicle': 'Google Security Operations',
+    'cloudaicompanion': 'Gemini for Google Cloud',
+    'cloudasset': 'Cloud Asset Inventory',
+    'cloudbuild': 'Cloud Build',
+    'clouddeploy': 'Cloud Deploy',
+    'cloudfunctions': 'Cloud Functions',
+    'cloudkms': 'Cloud KMS',
+    'cloudscheduler': 'Cloud Scheduler',
+    'cloudsql': 'Cloud SQL',
+    'cloudtasks': 'Cloud Tasks',
+    'cloudtrace': 'Cloud Trace',
+    'cloudtranslate': 'Cloud Translation',
+    'composer': 'Cloud Composer',
+    'compute': 'Compute Engine',
+    'contactcenterinsights': 'Contact Center AI Insights',
+    'container': 'Google Kubernetes Engine',
+    'containeranalysis': 'Artifact Analysis',
+    'datacatalog': 'Data Catalog',
+    'dataflow': 'Dataflow',
+    'dataform': 'Dataform',
+    'datafusion': 'Cloud Data Fusion',
+    'datamigration': 'Database Migration Service',
+    'dataplex': 'Dataplex',
+    'dataproc': 'Dataproc',
+    'datastore': 'Firestore',
+    'datastream': 'Datastream',
+    'deploymentmanager': 'Cloud Deployment Manager',
+    'dialogflow': 'Dialogflow',
+    'discoveryengine': 'Vertex AI Search',
+    'dlp': 'Sensitive Data Protection',
+    'dns': 'Cloud DNS',
+    'documentai': 'Document AI',
+    'domains': 'Cloud Domains',
+    'edgecontainer': 'Google Distributed Cloud Edge',
+    'errorreporting': 'Error Reporting',
+    'essentialcontacts': 'Essential Contacts',
+    'eventarc': 'Eventarc',
+    'file': 'Filestore',
+    'firebase': 'Firebase',
+    'gkebackup': 'Backup for GKE',
+    'gkehub': 'GKE Enterprise',
+    'gkeonprem': 'Google Distributed Cloud (software only)',
+    'healthcare': 'Cloud Healthcare API',
+    'iam': 'Identity and Access Management',
+    'iap': 'Identity-Aware Proxy',
+    'identitytoolkit': 'Identity Platform',
+    'livestream': 'Live Stream API',
+    'logging': 'Cloud Logging',
+    'looker': 'Looker',
+    'managedidentities': 'Managed Service for Microsoft Active Directory',
+    'managedkafka': 'Managed Service for Apache Kafka',
+    'memcache': 'Memorystore for Memcached',
+    'metastore': 'Dataproc Metastore',
+    'migrationcenter': 'Migration Center',
+    'ml': 'AI Platform',
+    'monitoring': 'Cloud Monitoring',
+    'netapp': 'NetApp Volumes',
+    'networkconnectivity': 'Network Connectivity Center',
+    'networkmanagement': 'Network Intelligence Center',
+    'networksecurity': 'Cloud Next Generation Firewall',
+    'networkservices': 'Cloud Load Balancing',
+    'notebooks': 'Vertex AI Workbench',
+    'oracledatabase': 'Oracle Database@Google Cloud',
+    'orgpolicy': 'Organization Policy',
+    'osconfig': 'VM Manager',
+    'privateca': 'Certificate Authority Service',
+    'pubsub': 'Pub/Sub',
+    'pubsublite': 'Pub/Sub Lite',
+    'recaptchaenterprise': 'reCAPTCHA',
+    'recommender': 'Recommender',
+    'redis': 'Memorystore for Redis',
+    'resourcemanager': 'Resource Manager',
+    'retail': 'Vertex AI Search for commerce',
+    'run': 'Cloud Run',
+    'secretmanager': 'Secret Manager',
+    'securesourcemanager': 'Secure Source Manager',
+    'securitycenter': 'Security Command Center',
+    'servicedirectory': 'Service Directory',
+    'servicenetworking': 'Service Networking',
+    'serviceusage': 'Service Usage',
+    'spanner': 'Spanner',
+    'speech': 'Speech-to-Text',
+    'storage': 'Cloud Storage',
+    'storagetransfer': 'Storage Transfer Service',
+    'tpu': 'Cloud TPU',
+    'transcoder': 'Transcoder API',
+    'visionai': 'Vertex AI Vision',
+    'vmmigration': 'Migrate to Virtual Machines',
+    'vmwareengine': 'Google Cloud VMware Engine',
+    'vpcaccess': 'Serverless VPC Access',
+    'workflows': 'Workflows',
+    'workstations': 'Cloud Workstations',
+}
+
+
+def product_name(service):
+    """Marketed product name for an API service id, or the id itself when unmapped."""
+    return PRODUCTS.get(service, service)
+
+
+def role_service(role_name):
+    """
+    Service a predefined role belongs to ("roles/run.admin" -> "run").
+
+    Basic roles (roles/owner) and custom roles span services and yield "".
+    """
+    if not role_name.startswith('roles/'):
+        return ''
+    local = role_name[len('roles/'):]
+    return local.split('.', 1)[0] if '.' in local else ''
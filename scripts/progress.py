@@ -0,0 +1,125 @@
+"""
+Progress reporting for long scraper phases.
+
+Text mode draws a single-line bar with an ETA when stderr is a terminal and
+prints a line every 10% otherwise (so CI logs stay readable). JSON mode
+emits one machine-readable object per line instead:
+
+    {"event": "progress", "phase": "permission pages", "done": 500, "total": 12698, ...}
+"""
+
+import json
+import sys
+import threading
+import time
+
+BAR_WIDTH = 30
+# Minimum seconds between redraws (text) or events (json)
+TEXT_INTERVAL = 0.1
+JSON_INTERVAL = 1.0
+
+
+def format_duration(seconds):
+    seconds = int(seconds)
+    if seconds >= 3600:
+        return f"{seconds // 3600}h{seconds % 3600 // 60:02d}m"
+    return f"{seconds // 60:02d}:{seconds % 60:02d}"
+
+
+class Progress:
+    """
+    Tracks one phase; `total` may be None when the amount of work is unknown.
+
+    Safe to update from several threads. Keyword arguments to `update` are
+    carried along as extra counters (e.g. pages=3) in every line emitted.
+    With `enabled` false nothing is printed.
+    """
+
+    def __init__(self, phase, total=None, log_format='text', unit='items', stream=sys.stderr, enabled=True):
+        self.phase = phase
+        self.total = total
+        self.log_format = log_format
+        self.unit = unit
+        self.stream = stream
+        self.done = 0
+        self.started = time.monotonic()
+        self._last_emit = 0.0
+        self._last_decile = 0
+        self._lock = threading.Lock()
+        self.counters = {}
+        self.enabled = enabled
+        self._interactive = log_format == 'text' and stream.isatty()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+    def eta(self):
+        """Estimated seconds remaining, or None if unknown."""
+        elapsed = time.monotonic() - self.started
+        if not self.total or not self.done or elapsed <= 0:
+            return None
+        return (self.total - self.done) * elapsed / self.done
+
+    def update(self, n=1, **counters):
+        with self._lock:
+            self.done += n
+            self.counters.update(counters)
+            now = time.monotonic()
+            interval = TEXT_INTERVAL if self._interactive else JSON_INTERVAL
+            if now - self._last_emit >= interval:
+                self._emit(final=False)
+                self._last_emit = now
+
+    def close(self):
+        with self._lock:
+            self._emit(final=True)
+            if self._interactive and self.enabled:
+                self.stream.write('\n')
+            self.stream.flush()
+
+    def _emit(self, final):
+        if not self.enabled:
+            return
+        elapsed = time.monotonic() - self.started
+        eta = self.eta()
+
+        if self.log_format == 'json':
+            self.stream.write(json.dumps({
+                'event': 'progress',
+                'phase': self.phase,
+                'done': self.done,
+                'total': self.total,
+                **self.counters,
+                'elapsed_s': round(elapsed, 1),
+                'eta_s': None if eta is None or final else round(eta, 1),
+                'finished': final,
+            }) + '\n')
+            self.stream.flush()
+            return
+
+        counts = f"{self.done}/{self.total}" if self.total else f"{self.done}"
+        extra = "".join(f", {value} {name}" for name, value in self.counters.items())
+        if self._interactive:
+            if self.total:
+                filled = int(BAR_WIDTH * min(self.done / self.total, 1))
+                bar = f"[{'#' * filled}{'.' * (BAR_WIDTH - filled)}] "
+            else:
+                bar = ""
+            timing = f"ETA {format_duration(eta)}" if eta is not None and not final else format_duration(elapsed)
+            self.stream.write(f"\r   {self.phase}: {bar}{counts} {self.unit}{extra} {timing}\033[K")
+            self.stream.flush()
+            return
+
+        # Non-interactive text: every 10% (or every update when the total is
+        # unknown) and at the end
+        decile = int(10 * self.done / self.total) if self.total else self._last_decile + 1
+        if final or decile > self._last_decile:
+            self._last_decile = decile
+            pct = f" ({100 * self.done // self.total}%)" if self.total else ""
+            eta_text = f", ETA {format_duration(eta)}" if eta is not None and not final else ""
+            self.stream.write(
+                f"   {self.phase}: {counts} {self.unit}{pct}{extra}, {format_duration(elapsed)} elapsed{eta_text}\n"
+            )
@@ -0,0 +1,57 @@
+"""
+Data-quality report for a freshly built dataset.
+
+Unlike validation, nothing here makes a dataset unusable: these are oddities
+in what the IAM API returned, worth a look when they show up or grow.
+"""
+
+from collections import Counter
+
+QUALITY_REPORT_NAME = "quality-report.json"
+
+
+def is_malformed_permission(name):
+    """GCP permission names are service.resource.verb, three non-empty parts."""
+    parts = name.split('.')
+    return len(parts) != 3 or not all(parts)
+
+
+def quality_report(roles, dataset, generated):
+    """
+    Findings for the API roles a dataset was built from and the dataset:
+    orphan permissions (granted by no role), roles with zero permissions,
+    GCP permission names that aren't three dot-separated parts, and role
+    names returned more than once.
+    """
+    orphans = sorted(p['name'] for p in dataset['permissions'] if not p['granted_by_roles'])
+    empty = sorted(r['name'] for r in dataset['roles'] if not r['included_permissions'])
+    malformed = sorted(
+        p['name'] for p in dataset['permissions']
+        if p.get('provider', 'gcp') == 'gcp' and is_malformed_permission(p['name'])
+    )
+    counts = Counter(role.get('name', '') for role in roles)
+    duplicates = [{'name': name, 'count': count} for name, count in sorted(counts.items()) if count > 1]
+    return {
+        'generated': generated,
+        'summary': {
+            'orphan_permissions': len(orphans),
+            'empty_roles': len(empty),
+            'malformed_permissions': len(malformed),
+            'duplicate_roles': len(duplicates),
+        },
+        'orphan_permissions': orphans,
+        'empty_roles': empty,
+        'malformed_permissions': malformed,
+        'duplicate_roles': duplicates,
+    }
+
+
+def format_findings(report):
+    """One line per non-empty finding, for the run log."""
+    labels = {
+        'orphan_permissions': "orphan permissions",
+        'empty_roles': "roles with no permissions",
+        'malformed_permissions': "malformed permission names",
+        'duplicate_roles': "duplicate roles",
+    }
+    return [f"{count} {labels[key]}" for key, count in report['summary'].items() if count]
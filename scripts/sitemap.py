@@ -0,0 +1,79 @@
+"""
+Sitemap generation.
+
+Lists the home page and every role, permission and service page. Search
+engines accept at most 50,000 URLs per sitemap file, so larger sites are
+split into sitemap-1.xml, sitemap-2.xml, ... with sitemap.xml becoming a
+sitemap index that points at them.
+"""
+
+from html import escape
+from urllib.parse import quote
+
+SITEMAP_NAME = "sitemap.xml"
+MAX_URLS_PER_SITEMAP = 50_000
+
+
+def sitemap_urls(roles, permissions, base_url):
+    """(loc, changefreq, priority) for every page; `roles` should exclude custom roles."""
+    base_url = base_url.rstrip('/')
+    urls = [(f"{base_url}/", 'daily', '1.0')]
+    urls += [
+        (f"{base_url}/roles/{quote(role['name'].replace('roles/', ''))}", 'weekly', '0.8')
+        for role in roles
+    ]
+    urls += [(f"{base_url}/permissions/{quote(perm['name'])}", 'weekly', '0.7') for perm in permissions]
+    services = sorted({perm['service'] for perm in permissions if perm.get('service')})
+    urls += [(f"{base_url}/services/{quote(service)}", 'weekly', '0.6') for service in services]
+    return urls
+
+
+def render_urlset(urls, lastmod):
+    entries = ''.join(
+        f"    <url>\n"
+        f"        <loc>{escape(loc)}</loc>\n"
+        f"        <lastmod>{lastmod}</lastmod>\n"
+        f"        <changefreq>{changefreq}</changefreq>\n"
+        f"        <priority>{priority}</priority>\n"
+        f"    </url>\n"
+        for loc, changefreq, priority in urls
+    )
+    return (
+        '<?xml version="1.0" encoding="UTF-8"?>\n'
+        '<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">\n'
+        f"{entries}</urlset>\n"
+    )
+
+
+def render_index(names, base_url, lastmod):
+    base_url = base_url.rstrip('/')
+    entries = ''.join(
+        f"    <sitemap>\n"
+        f"        <loc>{escape(f'{base_url}/{name}')}</loc>\n"
+        f"        <lastmod>{lastmod}</lastmod>\n"
+        f"    </sitemap>\n"
+        for name in names
+    )
+    return (
+        '<?xml version="1.0" encoding="UTF-8"?>\n'
+        '<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">\n'
+        f"{entries}</sitemapindex>\n"
+    )
+
+
+def build_sitemaps(roles, permissions, base_url, lastmod, max_urls=MAX_URLS_PER_SITEMAP):
+    """
+    File name -> XML for the sitemap files of a site.
+
+    A single sitemap.xml when everything fits, otherwise numbered sitemap
+    files plus a sitemap.xml index.
+    """
+    urls = sitemap_urls(roles, permissions, base_url)
+    if len(urls) <= max_urls:
+        return {SITEMAP_NAME: render_urlset(urls, lastmod)}
+
+    files = {}
+    for i in range(0, len(urls), max_urls):
+        files[f"sitemap-{i // max_urls + 1}.xml"] = render_urlset(urls[i:i + max_urls], lastmod)
+    files[SITEMAP_NAME] = render_index(list(files), base_url, lastmod)
+    return files
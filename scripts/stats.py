@@ -0,0 +1,77 @@
+"""
+Dataset statistics.
+
+A quick sanity report on an iam-data.json: totals, how permissions spread
+across services and roles across launch stages, the largest roles, and
+permissions only a single role grants.
+"""
+
+from collections import Counter
+
+
+def compute_stats(dataset, top=10):
+    """Summary numbers for a dataset; list sections are capped at `top` entries."""
+    roles = dataset['roles']
+    permissions = dataset['permissions']
+
+    by_service = Counter(p['service'] for p in permissions)
+    by_stage = Counter(r['stage'] for r in roles)
+    largest = sorted(roles, key=lambda r: (-len(r['included_permissions']), r['name']))
+    single_role = sorted(
+        (p['name'], p['granted_by_roles'][0]['name'])
+        for p in permissions
+        if len(p['granted_by_roles']) == 1
+    )
+    ungranted = sorted(p['name'] for p in permissions if not p['granted_by_roles'])
+
+    return {
+        'total_roles': len(roles),
+        'total_permissions': len(permissions),
+        'total_services': len(by_service),
+        'custom_roles': sum(1 for r in roles if r.get('custom')),
+        'roles_by_stage': dict(by_stage.most_common()),
+        'permissions_by_service': dict(by_service.most_common(top)),
+        'largest_roles': [
+            {'name': r['name'], 'permissions': len(r['included_permissions'])}
+            for r in largest[:top]
+        ],
+        'single_role_permissions_count': len(single_role),
+        'single_role_permissions': [
+            {'permission': perm, 'role': role} for perm, role in single_role[:top]
+        ],
+        'ungranted_permissions': ungranted,
+    }
+
+
+def format_stats(stats):
+    """Multi-line, human-readable rendering of compute_stats output."""
+    lines = [
+        f"Roles:       {stats['total_roles']} ({stats['custom_roles']} custom)",
+        f"Permissions: {stats['total_permissions']}",
+        f"Services:    {stats['total_services']}",
+    ]
+
+    def section(title, rows):
+        if rows:
+            width = max(len(label) for label, _ in rows)
+            lines.append("")
+            lines.append(title)
+            lines.extend(f"  {label.ljust(width)}  {value}" for label, value in rows)
+
+    section("Roles by stage", list(stats['roles_by_stage'].items()))
+    section(
+        f"Top {len(stats['permissions_by_service'])} services by permissions",
+        list(stats['permissions_by_service'].items()),
+    )
+    section("Largest roles", [(r['name'], r['permissions']) for r in stats['largest_roles']])
+    section(
+        f"Permissions granted by only one role ({stats['single_role_permissions_count']}, "
+        f"first {len(stats['single_role_permissions'])})",
+        [(p['permission'], p['role']) for p in stats['single_role_permissions']],
+    )
+    if stats['ungranted_permissions']:
+        section(
+            f"Permissions granted by no role ({len(stats['ungranted_permissions'])})",
+            [(name, '') for name in stats['ungranted_permissions']],
+        )
+    return "\n".join(lines)
@@ -0,0 +1,382 @@
+"""
+Dataset persistence for the IAM scraper.
+
+StorageManager owns everything the scraper reads from or writes to the data
+directory, so output formats and destinations are changed in one place.
+"""
+
+import gzip
+import json
+import re
+import shutil
+import sys
+from collections import defaultdict
+from datetime import datetime, timezone
+from pathlib import Path
+
+from manifest import MANIFEST_NAME, SIGNATURE_NAME, IntegrityError, build_manifest, checksum, verify_file
+
+DATA_FILE_NAME = "iam-data.json"
+ROLES_NDJSON = "roles.ndjson"
+PERMISSIONS_NDJSON = "permissions.ndjson"
+
+# Dataset output formats selectable with --format
+FORMATS = ('json', 'ndjson')
+
+# Compression for dataset files selectable with --compress, and their suffixes
+COMPRESSION_SUFFIXES = {'gzip': '.gz', 'zstd': '.zst'}
+
+# Dated snapshot directories (data/2024-07-01/), as the backend expects them
+SNAPSHOT_ID = re.compile(r'^\d{4}-\d{2}-\d{2}$')
+LATEST_LINK = "latest"
+LATEST_MANIFEST = "latest.json"
+DEFAULT_RETAIN = 30
+
+# Per-service shards (data/services/compute.json) and the index listing them
+SERVICES_DIR = "services"
+SHARD_INDEX = "index.json"
+SHARD_INDEX_VERSION = 1
+
+
+def _zstd():
+    """A module exposing zstd compress/decompress (stdlib on 3.14+, else zstandard)."""
+    try:
+        from compression import zstd
+        return zstd
+    except ImportError:
+        pass
+    try:
+        import zstandard
+    except ImportError:
+        raise RuntimeError("zstd needs Python 3.14+ or the zstandard package (pip install zstandard)")
+
+    class Zstandard:
+        @staticmethod
+        def compress(data):
+            return zstandard.ZstdCompressor(level=19).compress(data)
+
+        @staticmethod
+        def decompress(data):
+            return zstandard.ZstdDecompressor().decompressobj().decompress(data)
+
+    return Zstandard
+
+
+def compress(data, method):
+    if method == 'gzip':
+        return gzip.compress(data, compresslevel=9, mtime=0)
+    return _zstd().compress(data)
+
+
+def read_bytes(path):
+    """File contents, decompressed when the name ends in .gz or .zst."""
+    path = Path(path)
+    data = path.read_bytes()
+    if path.suffix == '.gz':
+        return gzip.decompress(data)
+    if path.suffix == '.zst':
+        return _zstd().decompress(data)
+    return data
+
+
+def find_variant(path):
+    """`path`, or its compressed variant when only that exists; None if neither does."""
+    path = Path(path)
+    for candidate in [path] + [path.with_name(path.name + s) for s in COMPRESSION_SUFFIXES.values()]:
+        if candidate.is_file():
+            return candidate
+    return None
+
+
+def load_dataset(path):
+    """Read a dataset file written by StorageManager.save_dataset, compressed or not."""
+    return json.loads(read_bytes(path))
+
+
+def to_ndjson(records):
+    """One compact JSON object per line, ready for BigQuery or jq."""
+    return ''.join(json.dumps(record, separators=(',', ':')) + '\n' for record in records)
+
+
+def load_ndjson(path):
+    return [json.loads(line) for line in read_bytes(path).decode().splitlines() if line.strip()]
+
+
+def shard_id(permission):
+    """
+    Shard a permission belongs to: its service for GCP ("compute"), else
+    provider and service ("aws.s3"), reduced to file-name-safe characters.
+    """
+    provider = permission.get('provider', 'gcp')
+    name = permission['service'] if provider == 'gcp' else f"{provider}.{permission['service']}"
+    return re.sub(r'[^A-Za-z0-9._-]', '_', name) or '_'
+
+
+def service_shards(dataset):
+    """
+    The dataset split by shard_id, each shard a complete dataset: the
+    service's permissions, and the roles granting any of them listing only
+    those permissions. Merging shards gives back the full role grants.
+    """
+    permissions = defaultdict(list)
+    for permission in dataset['permissions']:
+        permissions[shard_id(permission)].append(permission)
+
+    shards = {}
+    for sid, perms in sorted(permissions.items()):
+        names = {p['name'] for p in perms}
+        roles = []
+        for role in dataset['roles']:
+            granted = [p for p in role['included_permissions'] if p in names]
+            if granted:
+                roles.append({**role, 'included_permissions': granted})
+        shards[sid] = {
+            'roles': roles,
+            'permissions': perms,
+            'metadata': {
+                **dataset['metadata'],
+                'total_roles': len(roles),
+                'custom_roles': sum(1 for r in roles if r.get('custom')),
+                'total_permissions': len(perms),
+                'service': sid,
+            },
+        }
+    return shards
+
+
+def format_size(size):
+    for unit in ('B', 'KB', 'MB', 'GB'):
+        if size < 1024 or unit == 'GB':
+            return f"{size:.0f} {unit}" if unit == 'B' else f"{size:.1f} {unit}"
+        size /= 1024
+
+
+class StorageManager:
+    """
+    Reads and writes files under the data directory.
+
+    With dry_run set nothing touches the disk; writes are only recorded so
+    `report()` can show what a real run would produce. `formats` picks the
+    dataset encodings save_dataset writes: "json" (iam-data.json, read by
+    the backend and edge) and/or "ndjson" (roles.ndjson and
+    permissions.ndjson, one record per line). With `compression` set
+    ("gzip" or "zstd") each dataset file also gets a compressed sibling
+    such as iam-data.json.zst.
+
+    Every write is checksummed for write_manifest(); with `signing_key`
+    (an ed25519 private key) manifests are signed, and with `public_key`
+    load_previous() only trusts a dataset covered by a valid signature.
+    """
+
+    def __init__(self, output_dir, dry_run=False, formats=('json',), compression=None,
+                 signing_key=None, public_key=None):
+        self.output_dir = Path(output_dir)
+        self.dry_run = dry_run
+        self.formats = formats
+        self.compression = compression
+        self.signing_key = signing_key
+        self.public_key = public_key
+        self.written = []
+        self.checksums = {}
+        self.dataset_files = []
+        if compression == 'zstd':
+            _zstd()  # fail before fetching rather than after the first write
+
+    @property
+    def dataset_path(self):
+        return self.output_dir / DATA_FILE_NAME
+
+    @property
+    def static_dir(self):
+        """Root of the generated HTML pages, sitemap and index."""
+        return self.output_dir / "static"
+
+    def load_previous(self):
+        """The dataset written by the last run, or None if there is none."""
+        path = find_variant(self.dataset_path)
+        roles_path = find_variant(self.output_dir / ROLES_NDJSON)
+        permissions_path = find_variant(self.output_dir / PERMISSIONS_NDJSON)
+        try:
+            if path:
+                verify_file(path, self.public_key)
+                return load_dataset(path)
+            if roles_path and permissions_path:
+                verify_file(roles_path, self.public_key)
+                verify_file(permissions_path, self.public_key)
+                return {
+                    'roles': load_ndjson(roles_path),
+                    'permissions': load_ndjson(permissions_path),
+                    'metadata': {},
+                }
+        except (OSError, ValueError, IntegrityError) as e:
+            print(f"   Warning: Ignoring unreadable previous dataset in {self.output_dir}: {e}", file=sys.stderr)
+        return None
+
+    def write_bytes(self, path, data):
+        """Write a file (creating parent directories) and return its path."""
+        path = Path(path)
+        self.written.append((path, len(data)))
+        self.checksums[path] = (checksum(data), len(data))
+        if not self.dry_run:
+            path.parent.mkdir(parents=True, exist_ok=True)
+            path.write_bytes(data)
+        return path
+
+    def write_text(self, path, text):
+        return self.write_bytes(path, text.encode())
+
+    def delete(self, path):
+        """Remove a file the current run no longer produces."""
+        path = Path(path)
+        if not self.dry_run:
+            path.unlink(missing_ok=True)
+
+    def _write_dataset_file(self, path, text):
+        """Write a dataset file plus its compressed sibling; returns the paths."""
+        data = text.encode()
+        files = [(path, data)]
+        if self.compression:
+            suffix = COMPRESSION_SUFFIXES[self.compression]
+            files.append((path.with_name(path.name + suffix), compress(data, self.compression)))
+        self.dataset_files += files
+        return [self.write_bytes(p, d) for p, d in files]
+
+    def _write_manifest(self, directory, files):
+        """
+        Write (and sign) a manifest for `files` (paths under `directory`);
+        returns (path, data) for the manifest and signature, ready for publish().
+        """
+        generated = datetime.now(timezone.utc).isoformat().replace('+00:00', 'Z')
+        entries = {path.relative_to(directory).as_posix(): self.checksums[path] for path in files}
+        data = build_manifest(entries, generated)
+        written = [(directory / MANIFEST_NAME, data)]
+        if self.signing_key is not None:
+            written.append((directory / SIGNATURE_NAME, self.signing_key.sign(data)))
+        for path, content in written:
+            self.write_bytes(path, content)
+        return written
+
+    def write_manifest(self):
+        """
+        Write manifest.json covering every file written under the data
+        directory so far. Call it last; returns what _write_manifest does.
+        """
+        skip = {self.output_dir / MANIFEST_NAME, self.output_dir / SIGNATURE_NAME}
+        files = [path for path in self.checksums if path not in skip and path.is_relative_to(self.output_dir)]
+        return self._write_manifest(self.output_dir, files)
+
+    def save_dataset(self, dataset):
+        """Write the dataset in every configured format and return the paths written."""
+        # A run that dies before write_manifest() must not leave the old
+        # manifest describing the new files
+        self.delete(self.output_dir / MANIFEST_NAME)
+        self.delete(self.output_dir / SIGNATURE_NAME)
+        paths = []
+        if 'json' in self.formats:
+            paths += self._write_dataset_file(self.dataset_path, json.dumps(dataset, indent=2))
+        if 'ndjson' in self.formats:
+            paths += self._write_dataset_file(self.output_dir / ROLES_NDJSON, to_ndjson(dataset['roles']))
+            paths += self._write_dataset_file(self.output_dir / PERMISSIONS_NDJSON, to_ndjson(dataset['permissions']))
+        return paths
+
+    def save_service_shards(self, dataset):
+        """
+        Write one dataset per service under services/ plus services/index.json
+        listing them, removing shards of services that no longer exist.
+        Returns the paths written, index last.
+        """
+        directory = self.output_dir / SERVICES_DIR
+        shards = service_shards(dataset)
+        paths = []
+        entries = {}
+        for sid, shard in shards.items():
+            paths.append(self.write_text(directory / f"{sid}.json", json.dumps(shard, indent=2)))
+            entries[sid] = {
+                'path': f"{sid}.json",
+                'provider': shard['permissions'][0].get('provider', 'gcp'),
+                'product': shard['permissions'][0].get('product', ''),
+                'roles': shard['metadata']['total_roles'],
+                'permissions': shard['metadata']['total_permissions'],
+            }
+        if directory.is_dir():
+            for stale in directory.glob('*.json'):
+                if stale.name != SHARD_INDEX and stale.stem not in shards:
+                    self.delete(stale)
+        index = {
+            'version': SHARD_INDEX_VERSION,
+            'last_updated': dataset['metadata'].get('last_updated'),
+            'services': entries,
+        }
+        paths.append(self.write_text(directory / SHARD_INDEX, json.dumps(index, indent=2) + '\n'))
+        return paths
+
+    def snapshot_ids(self):
+        """Dated snapshot directories present under the data directory, oldest first."""
+        if not self.output_dir.is_dir():
+            return []
+        return sorted(p.name for p in self.output_dir.iterdir() if p.is_dir() and SNAPSHOT_ID.match(p.name))
+
+    def save_snapshot(self, dataset, snapshot_id):
+        """
+        Write the dataset to `<snapshot_id>/iam-data.json` and point "latest"
+        at it: a `latest` symlink where the filesystem allows one, and always
+        a latest.json manifest. Rerunning on the same day replaces that
+        day's snapshot. Returns the snapshot's dataset path.
+        Each snapshot carries its own manifest.
+        """
+        path = self.write_text(self.output_dir / snapshot_id / DATA_FILE_NAME, json.dumps(dataset, indent=2))
+        self._write_manifest(path.parent, [path])
+        manifest = {
+            'snapshot': snapshot_id,
+            'path': f"{snapshot_id}/{DATA_FILE_NAME}",
+            'last_updated': dataset['metadata'].get('last_updated'),
+        }
+        self.write_text(self.output_dir / LATEST_MANIFEST, json.dumps(manifest, indent=2) + '\n')
+
+        link = self.output_dir / LATEST_LINK
+        if not self.dry_run and (link.is_symlink() or not link.exists()):
+            try:
+                link.unlink(missing_ok=True)
+                link.symlink_to(snapshot_id, target_is_directory=True)
+            except OSError as e:
+                print(f"   Warning: Could not update {link} symlink ({e}); {LATEST_MANIFEST} is current", file=sys.stderr)
+        return path
+
+    def prune_snapshots(self, retain, current):
+        """Delete all but the newest `retain` snapshots (counting `current`); returns the IDs removed."""
+        ids = sorted(set(self.snapshot_ids()) | {current})
+        stale = ids[:-retain] if retain > 0 else []
+        for snapshot_id in stale:
+            if not self.dry_run:
+                shutil.rmtree(self.output_dir / snapshot_id)
+        return stale
+
+    def publish(self, sinks, files=None):
+        """
+        Upload each (path, data) in `files` (default: every dataset file
+        saved so far) to each sink; returns the destinations.
+        """
+        destinations = []
+        for sink in sinks:
+            for path, data in files if files is not None else self.dataset_files:
+                if not self.dry_run:
+                    sink.upload(path.name, data)
+                destinations.append(sink.destination(path.name))
+        return destinations
+
+    def report(self):
+        """Lines summarizing recorded writes, grouping directories of many files."""
+        by_dir = defaultdict(list)
+        for path, size in self.written:
+            by_dir[path.parent].append((path, size))
+
+        lines = []
+        for directory, files in sorted(by_dir.items()):
+            if len(files) > 3:
+                total = sum(size for _, size in files)
+                lines.append(f"{directory}/  {len(files)} files, {format_size(total)}")
+            else:
+                lines.extend(f"{path}  {format_size(size)}" for path, size in files)
+        total = sum(size for _, size in self.written)
+        lines.append(f"Total: {len(self.written)} files, {format_size(total)}")
+        return lines
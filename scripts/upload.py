@@ -0,0 +1,179 @@
+"""
+Upload sinks that publish dataset files after a scrape.
+
+Each sink takes a destination URL (gs://bucket/prefix or s3://bucket/prefix)
+and uploads files under that prefix, keeping their names.
+"""
+
+import hashlib
+import hmac
+import json
+import os
+import time
+import urllib.error
+import urllib.request
+import uuid
+from datetime import datetime, timezone
+from pathlib import Path
+from urllib.parse import quote, urlparse
+
+DEFAULT_CACHE_CONTROL = "public, max-age=300"
+
+CONTENT_TYPES = {
+    '.json': 'application/json',
+    '.ndjson': 'application/x-ndjson',
+    '.gz': 'application/gzip',
+    '.zst': 'application/zstd',
+    '.html': 'text/html; charset=utf-8',
+    '.xml': 'application/xml',
+}
+
+
+def content_type(path):
+    return CONTENT_TYPES.get(Path(path).suffix, 'application/octet-stream')
+
+
+def split_destination(url, scheme):
+    """("bucket", "prefix/") from scheme://bucket/prefix."""
+    parsed = urlparse(url)
+    if parsed.scheme != scheme or not parsed.netloc:
+        raise ValueError(f"Expected {scheme}://bucket/path, got '{url}'")
+    prefix = parsed.path.strip('/')
+    return parsed.netloc, f"{prefix}/" if prefix else ""
+
+
+class GcsSink:
+    """Google Cloud Storage via the JSON API, authenticated with the scraper's token."""
+
+    UPLOAD_URL = "https://storage.googleapis.com/upload/storage/v1/b/{bucket}/o?uploadType=multipart"
+
+    def __init__(self, client, url, cache_control=DEFAULT_CACHE_CONTROL):
+        self.client = client
+        self.bucket, self.prefix = split_destination(url, 'gs')
+        self.cache_control = cache_control
+
+    def destination(self, name):
+        return f"gs://{self.bucket}/{self.prefix}{name}"
+
+    def upload(self, name, data):
+        metadata = {
+            'name': self.prefix + name,
+            'contentType': content_type(name),
+            'cacheControl': self.cache_control,
+        }
+        boundary = uuid.uuid4().hex
+        body = b''.join([
+            f"--{boundary}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n".encode(),
+            json.dumps(metadata).encode(),
+            f"\r\n--{boundary}\r\nContent-Type: {metadata['contentType']}\r\n\r\n".encode(),
+            data,
+            f"\r\n--{boundary}--\r\n".encode(),
+        ])
+        self.client.request(
+            self.UPLOAD_URL.format(bucket=quote(self.bucket, safe='')),
+            method='POST',
+            body=body,
+            content_type=f"multipart/related; boundary={boundary}",
+        )
+
+
+def sigv4_authorization(method, url, headers, payload_hash, access_key, secret_key, region, now, service='s3'):
+    """
+    AWS Signature Version 4 Authorization header for a request.
+
+    `headers` must already contain every header to sign (host and the
+    x-amz-* headers); the returned value is sent as Authorization.
+    """
+    parsed = urlparse(url)
+    amz_date = now.strftime('%Y%m%dT%H%M%SZ')
+    date = amz_date[:8]
+
+    signed = sorted((k.lower(), ' '.join(str(v).split())) for k, v in headers.items())
+    canonical_headers = ''.join(f"{k}:{v}\n" for k, v in signed)
+    signed_headers = ';'.join(k for k, _ in signed)
+    query = '&'.join(sorted(parsed.query.split('&'))) if parsed.query else ''
+    canonical_request = '\n'.join([
+        method, parsed.path or '/', query, canonical_headers, signed_headers, payload_hash,
+    ])
+
+    scope = f"{date}/{region}/{service}/aws4_request"
+    string_to_sign = '\n'.join([
+        'AWS4-HMAC-SHA256', amz_date, scope, hashlib.sha256(canonical_request.encode()).hexdigest(),
+    ])
+
+    key = f"AWS4{secret_key}".encode()
+    for part in (date, region, service, 'aws4_request'):
+        key = hmac.new(key, part.encode(), hashlib.sha256).digest()
+    signature = hmac.new(key, string_to_sign.encode(), hashlib.sha256).hexdigest()
+
+    return f"AWS4-HMAC-SHA256 Credential={access_key}/{scope}, SignedHeaders={signed_headers}, Signature={signature}"
+
+
+class S3Sink:
+    """
+    S3 or an S3-compatible store (Cloudflare R2, MinIO) via signed PUTs.
+
+    Credentials come from AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY (and
+    AWS_SESSION_TOKEN if set). With an endpoint override objects are
+    addressed path-style ({endpoint}/{bucket}/{key}), which every
+    S3-compatible service accepts; R2 expects region "auto".
+    """
+
+    MAX_ATTEMPTS = 3
+
+    def __init__(self, url, endpoint=None, region=None, cache_control=DEFAULT_CACHE_CONTROL):
+        self.bucket, self.prefix = split_destination(url, 's3')
+        self.region = region or os.environ.get('AWS_REGION') or os.environ.get('AWS_DEFAULT_REGION') or 'us-east-1'
+        endpoint = endpoint or os.environ.get('AWS_ENDPOINT_URL_S3') or os.environ.get('AWS_ENDPOINT_URL')
+        if endpoint:
+            self.base_url = f"{endpoint.rstrip('/')}/{quote(self.bucket)}"
+        else:
+            self.base_url = f"https://{self.bucket}.s3.{self.region}.amazonaws.com"
+        self.cache_control = cache_control
+
+        self.access_key = os.environ.get('AWS_ACCESS_KEY_ID')
+        self.secret_key = os.environ.get('AWS_SECRET_ACCESS_KEY')
+        self.session_token = os.environ.get('AWS_SESSION_TOKEN')
+        if not self.access_key or not self.secret_key:
+            raise ValueError("S3 uploads need AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY")
+
+    def destination(self, name):
+        return f"s3://{self.bucket}/{self.prefix}{name}"
+
+    def upload(self, name, data):
+        url = f"{self.base_url}/{quote(self.prefix + name)}"
+        payload_hash = hashlib.sha256(data).hexdigest()
+
+        for attempt in range(1, self.MAX_ATTEMPTS + 1):
+            now = datetime.now(timezone.utc)
+            headers = {
+                'Host': urlparse(url).netloc,
+                'Content-Type': content_type(name),
+                'Cache-Control': self.cache_control,
+                'x-amz-content-sha256': payload_hash,
+                'x-amz-date': now.strftime('%Y%m%dT%H%M%SZ'),
+            }
+            if self.session_token:
+                headers['x-amz-security-token'] = self.session_token
+            headers['Authorization'] = sigv4_authorization(
+                'PUT', url, headers, payload_hash, self.access_key, self.secret_key, self.region, now,
+            )
+
+            req = urllib.request.Request(url, data=data, method='PUT', headers=headers)
+            try:
+                with urllib.request.urlopen(req, timeout=120):
+                    return
+            except urllib.error.HTTPError as e:
+                if e.code < 500 or attempt == self.MAX_ATTEMPTS:
+                    detail = e.read().decode(errors='replace')
+                    raise RuntimeError(f"S3 upload of {self.destination(name)} failed (HTTP {e.code}): {detail}")
+            time.sleep(2 ** attempt)
+
+
+def make_sink(client, url, cache_control=DEFAULT_CACHE_CONTROL, s3_endpoint=None, s3_region=None):
+    """The sink for a destination URL, chosen by scheme."""
+    if url.startswith('gs://'):
+        return GcsSink(client, url, cache_control)
+    if url.startswith('s3://'):
+        return S3Sink(url, endpoint=s3_endpoint, region=s3_region, cache_control=cache_control)
+    raise ValueError(f"Unsupported upload destination '{url}' (expected gs:// or s3://)")
@@ -0,0 +1,116 @@
+"""
+Dataset validation.
+
+Checks an iam-data.json for the problems that would break the backend or
+edge builds, or silently produce wrong search results.
+"""
+
+# field -> expected type, for each record kind
+ROLE_SCHEMA = {
+    'name': str,
+    'title': str,
+    'description': str,
+    'stage': str,
+    'included_permissions': list,
+}
+PERMISSION_SCHEMA = {
+    'name': str,
+    'service': str,
+    'resource': str,
+    'action': str,
+    'granted_by_roles': list,
+}
+METADATA_SCHEMA = {
+    'total_roles': int,
+    'total_permissions': int,
+}
+
+# Fields that must not be empty strings
+ROLE_REQUIRED = ('name', 'title', 'stage')
+PERMISSION_REQUIRED = ('name', 'service')
+
+
+def _check_schema(record, schema, label, problems):
+    """Record missing or mistyped fields; returns False if the record is unusable."""
+    if not isinstance(record, dict):
+        problems.append(f"{label}: expected an object, got {type(record).__name__}")
+        return False
+    ok = True
+    for field, expected in schema.items():
+        if field not in record:
+            problems.append(f"{label}: missing field '{field}'")
+            ok = False
+        elif not isinstance(record[field], expected) or (expected is int and isinstance(record[field], bool)):
+            problems.append(f"{label}: field '{field}' should be {expected.__name__}, got {type(record[field]).__name__}")
+            ok = False
+    return ok
+
+
+def _check_required(record, fields, label, problems):
+    for field in fields:
+        if isinstance(record.get(field), str) and not record[field].strip():
+            problems.append(f"{label}: empty '{field}'")
+
+
+def _check_duplicates(names, kind, problems):
+    seen = set()
+    for name in names:
+        if name in seen:
+            problems.append(f"Duplicate {kind} '{name}'")
+        seen.add(name)
+
+
+def validate_dataset(dataset):
+    """All problems found in a dataset, as human-readable strings."""
+    problems = []
+    if not isinstance(dataset, dict):
+        return [f"Dataset should be an object, got {type(dataset).__name__}"]
+
+    for key, expected in (('roles', list), ('permissions', list), ('metadata', dict)):
+        if not isinstance(dataset.get(key), expected):
+            problems.append(f"Top-level '{key}' should be {expected.__name__}")
+    if problems:
+        return problems
+
+    roles = [
+        r for i, r in enumerate(dataset['roles'])
+        if _check_schema(r, ROLE_SCHEMA, f"roles[{i}] ({r.get('name', '?') if isinstance(r, dict) else '?'})", problems)
+    ]
+    permissions = [
+        p for i, p in enumerate(dataset['permissions'])
+        if _check_schema(p, PERMISSION_SCHEMA, f"permissions[{i}] ({p.get('name', '?') if isinstance(p, dict) else '?'})", problems)
+    ]
+
+    for role in roles:
+        _check_required(role, ROLE_REQUIRED, f"Role '{role['name']}'", problems)
+    for permission in permissions:
+        _check_required(permission, PERMISSION_REQUIRED, f"Permission '{permission['name']}'", problems)
+
+    _check_duplicates((r['name'] for r in roles), 'role', problems)
+    _check_duplicates((p['name'] for p in permissions), 'permission', problems)
+
+    # Referential integrity in both directions; names come from every record so
+    # one malformed entry isn't also reported as missing everywhere it's used
+    role_names = {r.get('name') for r in dataset['roles'] if isinstance(r, dict)}
+    permission_names = {p.get('name') for p in dataset['permissions'] if isinstance(p, dict)}
+    for role in roles:
+        for perm in role['included_permissions']:
+            if perm not in permission_names:
+                problems.append(f"Role '{role['name']}' includes unknown permission '{perm}'")
+    for permission in permissions:
+        for grant in permission['granted_by_roles']:
+            grant_name = grant.get('name') if isinstance(grant, dict) else grant
+            if grant_name not in role_names:
+                problems.append(f"Permission '{permission['name']}' is granted by unknown role '{grant_name}'")
+
+    metadata = dataset['metadata']
+    if _check_schema(metadata, METADATA_SCHEMA, "metadata", problems):
+        if metadata['total_roles'] != len(dataset['roles']):
+            problems.append(f"metadata.total_roles is {metadata['total_roles']} but the dataset has {len(dataset['roles'])} roles")
+        if metadata['total_permissions'] != len(dataset['permissions']):
+            problems.append(
+                f"metadata.total_permissions is {metadata['total_permissions']} "
+                f"but the dataset has {len(dataset['permissions'])} permissions"
+            )
+
+    return problems
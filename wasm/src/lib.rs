@@ -0,0 +1,57 @@
+//! WebAssembly bindings for offline search in the browser
+//!
+//! Wraps the shared `PrebuiltIndex` so the frontend can load the same rkyv
+//! archive the edge embeds (`index.bin`) and search it in place without any
+//! API round-trips. Results are returned as JSON strings matching the
+//! `permissions`/`roles` arrays of `/api/v1/search`.
+
+use gcpiam_core::search::SearchOptions;
+use gcpiam_core::index::IndexBuf;
+use wasm_bindgen::prelude::*;
+
+/// A search index loaded from prebuilt index bytes
+#[wasm_bindgen]
+pub struct WasmIndex {
+    index: IndexBuf,
+}
+
+#[wasm_bindgen]
+impl WasmIndex {
+    /// Load an index produced by `PrebuiltIndex::to_bytes`
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<WasmIndex, JsError> {
+        let index = IndexBuf::new(bytes)?;
+        Ok(WasmIndex { index })
+    }
+
+    /// Search permissions; returns a JSON array of permission results
+    #[wasm_bindgen(js_name = searchPermissions)]
+    pub fn search_permissions(&self, query: &str, mode: &str, limit: usize) -> Result<String, JsError> {
+        let results = self.index.index().search_permissions(query, mode, &options(limit));
+        Ok(serde_json::to_string(&results)?)
+    }
+
+    /// Search roles; returns a JSON array of role results
+    #[wasm_bindgen(js_name = searchRoles)]
+    pub fn search_roles(&self, query: &str, mode: &str, limit: usize) -> Result<String, JsError> {
+        let results = self.index.index().search_roles(query, mode, &options(limit));
+        Ok(serde_json::to_string(&results)?)
+    }
+
+    #[wasm_bindgen(getter, js_name = permissionCount)]
+    pub fn permission_count(&self) -> usize {
+        self.index.index().permissions.len()
+    }
+
+    #[wasm_bindgen(getter, js_name = roleCount)]
+    pub fn role_count(&self) -> usize {
+        self.index.index().roles.len()
+    }
+}
+
+fn options(limit: usize) -> SearchOptions {
+    SearchOptions {
+        limit,
+        ..SearchOptions::default()
+    }
+}